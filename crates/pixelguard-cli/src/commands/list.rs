@@ -47,6 +47,7 @@ pub async fn run(args: ListArgs) -> Result<()> {
                     "path": shot.path,
                     "waitFor": shot.wait_for,
                     "delay": shot.delay,
+                    "actions": shot.actions,
                 })
             })
             .collect();
@@ -71,6 +72,10 @@ pub async fn run(args: ListArgs) -> Result<()> {
             "📐 Viewport: {}x{}",
             config.viewport.width, config.viewport.height
         );
+        if !config.browsers.is_empty() {
+            let browsers: Vec<_> = config.browsers.iter().map(|b| b.as_str()).collect();
+            println!("🧭 Browsers: {}", browsers.join(", "));
+        }
     }
 
     Ok(())
@@ -0,0 +1,106 @@
+//! The `merge-results` command for combining sharded test runs.
+//!
+//! Each CI job runs `pixelguard test --shard i/n` and keeps its output
+//! directory. This command merges those directories' results, current and diff
+//! images into the configured output directory and generates one report.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Args;
+use pixelguard_core::{generate_report, merge_results, plugins};
+use tracing::info;
+
+use super::test::{print_diff_summary, run_notifier_plugins, run_reporter_plugins};
+
+/// Arguments for the merge-results command.
+#[derive(Args)]
+pub struct MergeResultsArgs {
+    /// Output directories of the shard runs (e.g. downloaded CI artifacts)
+    #[arg(required = true)]
+    dirs: Vec<PathBuf>,
+
+    /// Path to config file (default: pixelguard.config.json)
+    #[arg(long, short)]
+    config: Option<String>,
+
+    /// CI mode: machine-readable output, exit code 1 on diffs or failed shots
+    #[arg(long)]
+    ci: bool,
+}
+
+/// Runs the merge-results command.
+pub async fn run(args: MergeResultsArgs) -> Result<()> {
+    let working_dir = std::env::current_dir()?;
+    let config = super::load_config(&working_dir, args.config.as_deref())?;
+    let output_dir = working_dir.join(&config.output_dir);
+
+    let dirs: Vec<PathBuf> = args.dirs.iter().map(|dir| working_dir.join(dir)).collect();
+    let (diff_result, shards) = merge_results(&output_dir, &dirs)?;
+
+    if !args.ci {
+        println!("🧩 Merged results of {} run(s):", shards.len());
+        for merged in &shards {
+            match merged.shard {
+                Some(shard) => println!(
+                    "   • shard {}: {} screenshot(s) from {}",
+                    shard,
+                    merged.total,
+                    merged.dir.display()
+                ),
+                None => println!(
+                    "   • {} screenshot(s) from {}",
+                    merged.total,
+                    merged.dir.display()
+                ),
+            }
+        }
+    }
+
+    let plugin_registry = plugins::init_plugins(&config, &working_dir)?;
+    if !plugin_registry.is_empty() && !args.ci {
+        info!("🔌 Loaded {} plugin(s)", plugin_registry.len());
+    }
+
+    let report_path = generate_report(&config, &diff_result, &working_dir)?;
+    run_reporter_plugins(&config, &diff_result, &working_dir, &plugin_registry)?;
+
+    let failing = diff_result.has_changes() || diff_result.has_failures();
+    if args.ci {
+        let result = serde_json::json!({
+            "status": if failing { "fail" } else { "pass" },
+            "unchanged": diff_result.unchanged.len(),
+            "changed": diff_result.changed.len(),
+            "added": diff_result.added.len(),
+            "removed": diff_result.removed.len(),
+            "a11yChanged": diff_result.a11y_changed.len(),
+            "unstable": diff_result.unstable().len(),
+            "failed": diff_result.failed.len(),
+            "shards": shards.len(),
+            "report": report_path.to_string_lossy(),
+        });
+        println!("{}", serde_json::to_string(&result)?);
+    } else {
+        print_diff_summary(&diff_result);
+        println!("\n📊 View report: {}", report_path.display());
+
+        if diff_result.has_changes() {
+            println!("\n💡 To review the changes: pixelguard review");
+        }
+    }
+
+    run_notifier_plugins(
+        &diff_result,
+        Some(&report_path),
+        args.ci,
+        &working_dir,
+        &plugin_registry,
+    )?;
+
+    // Exit with code 1 if there are diffs or failed shots in CI mode
+    if args.ci && failing {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
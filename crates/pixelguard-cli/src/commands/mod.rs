@@ -4,21 +4,29 @@
 //! - `init`: Initialize Pixelguard configuration
 //! - `test`: Capture and compare screenshots
 //! - `list`: List configured shots
+//! - `merge_results`: Combine the results of sharded test runs
 //! - `plugins`: List and validate installed plugins
 //! - `validate`: Check environment prerequisites
 //! - `apply`: Apply decisions from exported JSON file
 //! - `review`: Interactively review visual diffs
 //! - `serve`: Serve an existing report with decisions API
+//! - `record_har`: Record network traffic per shot for HAR replay
 
+use std::future::Future;
 use std::path::Path;
 
 use anyhow::Result;
+use pixelguard_core::web_server::{kill_process_tree, start_web_server};
 use pixelguard_core::Config;
 
+use crate::static_server::StaticServer;
+
 pub mod apply;
 pub mod init;
 pub mod list;
+pub mod merge_results;
 pub mod plugins;
+pub mod record_har;
 pub mod review;
 pub mod serve;
 pub mod test;
@@ -31,3 +39,78 @@ pub fn load_config(working_dir: &Path, config_path: Option<&str>) -> Result<Conf
         None => Config::load_or_default(working_dir),
     }
 }
+
+/// Runs `task` against the config's dev server, stopping the server afterwards.
+///
+/// A `staticDir` is served on an ephemeral port and `task` gets a config whose
+/// `baseUrl` points at it. A `webServer` is started and torn down whether `task`
+/// succeeds, fails or is interrupted with Ctrl-C or SIGTERM. With neither, `task`
+/// simply runs.
+pub async fn with_dev_server<T, F, Fut>(
+    mut config: Config,
+    working_dir: &Path,
+    task: F,
+) -> Result<T>
+where
+    F: FnOnce(Config) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    if let Some(static_dir) = config.static_dir() {
+        if config.web_server.is_some() {
+            anyhow::bail!(
+                "❌ Both 'staticDir' (or source \"storybook-static\") and 'webServer' are set.\n\n\
+                 💡 Solution: Keep one: 'staticDir' serves a build, 'webServer' starts a dev server."
+            );
+        }
+        let server = StaticServer::start(&working_dir.join(static_dir)).await?;
+        config.base_url = server.url().to_string();
+        return task(config).await;
+    }
+
+    let Some(web_server) = config.web_server.clone() else {
+        return task(config).await;
+    };
+
+    // Dropping the startup future on a signal kills whatever it has spawned
+    let server = tokio::select! {
+        server = start_web_server(&web_server, &config.base_url, working_dir) => server?,
+        _ = shutdown_signal() => anyhow::bail!("❌ Interrupted while waiting for the web server"),
+    };
+
+    // Watch for signals on another task: `task` runs blocking steps (Node.js checks,
+    // image diffing) during which it can't be interrupted
+    let watcher = server.pid().map(|pid| {
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            kill_process_tree(pid);
+            eprintln!("\n❌ Interrupted; stopped the web server");
+            std::process::exit(130);
+        })
+    });
+
+    let result = task(config).await;
+
+    if let Some(watcher) = watcher {
+        watcher.abort();
+    }
+    server.stop().await;
+    result
+}
+
+/// Resolves when the process is asked to stop (Ctrl-C, or SIGTERM on Unix).
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+
+    let _ = tokio::signal::ctrl_c().await;
+}
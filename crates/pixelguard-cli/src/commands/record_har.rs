@@ -0,0 +1,91 @@
+//! The `record-har` command for recording network traffic per shot.
+//!
+//! This command loads each shot once and writes its network traffic to
+//! `.pixelguard/har/{shot}.har`. Route rules with a `har` path can then
+//! replay those recordings during `pixelguard test`.
+
+use std::path::Path;
+
+use anyhow::Result;
+use clap::Args;
+use pixelguard_core::capture::{record_hars_in_dir, HAR_DIR};
+use pixelguard_core::Config;
+
+use super::test::{discover_shots, merge_shots, validate_capture_environment};
+
+/// Arguments for the record-har command.
+#[derive(Args)]
+pub struct RecordHarArgs {
+    /// Path to config file (default: pixelguard.config.json)
+    #[arg(long, short)]
+    config: Option<String>,
+
+    /// Only record shots matching this pattern
+    #[arg(long)]
+    filter: Option<String>,
+}
+
+/// Runs the record-har command.
+pub async fn run(args: RecordHarArgs) -> Result<()> {
+    let working_dir = std::env::current_dir()?;
+
+    // Load config from custom path or default
+    let config = super::load_config(&working_dir, args.config.as_deref())?;
+
+    super::with_dev_server(config, &working_dir, |config| {
+        record(args, config, &working_dir)
+    })
+    .await
+}
+
+/// Records a HAR file for each shot.
+async fn record(args: RecordHarArgs, mut config: Config, working_dir: &Path) -> Result<()> {
+    // Discover shots the same way `pixelguard test` does
+    if config.is_storybook() && !config.base_url.is_empty() {
+        let discovered = discover_shots(&config, working_dir).await?;
+        config.shots = merge_shots(discovered, &config.shots);
+    }
+
+    if let Some(pattern) = &args.filter {
+        config.shots.retain(|shot| shot.name.contains(pattern));
+    }
+
+    if config.shots.is_empty() {
+        anyhow::bail!(
+            "❌ No shots to record.\n\n\
+             💡 Solutions:\n  \
+             • Use 'pixelguard list' to see all available shots\n  \
+             • Check the --filter pattern for typos"
+        );
+    }
+
+    validate_capture_environment(&config.effective_browsers())?;
+
+    println!(
+        "🎙️  Recording network traffic for {} shot(s)...",
+        config.shots.len()
+    );
+
+    let result = record_hars_in_dir(&config, working_dir).await?;
+
+    for failed in &result.failed {
+        eprintln!("   ❌ {}: {}", failed.name, failed.error);
+    }
+
+    println!(
+        "\n✅ Recorded {} HAR file(s) in {}/{}/",
+        result.captured.len(),
+        config.output_dir,
+        HAR_DIR
+    );
+    println!(
+        "💡 Tip: Replay them with a route rule: {{ \"url\": \"**/api/**\", \"har\": \"{}/{}/{{shot}}.har\" }}",
+        config.output_dir, HAR_DIR
+    );
+
+    if !result.failed.is_empty() {
+        anyhow::bail!("❌ {} shot(s) failed to record", result.failed.len());
+    }
+
+    Ok(())
+}
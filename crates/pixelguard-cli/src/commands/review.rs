@@ -8,7 +8,7 @@ use std::path::PathBuf;
 use anyhow::{Context, Result};
 use clap::Args;
 use dialoguer::{theme::ColorfulTheme, Confirm, Select};
-use pixelguard_core::{capture::update_baseline, plugins};
+use pixelguard_core::{a11y::A11yChangedShot, capture::update_baseline, plugins};
 use serde::Deserialize;
 use tracing::info;
 
@@ -44,6 +44,9 @@ struct ResultsSummary {
     passed: bool,
     /// Number of changed shots
     changed: usize,
+    /// Number of shots whose accessibility tree changed
+    #[serde(default, rename = "a11yChanged")]
+    a11y_changed: usize,
 }
 
 /// Detailed results by category.
@@ -51,6 +54,9 @@ struct ResultsSummary {
 struct ResultsDetail {
     /// Changed shots with diff info
     changed: Vec<ChangedShot>,
+    /// Shots whose accessibility tree changed
+    #[serde(default, rename = "a11yChanged")]
+    a11y_changed: Vec<A11yChangedShot>,
 }
 
 /// A changed shot.
@@ -64,10 +70,43 @@ struct ChangedShot {
     /// Viewport name (if multi-viewport)
     #[allow(dead_code)]
     viewport: Option<String>,
+    /// Browser name (if multi-browser)
+    #[allow(dead_code)]
+    browser: Option<String>,
     /// Path to diff image
     diff_path: String,
 }
 
+/// A shot to review: its pixel diff, its accessibility tree changes, or both.
+struct ReviewItem<'a> {
+    /// Name of the shot
+    name: &'a str,
+    /// The pixel diff, if the screenshot changed
+    changed: Option<&'a ChangedShot>,
+    /// The accessibility tree changes, if any
+    a11y: Option<&'a A11yChangedShot>,
+}
+
+/// Lists the changed screenshots, then those whose accessibility tree alone changed.
+fn review_items(results: &ResultsDetail) -> Vec<ReviewItem<'_>> {
+    let a11y = |name: &str| results.a11y_changed.iter().find(|shot| shot.name == name);
+    let changed = results.changed.iter().map(|shot| ReviewItem {
+        name: &shot.name,
+        changed: Some(shot),
+        a11y: a11y(&shot.name),
+    });
+    let a11y_only = results
+        .a11y_changed
+        .iter()
+        .filter(|shot| !results.changed.iter().any(|c| c.name == shot.name))
+        .map(|shot| ReviewItem {
+            name: &shot.name,
+            changed: None,
+            a11y: Some(shot),
+        });
+    changed.chain(a11y_only).collect()
+}
+
 /// Action the user can take for each shot.
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum ReviewAction {
@@ -123,16 +162,17 @@ pub async fn run(args: ReviewArgs) -> Result<()> {
     })?;
 
     // Check if there are changes to review
-    if results.summary.passed || results.summary.changed == 0 {
+    if results.summary.passed || (results.summary.changed == 0 && results.summary.a11y_changed == 0)
+    {
         println!("✅ All tests passed! No changes to review.");
         println!("\n💡 Tip: Visual regression tests are passing. Great work!");
         return Ok(());
     }
 
-    let changed = &results.results.changed;
+    let items = review_items(&results.results);
     println!(
         "\n🔍 Found {} changed screenshot(s) to review\n",
-        changed.len()
+        items.len()
     );
 
     let theme = ColorfulTheme::default();
@@ -143,23 +183,40 @@ pub async fn run(args: ReviewArgs) -> Result<()> {
     let output_dir = working_dir.join(&config.output_dir);
 
     // Review each changed shot
-    for (i, shot) in changed.iter().enumerate() {
+    for (i, item) in items.iter().enumerate() {
         loop {
+            let mut details = Vec::new();
+            if let Some(shot) = item.changed {
+                details.push(format!("{:.2}% different", shot.diff_percentage));
+            }
+            if let Some(shot) = item.a11y {
+                details.push(format!(
+                    "{} accessibility tree change(s)",
+                    shot.changes.len()
+                ));
+            }
             println!(
-                "\n[{}/{}] {} ({:.2}% different)",
+                "\n[{}/{}] {} ({})",
                 i + 1,
-                changed.len(),
-                shot.name,
-                shot.diff_percentage
+                items.len(),
+                item.name,
+                details.join(", ")
             );
+            if let Some(shot) = item.a11y {
+                for change in &shot.changes {
+                    println!("    {}", change);
+                }
+            }
 
-            let actions = vec![
+            let mut actions = vec![
                 ReviewAction::Approve,
                 ReviewAction::Reject,
                 ReviewAction::Skip,
-                ReviewAction::ViewDiff,
-                ReviewAction::Quit,
             ];
+            if item.changed.is_some() {
+                actions.push(ReviewAction::ViewDiff);
+            }
+            actions.push(ReviewAction::Quit);
 
             let selection = Select::with_theme(&theme)
                 .with_prompt("What would you like to do?")
@@ -169,21 +226,24 @@ pub async fn run(args: ReviewArgs) -> Result<()> {
 
             match actions[selection] {
                 ReviewAction::Approve => {
-                    approved.push(shot.name.clone());
-                    println!("  \u{2713} Approved: {}", shot.name);
+                    approved.push(item.name.to_string());
+                    println!("  \u{2713} Approved: {}", item.name);
                     break;
                 }
                 ReviewAction::Reject => {
-                    rejected.push(shot.name.clone());
-                    println!("  \u{2717} Rejected: {}", shot.name);
+                    rejected.push(item.name.to_string());
+                    println!("  \u{2717} Rejected: {}", item.name);
                     break;
                 }
                 ReviewAction::Skip => {
-                    skipped.push(shot.name.clone());
-                    println!("  \u{2192} Skipped: {}", shot.name);
+                    skipped.push(item.name.to_string());
+                    println!("  \u{2192} Skipped: {}", item.name);
                     break;
                 }
                 ReviewAction::ViewDiff => {
+                    let Some(shot) = item.changed else {
+                        continue;
+                    };
                     let diff_path = output_dir.join(&shot.diff_path);
                     if diff_path.exists() {
                         if let Err(e) = open::that(&diff_path) {
@@ -199,8 +259,8 @@ pub async fn run(args: ReviewArgs) -> Result<()> {
                 ReviewAction::Quit => {
                     println!("\nQuitting review...");
                     // Treat remaining as skipped
-                    for remaining in changed.iter().skip(i) {
-                        skipped.push(remaining.name.clone());
+                    for remaining in items.iter().skip(i) {
+                        skipped.push(remaining.name.to_string());
                     }
                     break;
                 }
@@ -208,7 +268,7 @@ pub async fn run(args: ReviewArgs) -> Result<()> {
         }
 
         // Check if user quit
-        if skipped.len() > changed.len() - i - 1 {
+        if skipped.len() > items.len() - i - 1 {
             break;
         }
     }
@@ -4,6 +4,7 @@
 //! compares them against the baseline, and generates an HTML report.
 //! Supports plugins for capture, diff, report, and notification.
 
+use std::collections::HashSet;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -18,16 +19,16 @@ use axum::{
 };
 use clap::Args;
 use pixelguard_core::{
-    capture::{capture_screenshots_in_dir, update_baseline},
-    config::Shot,
+    affected::{carry_forward, changed_files, select_affected},
+    capture::{update_baseline, CaptureEvent, CaptureResult},
+    config::{Browser, Shot},
     diff::{diff_images, has_baseline, DiffResult},
-    fetch_storybook_stories, generate_report,
+    fetch_storybook_stories, generate_report, load_storybook_stories,
     plugins::{
-        self, CaptureInput, CaptureOutput, CaptureShot, CaptureViewport, NotifierInput,
-        PluginCategory, PluginRegistry, ReporterChangedShot, ReporterConfig, ReporterDiffResult,
-        ReporterInput,
+        self, NotifierInput, PluginRegistry, ReporterChangedShot, ReporterConfig,
+        ReporterDiffResult, ReporterInput,
     },
-    Config,
+    select_backend, CaptureBackend, Config, FailureArtifacts, Shard,
 };
 use tower_http::services::ServeDir;
 use tracing::info;
@@ -47,7 +48,7 @@ pub struct TestArgs {
     #[arg(long, value_delimiter = ',')]
     update_only: Option<Vec<String>>,
 
-    /// CI mode: machine-readable output, exit code 1 on diffs
+    /// CI mode: machine-readable output, exit code 1 on diffs or failed shots
     #[arg(long)]
     ci: bool,
 
@@ -55,6 +56,16 @@ pub struct TestArgs {
     #[arg(long)]
     filter: Option<String>,
 
+    /// Only test shots affected by changes since this git ref (e.g. origin/main);
+    /// the baselines of the others are carried forward as unchanged
+    #[arg(long, value_name = "GIT_REF")]
+    changed_since: Option<String>,
+
+    /// Only test one part of the shot × viewport combinations, e.g. 2/4
+    /// (combine the parts with 'pixelguard merge-results')
+    #[arg(long)]
+    shard: Option<Shard>,
+
     /// Show detailed progress
     #[arg(long)]
     verbose: bool,
@@ -73,7 +84,25 @@ pub async fn run(args: TestArgs) -> Result<()> {
     let working_dir = std::env::current_dir()?;
 
     // Load config from custom path or default
-    let mut config = super::load_config(&working_dir, args.config.as_deref())?;
+    let config = super::load_config(&working_dir, args.config.as_deref())?;
+
+    // Shot discovery and capture both need the dev server, so start it first
+    let failed = super::with_dev_server(config, &working_dir, |config| {
+        run_tests(args, config, &working_dir)
+    })
+    .await?;
+
+    // Exit with code 1 if there are diffs in CI mode
+    if failed {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Captures, compares and reports. Returns true if CI mode should fail the run.
+async fn run_tests(args: TestArgs, mut config: Config, working_dir: &Path) -> Result<bool> {
+    let working_dir = working_dir.to_path_buf();
 
     // Initialize plugins
     let plugin_registry = plugins::init_plugins(&config, &working_dir)?;
@@ -82,8 +111,8 @@ pub async fn run(args: TestArgs) -> Result<()> {
     }
 
     // Dynamically discover shots if source is storybook and no shots configured
-    if config.source == "storybook" && !config.base_url.is_empty() {
-        let discovered = discover_shots(&config).await?;
+    if config.is_storybook() && !config.base_url.is_empty() {
+        let discovered = discover_shots(&config, &working_dir).await?;
         if discovered.is_empty() {
             anyhow::bail!(
                 "❌ Could not discover any stories from Storybook at {}\n\n\
@@ -148,20 +177,88 @@ pub async fn run(args: TestArgs) -> Result<()> {
         }
     }
 
+    // Only capture shots whose modules changed since the ref
+    if let Some(git_ref) = &args.changed_since {
+        let changed = changed_files(&working_dir, git_ref)?;
+        let selection = select_affected(&config, &working_dir, &changed)?;
+
+        if !args.ci {
+            println!(
+                "🔀 {} file(s) changed since {}: testing {} of {} shots",
+                changed.len(),
+                git_ref,
+                selection.affected.len(),
+                config.shots.len()
+            );
+            if selection.untraced > 0 {
+                println!(
+                    "   {} of them have no importPath to trace and are always tested",
+                    selection.untraced
+                );
+            }
+        }
+
+        let affected: HashSet<&str> = selection.affected.iter().map(String::as_str).collect();
+        config
+            .shots
+            .retain(|shot| affected.contains(shot.name.as_str()));
+        config.carried_forward = selection.unaffected.into_iter().collect();
+    }
+
+    if let Some(shard) = args.shard {
+        let original_count = config.shots.len();
+        config.set_shard(shard)?;
+
+        if !args.ci {
+            println!(
+                "🧩 Shard {}: {} of {} shots",
+                shard,
+                config.shots.len(),
+                original_count
+            );
+        }
+    }
+
     let shot_count = config.shots.len();
 
     // Early validation: Check Node.js and Playwright before capturing
-    // Skip if using a capture plugin (it handles its own dependencies)
-    if !plugin_registry.has_override(PluginCategory::Capture) {
-        validate_capture_environment()?;
+    // Skip if the backend doesn't run Playwright (a capture plugin handles its own
+    // dependencies, fixtures need none) or if --changed-since left nothing to capture
+    let backend = select_backend(&config, &working_dir, &plugin_registry)?;
+    if backend.needs_playwright() && shot_count > 0 {
+        validate_capture_environment(&config.effective_browsers())?;
     }
 
     if !args.ci {
         println!("📸 Capturing {} screenshots...", shot_count);
     }
 
-    // Capture screenshots (using plugin if available)
-    let capture_result = capture_with_plugin(&config, &working_dir, &plugin_registry).await?;
+    // Capture screenshots with the configured backend
+    let capture_started = std::time::Instant::now();
+    let capture_result = capture(backend.as_ref(), &config, &working_dir, args.ci).await?;
+    let capture_ms = capture_started.elapsed().as_millis();
+
+    if !args.ci {
+        let slowest = slowest_shots(&capture_result, 3);
+        if capture_result.total() > slowest.len() && !slowest.is_empty() {
+            println!(
+                "\n⏱️  Captured in {:.1}s. Slowest shots:",
+                capture_ms as f64 / 1000.0
+            );
+            for (name, ms) in slowest {
+                println!("   • {}{}", name, format_duration(Some(ms)));
+            }
+        }
+    }
+
+    if let Some(error) = &capture_result.error {
+        eprintln!(
+            "\n⚠️  Warning: capture stopped early; continuing with the {} shot(s) captured before it did.",
+            capture_result.total()
+        );
+        eprintln!("   {}", error);
+        eprintln!();
+    }
 
     if !capture_result.failed.is_empty() {
         eprintln!(
@@ -170,11 +267,66 @@ pub async fn run(args: TestArgs) -> Result<()> {
         );
         for failed in &capture_result.failed {
             eprintln!("   ❌ {}: {}", failed.name, failed.error);
+            for artifact in &failed.artifacts {
+                eprintln!(
+                    "      📎 {}",
+                    Path::new(&config.output_dir).join(artifact).display()
+                );
+            }
+        }
+        if backend.needs_playwright() {
+            eprintln!("\n💡 Tip: Check that your dev server is running and URLs are correct.");
+            if config.failure_artifacts == FailureArtifacts::Off {
+                eprintln!("💡 Tip: Set failureArtifacts to \"page\" or \"trace\" to save the page HTML, a screenshot and a Playwright trace of failed shots.");
+            }
+        }
+        eprintln!();
+    }
+
+    if !capture_result.unstable.is_empty() && !args.ci {
+        eprintln!(
+            "\n〰️  Warning: {} shot(s) never stopped changing:",
+            capture_result.unstable.len()
+        );
+        for unstable in &capture_result.unstable {
+            if let Some(stability) = unstable.stability {
+                eprintln!(
+                    "   〰️  {}: {} attempts in {}ms",
+                    unstable.name, stability.attempts, stability.duration_ms
+                );
+            }
+        }
+        eprintln!(
+            "\n💡 Tip: Mask or stabilize the animated parts, or raise stableCapture.timeout."
+        );
+        eprintln!();
+    }
+
+    let page_errors: Vec<_> = capture_result.with_page_errors().collect();
+    if !page_errors.is_empty() && !args.ci {
+        eprintln!(
+            "\n🐞 Warning: {} shot(s) logged page errors:",
+            page_errors.len()
+        );
+        for (name, errors) in &page_errors {
+            eprintln!("   🐞 {}:", name);
+            for (kind, message) in errors.iter() {
+                eprintln!("      {}: {}", kind, message);
+            }
         }
-        eprintln!("\n💡 Tip: Check that your dev server is running and URLs are correct.");
+        eprintln!("\n💡 Tip: Set pageErrors.fail to fail these shots, or pageErrors.allow to ignore known noise.");
         eprintln!();
     }
 
+    // Fill in the screenshots --changed-since didn't capture from the baseline
+    let carried = carry_forward(&config, &working_dir)?;
+    if carried > 0 && !args.ci {
+        println!(
+            "⏩ Carried forward {} unchanged screenshot(s) from the baseline",
+            carried
+        );
+    }
+
     // Handle --update or --update-only flag
     let should_update = args.update || args.update_only.is_some();
 
@@ -196,7 +348,7 @@ pub async fn run(args: TestArgs) -> Result<()> {
             println!("💡 Tip: Commit .pixelguard/baseline/ to your version control.");
         }
 
-        return Ok(());
+        return Ok(false);
     }
 
     // Check if baseline exists
@@ -208,7 +360,7 @@ pub async fn run(args: TestArgs) -> Result<()> {
             );
             // In CI mode, no baseline means first run - exit successfully
             // User should run with --update to create baseline
-            return Ok(());
+            return Ok(false);
         }
 
         println!(
@@ -220,7 +372,7 @@ pub async fn run(args: TestArgs) -> Result<()> {
              2️⃣ Run 'pixelguard test' to detect visual changes\n  \
              3️⃣ Use 'pixelguard review' for interactive approval"
         );
-        return Ok(());
+        return Ok(false);
     }
 
     // Compare against baseline
@@ -228,7 +380,8 @@ pub async fn run(args: TestArgs) -> Result<()> {
         println!("🔍 Comparing against baseline...");
     }
 
-    let diff_result = diff_images(&config, &working_dir, Some(&plugin_registry))?;
+    let mut diff_result = diff_images(&config, &working_dir, Some(&plugin_registry))?;
+    diff_result.record_failed(&capture_result.failed);
 
     // Generate built-in report
     let report_path = generate_report(&config, &diff_result, &working_dir)?;
@@ -237,51 +390,32 @@ pub async fn run(args: TestArgs) -> Result<()> {
     run_reporter_plugins(&config, &diff_result, &working_dir, &plugin_registry)?;
 
     // Output results
+    let failing = diff_result.has_changes() || diff_result.has_failures();
     if args.ci {
-        let result = serde_json::json!({
-            "status": if diff_result.has_changes() { "fail" } else { "pass" },
+        let mut result = serde_json::json!({
+            "status": if failing { "fail" } else { "pass" },
             "unchanged": diff_result.unchanged.len(),
             "changed": diff_result.changed.len(),
             "added": diff_result.added.len(),
             "removed": diff_result.removed.len(),
+            "a11yChanged": diff_result.a11y_changed.len(),
+            "unstable": diff_result.unstable().len(),
+            "failed": diff_result.failed.len(),
+            "pageErrors": diff_result.page_errors.len(),
+            "captureMs": capture_ms,
             "report": report_path.to_string_lossy(),
         });
+        if let Some(shard) = args.shard {
+            result["shard"] = shard.to_string().into();
+        }
         println!("{}", serde_json::to_string(&result)?);
 
-        // Exit with code 1 if there are diffs
-        if diff_result.has_changes() {
-            std::process::exit(1);
+        // Exit with code 1 if there are diffs or failed shots
+        if failing {
+            return Ok(true);
         }
     } else {
-        println!();
-
-        if !diff_result.unchanged.is_empty() {
-            println!("✅ {} unchanged", diff_result.unchanged.len());
-        }
-
-        if !diff_result.changed.is_empty() {
-            println!("❌ {} changed", diff_result.changed.len());
-            for shot in &diff_result.changed {
-                println!(
-                    "   └─ {} ({:.2}% different)",
-                    shot.name, shot.diff_percentage
-                );
-            }
-        }
-
-        if !diff_result.added.is_empty() {
-            println!("➕ {} added", diff_result.added.len());
-            for name in &diff_result.added {
-                println!("   └─ {}", name);
-            }
-        }
-
-        if !diff_result.removed.is_empty() {
-            println!("➖ {} removed", diff_result.removed.len());
-            for name in &diff_result.removed {
-                println!("   └─ {}", name);
-            }
-        }
+        print_diff_summary(&diff_result);
 
         if args.serve {
             println!("\n🚀 Starting server...");
@@ -294,14 +428,16 @@ pub async fn run(args: TestArgs) -> Result<()> {
         }
     }
 
-    // Run notifier plugins
-    run_notifier_plugins(
-        &diff_result,
-        Some(&report_path),
-        args.ci,
-        &working_dir,
-        &plugin_registry,
-    )?;
+    // Run notifier plugins, once for the merged results of a sharded run
+    if args.shard.is_none() {
+        run_notifier_plugins(
+            &diff_result,
+            Some(&report_path),
+            args.ci,
+            &working_dir,
+            &plugin_registry,
+        )?;
+    }
 
     // Serve the report if requested
     if args.serve && !args.ci {
@@ -309,76 +445,143 @@ pub async fn run(args: TestArgs) -> Result<()> {
         serve_report(&output_dir, args.port).await?;
     }
 
-    Ok(())
+    Ok(false)
 }
 
-/// Captures screenshots using a plugin if available, otherwise uses built-in capture.
-async fn capture_with_plugin(
+/// Captures screenshots with `backend`, printing a line per shot as it finishes
+/// unless `ci` is set.
+async fn capture(
+    backend: &dyn CaptureBackend,
     config: &Config,
     working_dir: &Path,
-    registry: &PluginRegistry,
-) -> Result<pixelguard_core::capture::CaptureResult> {
-    if let Some(plugin) = registry.get(PluginCategory::Capture) {
-        info!("🔌 Using capture plugin: {}", plugin.name());
-
-        let output_dir = working_dir
-            .join(&config.output_dir)
-            .join("current")
-            .to_string_lossy()
-            .to_string();
-
-        // Create output directory
-        std::fs::create_dir_all(working_dir.join(&config.output_dir).join("current"))?;
-
-        let input = CaptureInput {
-            shots: config
-                .shots
-                .iter()
-                .map(|s| CaptureShot {
-                    name: s.name.clone(),
-                    path: s.path.clone(),
-                    wait_for: s.wait_for.clone(),
-                    delay: s.delay,
-                })
-                .collect(),
-            base_url: config.base_url.clone(),
-            viewport: CaptureViewport {
-                width: config.viewport.width,
-                height: config.viewport.height,
-            },
-            output_dir,
-            options: serde_json::json!({}),
-        };
-
-        let output: CaptureOutput =
-            plugins::executor::execute_hook(plugin, "capture", &input, working_dir)?;
-
-        Ok(pixelguard_core::capture::CaptureResult {
-            captured: output
-                .captured
-                .into_iter()
-                .map(|s| pixelguard_core::capture::CapturedShot {
-                    name: s.name,
-                    path: std::path::PathBuf::from(s.path),
-                })
-                .collect(),
-            failed: output
-                .failed
-                .into_iter()
-                .map(|s| pixelguard_core::capture::FailedShot {
-                    name: s.name,
-                    error: s.error,
-                })
-                .collect(),
+    ci: bool,
+) -> Result<CaptureResult> {
+    let mut total = 0;
+    let mut done = 0;
+    backend
+        .capture(config, working_dir, &mut |event| match event {
+            CaptureEvent::Started { total: count } => total = *count,
+            CaptureEvent::Captured(shot) if !ci => {
+                done += 1;
+                let icon = if shot.stability.is_some_and(|s| !s.stable) {
+                    "〰️"
+                } else {
+                    "✅"
+                };
+                println!(
+                    "   [{}/{}] {} {}{}",
+                    done,
+                    total,
+                    icon,
+                    shot.name,
+                    format_duration(shot.duration_ms)
+                );
+            }
+            CaptureEvent::Failed(shot) if !ci => {
+                done += 1;
+                println!(
+                    "   [{}/{}] ❌ {}{}",
+                    done,
+                    total,
+                    shot.name,
+                    format_duration(shot.duration_ms)
+                );
+            }
+            _ => {}
         })
-    } else {
-        // Use built-in capture
-        capture_screenshots_in_dir(config, working_dir).await
+        .await
+}
+
+/// Formats a shot's capture time for progress output, e.g. ` (1.2s)`.
+fn format_duration(duration_ms: Option<u64>) -> String {
+    duration_ms
+        .map(|ms| format!(" ({:.1}s)", ms as f64 / 1000.0))
+        .unwrap_or_default()
+}
+
+/// Returns up to `count` of the slowest captured or failed shots, slowest first.
+fn slowest_shots(result: &CaptureResult, count: usize) -> Vec<(&str, u64)> {
+    let mut timings: Vec<(&str, u64)> = result
+        .captured
+        .iter()
+        .chain(&result.unstable)
+        .filter_map(|s| Some((s.name.as_str(), s.duration_ms?)))
+        .chain(
+            result
+                .failed
+                .iter()
+                .filter_map(|s| Some((s.name.as_str(), s.duration_ms?))),
+        )
+        .collect();
+    timings.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    timings.truncate(count);
+    timings
+}
+
+/// Prints the unchanged, changed, added and removed screenshots.
+pub(crate) fn print_diff_summary(diff_result: &DiffResult) {
+    println!();
+
+    if !diff_result.unchanged.is_empty() {
+        println!("✅ {} unchanged", diff_result.unchanged.len());
+    }
+
+    if !diff_result.changed.is_empty() {
+        println!("❌ {} changed", diff_result.changed.len());
+        for shot in &diff_result.changed {
+            match &shot.size_change {
+                Some(size) => println!(
+                    "   └─ {} (resized {}, {:.2}% different)",
+                    shot.name, size, shot.diff_percentage
+                ),
+                None => println!(
+                    "   └─ {} ({:.2}% different)",
+                    shot.name, shot.diff_percentage
+                ),
+            }
+            for change in shot.dom_changes.iter().take(3) {
+                println!("      • {}: {}", change.path, change.details().join(", "));
+            }
+            if shot.dom_changes.len() > 3 {
+                println!(
+                    "      • ... and {} more element(s), see the report",
+                    shot.dom_changes.len() - 3
+                );
+            }
+        }
+    }
+
+    if !diff_result.added.is_empty() {
+        println!("➕ {} added", diff_result.added.len());
+        for name in &diff_result.added {
+            println!("   └─ {}", name);
+        }
+    }
+
+    if !diff_result.removed.is_empty() {
+        println!("➖ {} removed", diff_result.removed.len());
+        for name in &diff_result.removed {
+            println!("   └─ {}", name);
+        }
+    }
+
+    if !diff_result.a11y_changed.is_empty() {
+        println!(
+            "♿ {} accessibility tree(s) changed",
+            diff_result.a11y_changed.len()
+        );
+        for shot in &diff_result.a11y_changed {
+            println!(
+                "   └─ {} ({} node change(s))",
+                shot.name,
+                shot.changes.len()
+            );
+        }
     }
 }
 
 /// Runs all registered reporter plugins.
-fn run_reporter_plugins(
+pub(crate) fn run_reporter_plugins(
     config: &Config,
     diff_result: &DiffResult,
     working_dir: &Path,
@@ -415,7 +618,7 @@ fn run_reporter_plugins(
 }
 
 /// Runs all registered notifier plugins.
-fn run_notifier_plugins(
+pub(crate) fn run_notifier_plugins(
     diff_result: &DiffResult,
     report_path: Option<&Path>,
     ci_mode: bool,
@@ -460,6 +663,7 @@ fn convert_diff_result(diff_result: &DiffResult) -> ReporterDiffResult {
             .collect(),
         added: diff_result.added.clone(),
         removed: diff_result.removed.clone(),
+        a11y_changed: diff_result.a11y_changed.clone(),
     }
 }
 
@@ -539,17 +743,23 @@ async fn load_decisions(
 }
 
 /// Discovers shots dynamically from the source (e.g., Storybook).
-async fn discover_shots(config: &Config) -> Result<Vec<Shot>> {
+///
+/// Stories come from `index.json` on disk for a static build, or over HTTP from
+/// a running Storybook.
+pub(crate) async fn discover_shots(config: &Config, working_dir: &Path) -> Result<Vec<Shot>> {
     match config.source.as_str() {
-        "storybook" => {
-            let stories = fetch_storybook_stories(&config.base_url)
-                .await
-                .ok_or_else(|| {
-                    anyhow::anyhow!(
-                        "Failed to fetch stories from Storybook at {}. Is it running?",
-                        config.base_url
-                    )
-                })?;
+        "storybook" | "storybook-static" => {
+            let stories = match config.static_dir() {
+                Some(static_dir) => load_storybook_stories(&working_dir.join(static_dir))?,
+                None => fetch_storybook_stories(&config.base_url)
+                    .await
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Failed to fetch stories from Storybook at {}. Is it running?",
+                            config.base_url
+                        )
+                    })?,
+            };
 
             // Apply include/exclude filters
             let filtered: Vec<Shot> = stories
@@ -598,8 +808,9 @@ fn glob_match(pattern: &str, name: &str) -> bool {
 
 /// Merges discovered shots with config overrides.
 ///
-/// Config shots can provide custom waitFor, delay, or completely override a shot.
-fn merge_shots(discovered: Vec<Shot>, overrides: &[Shot]) -> Vec<Shot> {
+/// Config shots can provide custom waitFor, delay, additional actions, or completely
+/// override a shot.
+pub(crate) fn merge_shots(discovered: Vec<Shot>, overrides: &[Shot]) -> Vec<Shot> {
     discovered
         .into_iter()
         .map(|mut shot| {
@@ -612,6 +823,53 @@ fn merge_shots(discovered: Vec<Shot>, overrides: &[Shot]) -> Vec<Shot> {
                 if override_shot.delay.is_some() {
                     shot.delay = override_shot.delay;
                 }
+                if override_shot.selector.is_some() {
+                    shot.selector = override_shot.selector.clone();
+                }
+                if override_shot.padding.is_some() {
+                    shot.padding = override_shot.padding;
+                }
+                if !override_shot.viewports.is_empty() {
+                    shot.viewports = override_shot.viewports.clone();
+                }
+                if !override_shot.browsers.is_empty() {
+                    shot.browsers = override_shot.browsers.clone();
+                }
+                if override_shot.full_page.is_some() {
+                    shot.full_page = override_shot.full_page;
+                }
+                if override_shot.stabilize.is_some() {
+                    shot.stabilize = override_shot.stabilize.clone();
+                }
+                shot.mask.extend(override_shot.mask.iter().cloned());
+                shot.ignore_regions
+                    .extend(override_shot.ignore_regions.iter().copied());
+                shot.actions.extend(override_shot.actions.iter().cloned());
+                shot.routes.extend(override_shot.routes.iter().cloned());
+                if override_shot.stable_capture.is_some() {
+                    shot.stable_capture = override_shot.stable_capture;
+                }
+                if override_shot.ready.is_some() {
+                    shot.ready = override_shot.ready.clone();
+                }
+                if override_shot.timeouts.is_some() {
+                    shot.timeouts = override_shot.timeouts;
+                }
+                if override_shot.page_errors.is_some() {
+                    shot.page_errors = override_shot.page_errors.clone();
+                }
+                if override_shot.a11y.is_some() {
+                    shot.a11y = override_shot.a11y;
+                }
+                if override_shot.dom_snapshot.is_some() {
+                    shot.dom_snapshot = override_shot.dom_snapshot;
+                }
+                if override_shot.auth.is_some() {
+                    shot.auth = override_shot.auth.clone();
+                }
+                if shot.import_path.is_none() {
+                    shot.import_path = override_shot.import_path.clone();
+                }
                 // Path override is intentionally not applied - use discovered path
             }
             shot
@@ -619,11 +877,12 @@ fn merge_shots(discovered: Vec<Shot>, overrides: &[Shot]) -> Vec<Shot> {
         .collect()
 }
 
-/// Validates that Node.js and Playwright are available before attempting capture.
+/// Validates that Node.js and Playwright are available before attempting capture,
+/// naming `browsers` in the install hint.
 ///
 /// This provides a clear error message upfront rather than failing mid-capture
 /// with a confusing "command not found" error.
-fn validate_capture_environment() -> Result<()> {
+pub(crate) fn validate_capture_environment(browsers: &[Browser]) -> Result<()> {
     // Check Node.js
     Command::new("node").arg("--version").output().context(
         "❌ Node.js is required for screenshot capture but was not found.\n\n\
@@ -643,13 +902,19 @@ fn validate_capture_environment() -> Result<()> {
         || Path::new("node_modules/playwright").exists();
 
     if !has_playwright {
+        let browsers = browsers
+            .iter()
+            .map(Browser::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
         anyhow::bail!(
             "❌ Playwright is required for screenshot capture but was not found.\n\n\
              💡 Installation steps:\n  \
              1️⃣ Install Playwright: npm install -D @playwright/test\n  \
-             2️⃣ Install browsers: npx playwright install chromium\n\n  \
-             📝 Note: Chromium is the only browser required for screenshots.\n  \
-             🔗 Docs: https://playwright.dev/docs/intro"
+             2️⃣ Install browsers: npx playwright install {}\n\n  \
+             📝 Note: Only the browsers in `browsers` of pixelguard.config.json are needed.\n  \
+             🔗 Docs: https://playwright.dev/docs/intro",
+            browsers
         );
     }
 
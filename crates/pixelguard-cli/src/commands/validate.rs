@@ -4,10 +4,13 @@
 //! Pixelguard tests: config exists, Node.js is available, Playwright
 //! is installed, and optionally checks if the base URL is reachable.
 
+use std::path::Path;
 use std::process::Command;
 
 use anyhow::Result;
 use clap::Args;
+use pixelguard_core::web_server::{check_browser_endpoint, check_url, Reachability};
+use pixelguard_core::{load_storybook_stories, Config, WebServer};
 
 /// Arguments for the validate command.
 #[derive(Args)]
@@ -20,7 +23,7 @@ pub struct ValidateArgs {
     #[arg(long)]
     json: bool,
 
-    /// Skip checking if base URL is reachable
+    /// Skip checking if the base URL and browser endpoint are reachable
     #[arg(long)]
     skip_url_check: bool,
 }
@@ -81,7 +84,11 @@ pub async fn run(args: ValidateArgs) -> Result<()> {
     // Check 4: Base URL (if config loaded and not skipped)
     if !args.skip_url_check {
         if let Some(ref cfg) = config {
-            if !cfg.base_url.is_empty() {
+            if let Some(static_dir) = cfg.static_dir() {
+                checks.push(check_static_dir(cfg, &working_dir.join(static_dir)));
+            } else if let Some(ref web_server) = cfg.web_server {
+                checks.push(check_web_server(web_server, &cfg.base_url).await);
+            } else if !cfg.base_url.is_empty() {
                 checks.push(check_base_url(&cfg.base_url).await);
             }
         }
@@ -89,12 +96,12 @@ pub async fn run(args: ValidateArgs) -> Result<()> {
 
     // Check 5: Shots configured
     if let Some(ref cfg) = config {
-        if cfg.shots.is_empty() && cfg.source != "storybook" {
+        if cfg.shots.is_empty() && !cfg.is_storybook() {
             checks.push(CheckResult::fail(
                 "shots",
                 "❌ No shots configured.\n\n💡 Solutions:\n  • Run 'pixelguard init' to auto-detect shots\n  • Or add shots manually to pixelguard.config.json",
             ));
-        } else if cfg.source == "storybook" {
+        } else if cfg.is_storybook() {
             checks.push(CheckResult::pass(
                 "shots",
                 "✨ Shots will be discovered from Storybook at runtime",
@@ -107,6 +114,106 @@ pub async fn run(args: ValidateArgs) -> Result<()> {
         }
     }
 
+    // Check 6: Viewports, device presets and per-shot selections
+    if let Some(ref cfg) = config {
+        if !cfg.viewports.is_empty() || !cfg.matrix.is_empty() {
+            let selections = cfg.effective_viewports().and_then(|viewports| {
+                for shot in &cfg.shots {
+                    cfg.viewports_for(shot)?;
+                    cfg.browsers_for(shot)?;
+                }
+                Ok(viewports)
+            });
+            checks.push(match selections {
+                Ok(viewports) => CheckResult::pass(
+                    "viewports",
+                    format!("✅ {} viewports configured", viewports.len()),
+                ),
+                Err(e) => CheckResult::fail("viewports", e.to_string()),
+            });
+        }
+    }
+
+    // Check 7: Route rules
+    if let Some(ref cfg) = config {
+        let rules: Vec<_> = cfg
+            .routes
+            .iter()
+            .chain(cfg.shots.iter().flat_map(|s| &s.routes))
+            .collect();
+        if !rules.is_empty() {
+            let invalid = rules
+                .iter()
+                .find_map(|rule| rule.validate().err())
+                .or_else(|| {
+                    rules.iter().find_map(|rule| {
+                        let fixture = working_dir.join(rule.fixture.as_ref()?);
+                        (!fixture.is_file()).then(|| {
+                            anyhow::anyhow!("Route fixture not found: {}", fixture.display())
+                        })
+                    })
+                });
+            checks.push(match invalid {
+                None => CheckResult::pass(
+                    "routes",
+                    format!("✅ {} route rules configured", rules.len()),
+                ),
+                Some(e) => CheckResult::fail("routes", format!("❌ {}", e)),
+            });
+        }
+    }
+
+    // Check 8: Login setup
+    if let Some(ref cfg) = config {
+        if let Some(ref setup) = cfg.setup {
+            let invalid = setup
+                .validate()
+                .err()
+                .or_else(|| {
+                    setup.roles.iter().find_map(|(name, role)| {
+                        let script = working_dir.join(role.script.as_ref()?);
+                        (!script.is_file()).then(|| {
+                            anyhow::anyhow!(
+                                "❌ Login script for role '{}' not found: {}\n\n\
+                                 💡 Script paths are relative to the directory containing pixelguard.config.json.",
+                                name,
+                                script.display()
+                            )
+                        })
+                    })
+                })
+                .or_else(|| {
+                    cfg.shots
+                        .iter()
+                        .find_map(|shot| cfg.auth_role_for(shot).err())
+                });
+            checks.push(match invalid {
+                None => CheckResult::pass(
+                    "setup",
+                    format!("✅ {} auth roles configured", setup.roles.len()),
+                ),
+                Some(e) => CheckResult::fail("setup", e.to_string()),
+            });
+        }
+    }
+
+    // Check 9: Browser launch options or endpoint
+    if let Some(ref cfg) = config {
+        if !cfg.browser.is_empty() {
+            checks.push(match cfg.browser.validate(&cfg.effective_browsers()) {
+                Err(e) => CheckResult::fail("browser", e.to_string()),
+                Ok(()) => match cfg.browser.endpoint() {
+                    Some(endpoint) if !args.skip_url_check => check_browser(endpoint).await,
+                    Some(endpoint) => CheckResult::pass(
+                        "browser",
+                        format!("✅ Browser will be connected at {}", endpoint),
+                    ),
+                    None => CheckResult::pass("browser", "✅ Browser launch options configured"),
+                },
+            });
+        }
+    }
+
     // Output results
     if args.json {
         output_json(&checks)?;
@@ -171,15 +278,78 @@ fn check_playwright() -> CheckResult {
 
 /// Checks if the base URL is reachable.
 async fn check_base_url(url: &str) -> CheckResult {
-    match reqwest::get(url).await {
-        Ok(response) if response.status().is_success() => {
+    match check_url(url).await {
+        Reachability::Ready => {
             CheckResult::pass("base_url", format!("✅ {} is reachable", url))
         }
-        Ok(response) => CheckResult::fail(
+        Reachability::Status(status) => CheckResult::fail(
             "base_url",
-            format!("❌ {} returned status {}\n\n💡 Solution: Check that your dev server is running on the correct port.", url, response.status()),
+            format!("❌ {} returned status {}\n\n💡 Solution: Check that your dev server is running on the correct port.", url, status),
+        ),
+        Reachability::Unreachable(e) => CheckResult::fail("base_url", format!("❌ {} is not reachable: {}\n\n💡 Solutions:\n  • Start your dev server\n  • Check firewall settings\n  • Verify the URL in pixelguard.config.json", url, e)),
+    }
+}
+
+/// Checks that the configured browser endpoint accepts connections.
+async fn check_browser(endpoint: &str) -> CheckResult {
+    match check_browser_endpoint(endpoint).await {
+        Reachability::Ready => {
+            CheckResult::pass("browser", format!("✅ Browser endpoint {} is reachable", endpoint))
+        }
+        Reachability::Status(status) => CheckResult::fail(
+            "browser",
+            format!("❌ Browser endpoint {} returned status {}\n\n💡 Solution: Check that the endpoint is the browser's remote debugging address, e.g. http://browser:9222", endpoint, status),
+        ),
+        Reachability::Unreachable(e) => CheckResult::fail("browser", format!("❌ Browser endpoint {} is not reachable: {}\n\n💡 Solutions:\n  • Start the browser container or server\n  • Check the host and port in pixelguard.config.json\n  • Run with --skip-url-check if the browser starts later in CI", endpoint, e)),
+    }
+}
+
+/// Checks that a `staticDir` build exists (and has a story index, for Storybook).
+fn check_static_dir(config: &Config, dir: &Path) -> CheckResult {
+    if !dir.is_dir() {
+        return CheckResult::fail(
+            "static_dir",
+            format!("❌ Static build directory not found: {}\n\n💡 Solution: Build it first, e.g. npm run build-storybook", dir.display()),
+        );
+    }
+    if !config.is_storybook() {
+        return CheckResult::pass(
+            "static_dir",
+            format!("✅ {} will be served locally", dir.display()),
+        );
+    }
+    match load_storybook_stories(dir) {
+        Ok(stories) => CheckResult::pass(
+            "static_dir",
+            format!("✅ {} stories found in {}", stories.len(), dir.display()),
+        ),
+        Err(e) => CheckResult::fail("static_dir", e.to_string()),
+    }
+}
+
+/// Checks that a `webServer` can be started (or reused) at its URL.
+async fn check_web_server(web_server: &WebServer, base_url: &str) -> CheckResult {
+    let url = web_server.url(base_url);
+    if url.is_empty() {
+        return CheckResult::fail(
+            "web_server",
+            "❌ webServer has no URL to wait for.\n\n💡 Solution: Set 'baseUrl' or 'webServer.url' in pixelguard.config.json",
+        );
+    }
+
+    match check_url(url).await {
+        Reachability::Ready if web_server.reuse_existing => CheckResult::pass(
+            "web_server",
+            format!("✅ {} is already running and will be reused", url),
+        ),
+        Reachability::Ready => CheckResult::fail(
+            "web_server",
+            format!("❌ {} is already in use, so 'pixelguard test' won't start the webServer.\n\n💡 Solutions:\n  • Stop the server that is already running\n  • Or set \"reuseExisting\": true in webServer", url),
+        ),
+        _ => CheckResult::pass(
+            "web_server",
+            format!("🚀 {} will be started with: {}", url, web_server.command),
         ),
-        Err(e) => CheckResult::fail("base_url", format!("❌ {} is not reachable: {}\n\n💡 Solutions:\n  • Start your dev server\n  • Check firewall settings\n  • Verify the URL in pixelguard.config.json", url, e)),
     }
 }
 
@@ -5,6 +5,7 @@
 //! visual regression tests.
 
 mod commands;
+mod static_server;
 
 use clap::{Parser, Subcommand};
 use tracing_subscriber::EnvFilter;
@@ -51,6 +52,12 @@ enum Commands {
 
     /// Serve an existing report for review (without re-running tests)
     Serve(commands::serve::ServeArgs),
+
+    /// Record each shot's network traffic to a HAR file for later replay
+    RecordHar(commands::record_har::RecordHarArgs),
+
+    /// Combine the output of sharded test runs into one report
+    MergeResults(commands::merge_results::MergeResultsArgs),
 }
 
 #[tokio::main]
@@ -83,5 +90,7 @@ async fn main() -> anyhow::Result<()> {
         Commands::Apply(args) => commands::apply::run(args).await,
         Commands::Review(args) => commands::review::run(args).await,
         Commands::Serve(args) => commands::serve::run(args).await,
+        Commands::RecordHar(args) => commands::record_har::run(args).await,
+        Commands::MergeResults(args) => commands::merge_results::run(args).await,
     }
 }
@@ -0,0 +1,58 @@
+//! Embedded static file server for `staticDir` builds.
+//!
+//! Serves a directory such as `storybook-static/` on an ephemeral localhost
+//! port for the duration of a run, so no dev server is needed for capture.
+
+use std::net::SocketAddr;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use axum::Router;
+use tokio::task::JoinHandle;
+use tower_http::services::ServeDir;
+use tracing::info;
+
+/// A running static file server. Stops when dropped.
+pub struct StaticServer {
+    url: String,
+    task: JoinHandle<()>,
+}
+
+impl StaticServer {
+    /// Serves `dir` on `127.0.0.1` at a port picked by the OS.
+    pub async fn start(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            anyhow::bail!(
+                "❌ Static build directory not found: {}\n\n\
+                 💡 Solutions:\n  \
+                 • Build it first, e.g. npm run build-storybook\n  \
+                 • Point 'staticDir' at the build output directory",
+                dir.display()
+            );
+        }
+
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .context("Failed to bind a local port for the static server")?;
+        let url = format!("http://{}", listener.local_addr()?);
+
+        let app = Router::new().fallback_service(ServeDir::new(dir));
+        let task = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        info!("📦 Serving {} at {}", dir.display(), url);
+        Ok(Self { url, task })
+    }
+
+    /// Base URL of the server, without a trailing slash.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Drop for StaticServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -130,6 +130,37 @@ fn validate_command_json_output() {
         .stdout(predicate::str::contains(r#""passed""#));
 }
 
+#[test]
+fn validate_command_checks_browser_endpoint() {
+    let dir = tempdir().unwrap();
+
+    // Bind and release a port so nothing is listening on it
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let config = format!(
+        r#"{{ "browser": {{ "wsEndpoint": "ws://127.0.0.1:{}/" }} }}"#,
+        port
+    );
+    fs::write(dir.path().join("pixelguard.config.json"), config).unwrap();
+
+    // An empty PATH makes the Node.js and Playwright checks fail fast
+    let empty_bin = dir.path().join("bin");
+    fs::create_dir(&empty_bin).unwrap();
+
+    pixelguard()
+        .current_dir(dir.path())
+        .env("PATH", &empty_bin)
+        .args(["validate", "--json"])
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(r#""check": "browser""#))
+        .stdout(predicate::str::contains("is not reachable"));
+}
+
 #[test]
 fn init_command_creates_config() {
     let dir = tempdir().unwrap();
@@ -211,6 +242,453 @@ fn test_command_with_empty_shots() {
         .stderr(predicate::str::contains("No shots configured"));
 }
 
+#[cfg(unix)]
+#[test]
+fn test_command_starts_web_server_and_reports_early_exit() {
+    let dir = tempdir().unwrap();
+
+    fs::write(
+        dir.path().join("pixelguard.config.json"),
+        r#"{
+            "baseUrl": "http://127.0.0.1:9",
+            "webServer": { "command": "touch started && echo boom >&2 && exit 3", "timeout": 5000 },
+            "shots": [{"name": "home", "path": "/"}]
+        }"#,
+    )
+    .unwrap();
+
+    pixelguard()
+        .current_dir(dir.path())
+        .arg("test")
+        .assert()
+        .failure()
+        .stderr(
+            predicate::str::contains("webServer command exited")
+                .and(predicate::str::contains("boom")),
+        );
+
+    // The command runs in the project directory
+    assert!(dir.path().join("started").exists());
+}
+
+#[test]
+fn test_command_discovers_stories_from_static_build() {
+    let dir = tempdir().unwrap();
+    let static_dir = dir.path().join("storybook-static");
+    fs::create_dir(&static_dir).unwrap();
+    fs::write(
+        static_dir.join("iframe.html"),
+        "<div id=\"storybook-root\"></div>",
+    )
+    .unwrap();
+    fs::write(
+        static_dir.join("index.json"),
+        r#"{
+            "v": 5,
+            "entries": {
+                "button--docs": { "id": "button--docs", "type": "docs" },
+                "button--primary": { "id": "button--primary", "type": "story" },
+                "card--default": { "id": "card--default", "type": "story" }
+            }
+        }"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("pixelguard.config.json"),
+        r#"{"source": "storybook-static"}"#,
+    )
+    .unwrap();
+
+    // Stories are read from index.json on disk; no Storybook server is running
+    pixelguard()
+        .current_dir(dir.path())
+        .args(["test", "--filter", "no-such-story"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("2 shots were filtered out"));
+}
+
+#[test]
+fn test_command_requires_static_build_directory() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("pixelguard.config.json"),
+        r#"{"source": "storybook-static"}"#,
+    )
+    .unwrap();
+
+    pixelguard()
+        .current_dir(dir.path())
+        .arg("test")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Static build directory not found"));
+}
+
+/// Runs git in `dir`, panicking on failure.
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+#[test]
+fn test_command_carries_forward_shots_unaffected_by_changes() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(root.join("src/Button.tsx"), "export const Button = 1;").unwrap();
+    fs::write(
+        root.join("src/Card.tsx"),
+        "import { Button } from './Button';",
+    )
+    .unwrap();
+    fs::write(root.join("README.md"), "# Components").unwrap();
+    fs::write(
+        root.join("pixelguard.config.json"),
+        r#"{
+            "source": "manual",
+            "baseUrl": "http://localhost:9",
+            "shots": [
+                { "name": "button", "path": "/button", "importPath": "./src/Button.tsx" },
+                { "name": "card", "path": "/card", "importPath": "./src/Card.tsx" }
+            ]
+        }"#,
+    )
+    .unwrap();
+    fs::create_dir_all(root.join(".pixelguard/baseline")).unwrap();
+    fs::write(root.join(".pixelguard/baseline/button.png"), "png").unwrap();
+    fs::write(root.join(".pixelguard/baseline/card.png"), "png").unwrap();
+
+    git(root, &["init", "-q"]);
+    git(root, &["add", "."]);
+    git(root, &["commit", "-q", "-m", "Add components"]);
+
+    // Nothing imports the README, so no shot is captured (and no browser needed)
+    fs::write(root.join("README.md"), "# Components\n\nUpdated.").unwrap();
+
+    pixelguard()
+        .current_dir(root)
+        .args(["test", "--changed-since", "HEAD"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("testing 0 of 2 shots"))
+        .stdout(predicate::str::contains("Carried forward 2"))
+        .stdout(predicate::str::contains("2 unchanged"));
+
+    assert!(root.join(".pixelguard/current/card.png").exists());
+}
+
+/// Writes a solid 8x8 PNG of `color` to `path`.
+fn write_png(path: &std::path::Path, color: [u8; 4]) {
+    image::RgbaImage::from_pixel(8, 8, image::Rgba(color))
+        .save(path)
+        .unwrap();
+}
+
+/// Creates a project that captures `home` and `about` from pre-rendered
+/// screenshots in `fixtures/`, so `pixelguard test` runs without a browser.
+fn fixtures_project() -> tempfile::TempDir {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join("fixtures")).unwrap();
+    write_png(&dir.path().join("fixtures/home.png"), [255, 0, 0, 255]);
+    write_png(&dir.path().join("fixtures/about.png"), [0, 0, 255, 255]);
+    fs::write(
+        dir.path().join("pixelguard.config.json"),
+        r#"{
+            "source": "manual",
+            "baseUrl": "http://localhost:9",
+            "capture": { "backend": "fixtures", "dir": "fixtures" },
+            "shots": [
+                { "name": "home", "path": "/" },
+                { "name": "about", "path": "/about" }
+            ]
+        }"#,
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn test_command_captures_diffs_and_reports_fixtures() {
+    let dir = fixtures_project();
+    let root = dir.path();
+
+    pixelguard()
+        .current_dir(root)
+        .args(["test", "--update"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Updated baseline with 2 screenshot(s)",
+        ));
+    assert!(root.join(".pixelguard/baseline/home.png").exists());
+
+    pixelguard()
+        .current_dir(root)
+        .args(["test", "--ci"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""status":"pass""#))
+        .stdout(predicate::str::contains(r#""unchanged":2"#));
+
+    // Re-render one page differently
+    write_png(&root.join("fixtures/home.png"), [0, 255, 0, 255]);
+
+    pixelguard()
+        .current_dir(root)
+        .args(["test", "--ci"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(r#""status":"fail""#))
+        .stdout(predicate::str::contains(r#""changed":1"#));
+
+    assert!(root.join(".pixelguard/diff/home.png").exists());
+    let results = fs::read_to_string(root.join(".pixelguard/results.json")).unwrap();
+    assert!(results.contains(r#""name": "home""#));
+    let report = fs::read_to_string(root.join(".pixelguard/report.html")).unwrap();
+    assert!(report.contains("home"));
+}
+
+#[test]
+fn test_command_reports_missing_fixtures_as_failed() {
+    let dir = fixtures_project();
+    let root = dir.path();
+
+    pixelguard()
+        .current_dir(root)
+        .args(["test", "--update"])
+        .assert()
+        .success();
+
+    fs::remove_file(root.join("fixtures/about.png")).unwrap();
+
+    pixelguard()
+        .current_dir(root)
+        .args(["test", "--ci"])
+        .assert()
+        .stdout(predicate::str::contains(r#""failed":1"#))
+        .stderr(predicate::str::contains("Fixture not found"));
+}
+
+/// A stand-in for the `playwright` module: every page renders `page.png` from
+/// the project directory, and `/broken` throws an uncaught exception.
+#[cfg(unix)]
+const FAKE_PLAYWRIGHT: &str = r#"
+const fs = require('fs');
+const path = require('path');
+const page = () => {
+    const handlers = {};
+    return {
+        on: (event, handler) => { handlers[event] = handler; },
+        route: async () => {}, emulateMedia: async () => {},
+        waitForSelector: async () => {}, evaluate: async () => ({}), $: async () => null,
+        viewportSize: () => ({ width: 8, height: 8 }),
+        locator: () => ({ first() { return this; }, all: async () => [], waitFor: async () => {} }),
+        goto: async url => {
+            if (url.endsWith('/broken')) handlers.pageerror(new Error('x is undefined'));
+        },
+        screenshot: async ({ path: file }) => fs.copyFileSync(path.join(__dirname, '../../page.png'), file),
+        close: async () => {}
+    };
+};
+const browser = {
+    newContext: async () => ({ newPage: async () => page(), close: async () => {}, addInitScript: async () => {} }),
+    close: async () => {}
+};
+module.exports = { chromium: { launch: async () => browser } };
+"#;
+
+/// Returns a PATH holding only `node`, so the Playwright check finds the fake
+/// module in node_modules instead of running `npx`. None if Node.js is missing.
+#[cfg(unix)]
+fn node_only_path(dir: &std::path::Path) -> Option<std::path::PathBuf> {
+    let output = std::process::Command::new("sh")
+        .args(["-c", "command -v node"])
+        .output()
+        .ok()?;
+    let node = String::from_utf8(output.stdout).ok()?;
+    if node.trim().is_empty() {
+        return None;
+    }
+    let bin = dir.join("bin");
+    fs::create_dir(&bin).unwrap();
+    std::os::unix::fs::symlink(node.trim(), bin.join("node")).unwrap();
+    Some(bin)
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_fails_ci_for_new_shot_with_page_errors() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let Some(path) = node_only_path(root) else {
+        return;
+    };
+    fs::create_dir_all(root.join("node_modules/playwright")).unwrap();
+    fs::write(
+        root.join("node_modules/playwright/index.js"),
+        FAKE_PLAYWRIGHT,
+    )
+    .unwrap();
+    write_png(&root.join("page.png"), [255, 0, 0, 255]);
+    fs::create_dir_all(root.join(".pixelguard/baseline")).unwrap();
+    write_png(
+        &root.join(".pixelguard/baseline/home.png"),
+        [255, 0, 0, 255],
+    );
+    fs::write(
+        root.join("pixelguard.config.json"),
+        r#"{
+            "source": "manual",
+            "baseUrl": "http://localhost:9",
+            "pageErrors": { "fail": true },
+            "shots": [
+                { "name": "home", "path": "/" },
+                { "name": "broken", "path": "/broken" }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    pixelguard()
+        .current_dir(root)
+        .env("PATH", path)
+        .args(["test", "--ci"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(r#""status":"fail""#))
+        .stdout(predicate::str::contains(r#""unchanged":1"#))
+        .stdout(predicate::str::contains(r#""added":0"#))
+        .stdout(predicate::str::contains(r#""failed":1"#));
+}
+
+#[test]
+fn test_command_rejects_invalid_shard() {
+    pixelguard()
+        .args(["test", "--shard", "5/4"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid shard"));
+}
+
+/// Writes a shard's output directory with a results.json reporting `unchanged`
+/// and `changed`, plus their current and diff images.
+fn write_shard_output(dir: &std::path::Path, index: u32, unchanged: &str, changed: &str) {
+    fs::create_dir_all(dir.join("current")).unwrap();
+    fs::create_dir_all(dir.join("diff")).unwrap();
+    fs::write(
+        dir.join("current").join(format!("{}.png", unchanged)),
+        "png",
+    )
+    .unwrap();
+    fs::write(dir.join("current").join(format!("{}.png", changed)), "png").unwrap();
+    fs::write(dir.join("diff").join(format!("{}.png", changed)), "png").unwrap();
+
+    let results = serde_json::json!({
+        "version": "1.0",
+        "timestamp": "2026-01-14T12:00:00Z",
+        "shard": { "index": index, "total": 2 },
+        "summary": { "total": 2, "unchanged": 1, "changed": 1, "added": 0, "removed": 0, "passed": false },
+        "results": {
+            "changed": [{
+                "name": changed,
+                "diffPercentage": 3.0,
+                "baselinePath": format!("baseline/{}.png", changed),
+                "currentPath": format!("current/{}.png", changed),
+                "diffPath": format!("diff/{}.png", changed)
+            }],
+            "added": [],
+            "removed": [],
+            "unchanged": [unchanged]
+        }
+    });
+    fs::write(dir.join("results.json"), results.to_string()).unwrap();
+}
+
+#[test]
+fn merge_results_combines_shards_into_one_report() {
+    let dir = tempdir().unwrap();
+    write_shard_output(&dir.path().join("shard-1"), 1, "button", "card");
+    write_shard_output(&dir.path().join("shard-2"), 2, "modal", "table");
+
+    let output = pixelguard()
+        .current_dir(dir.path())
+        .args(["merge-results", "shard-1", "shard-2", "--ci"])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+
+    // The summary is the last line, after any log output
+    let stdout = String::from_utf8(output).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+    assert_eq!(summary["status"], "fail");
+    assert_eq!(summary["unchanged"], 2);
+    assert_eq!(summary["changed"], 2);
+    assert_eq!(summary["shards"], 2);
+
+    let merged = dir.path().join(".pixelguard");
+    assert!(merged.join("report.html").exists());
+    assert!(merged.join("current/modal.png").exists());
+    assert!(merged.join("diff/card.png").exists());
+    let results: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(merged.join("results.json")).unwrap()).unwrap();
+    assert_eq!(results["summary"]["total"], 4);
+    assert!(results.get("shard").is_none());
+}
+
+#[test]
+fn merge_results_fails_ci_for_failed_shots() {
+    let dir = tempdir().unwrap();
+    write_shard_output(&dir.path().join("shard-1"), 1, "button", "card");
+    write_shard_output(&dir.path().join("shard-2"), 2, "modal", "table");
+    // Neither shard changed, but shard 2 could not capture `table`
+    for (shard, failed) in [("shard-1", None), ("shard-2", Some("table"))] {
+        let path = dir.path().join(shard).join("results.json");
+        let mut results: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        results["results"]["changed"] = serde_json::json!([]);
+        if let Some(name) = failed {
+            results["results"]["failed"] = serde_json::json!([{ "name": name, "error": "boom" }]);
+        }
+        fs::write(&path, results.to_string()).unwrap();
+    }
+
+    let output = pixelguard()
+        .current_dir(dir.path())
+        .args(["merge-results", "shard-1", "shard-2", "--ci"])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+
+    let stdout = String::from_utf8(output).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+    assert_eq!(summary["status"], "fail");
+    assert_eq!(summary["changed"], 0);
+    assert_eq!(summary["failed"], 1);
+}
+
+#[test]
+fn merge_results_reports_missing_shards() {
+    let dir = tempdir().unwrap();
+    write_shard_output(&dir.path().join("shard-1"), 1, "button", "card");
+
+    pixelguard()
+        .current_dir(dir.path())
+        .args(["merge-results", "shard-1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Missing results for shard(s) 2/2"));
+}
+
 #[test]
 fn config_custom_path_flag_works() {
     let dir = tempdir().unwrap();
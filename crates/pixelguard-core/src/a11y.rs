@@ -0,0 +1,253 @@
+//! Accessibility tree snapshots and their structural diff.
+//!
+//! With `a11y` enabled, the capture script saves Playwright's ARIA snapshot
+//! (`locator.ariaSnapshot()`) of each page as `{name}.a11y.yaml` next to the
+//! screenshot, in `current/` and, once approved, in `baseline/`. The snapshot
+//! already has one line per node (role, name and states, indented by depth), so
+//! [`compare_snapshots`] diffs the lines, and a lost label or a changed role shows
+//! up even when the pixels don't move.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// File name suffix of accessibility snapshots.
+pub const A11Y_SNAPSHOT_SUFFIX: &str = ".a11y.yaml";
+
+/// Returns the path of a screenshot's accessibility snapshot in an image directory.
+pub fn snapshot_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}{}", name, A11Y_SNAPSHOT_SUFFIX))
+}
+
+/// A screenshot whose accessibility tree differs from its baseline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct A11yChangedShot {
+    /// Name of the screenshot
+    pub name: String,
+    /// Nodes added or removed, in tree order
+    pub changes: Vec<A11yChange>,
+}
+
+/// A node that only exists in one of the two trees.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct A11yChange {
+    /// Whether the node was added or removed
+    pub kind: A11yChangeKind,
+    /// Depth of the node in the tree (0 for the root)
+    pub depth: usize,
+    /// The node's role, name and states, e.g. `button "Save" [disabled]`
+    pub node: String,
+}
+
+/// How a node changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum A11yChangeKind {
+    /// Only in the current tree
+    Added,
+    /// Only in the baseline tree
+    Removed,
+}
+
+impl std::fmt::Display for A11yChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = match self.kind {
+            A11yChangeKind::Added => '+',
+            A11yChangeKind::Removed => '-',
+        };
+        write!(f, "{} {}{}", sign, "  ".repeat(self.depth), self.node)
+    }
+}
+
+/// Compares the accessibility snapshots of a screenshot in two image directories.
+///
+/// Returns None if either snapshot is missing (e.g. `a11y` was just turned on and
+/// the baseline has none yet), and an empty list if the trees match.
+pub fn compare_snapshots(
+    baseline_dir: &Path,
+    current_dir: &Path,
+    name: &str,
+) -> Result<Option<Vec<A11yChange>>> {
+    let (Some(baseline), Some(current)) = (
+        load_snapshot(&snapshot_path(baseline_dir, name))?,
+        load_snapshot(&snapshot_path(current_dir, name))?,
+    ) else {
+        return Ok(None);
+    };
+    Ok(Some(diff_snapshots(&baseline, &current)))
+}
+
+/// Reads a snapshot file, or None if it doesn't exist.
+fn load_snapshot(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(Some(content))
+}
+
+/// Diffs two ARIA snapshots node by node, keeping the nodes common to both in
+/// order (a longest common subsequence of their lines).
+pub fn diff_snapshots(baseline: &str, current: &str) -> Vec<A11yChange> {
+    let old = flatten(baseline);
+    let new = flatten(current);
+
+    // Only the middle part that differs needs the quadratic table
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    // lengths[i][j]: common subsequence length of old[i..] and new[j..]
+    let mut lengths = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let change = |kind, (depth, node): &(usize, String)| A11yChange {
+        kind,
+        depth: *depth,
+        node: node.clone(),
+    };
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            changes.push(change(A11yChangeKind::Removed, &old[i]));
+            i += 1;
+        } else {
+            changes.push(change(A11yChangeKind::Added, &new[j]));
+            j += 1;
+        }
+    }
+    changes.extend(old[i..].iter().map(|n| change(A11yChangeKind::Removed, n)));
+    changes.extend(new[j..].iter().map(|n| change(A11yChangeKind::Added, n)));
+    changes
+}
+
+/// Splits an ARIA snapshot into `(depth, node)` lines in document order.
+///
+/// `- link "Docs":` at two spaces of indentation becomes `(1, "link \"Docs\"")`:
+/// the list marker and the colon that opens a node's children aren't part of it.
+fn flatten(snapshot: &str) -> Vec<(usize, String)> {
+    snapshot
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let node = line.trim_start();
+            let depth = (line.len() - node.len()) / 2;
+            let node = node.strip_prefix("- ").unwrap_or(node).trim_end();
+            let node = node.strip_suffix(':').unwrap_or(node);
+            (depth, node.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form(textbox: &str, role: &str) -> String {
+        format!(
+            "- main:\n  - heading \"Create account\" [level=1]\n  - {}\n  - {} \"Submit\"\n",
+            textbox, role
+        )
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_changes() {
+        assert!(diff_snapshots(
+            &form(r#"textbox "Email""#, "button"),
+            &form(r#"textbox "Email""#, "button")
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn lost_label_and_wrong_role_are_reported() {
+        let changes = diff_snapshots(
+            &form(r#"textbox "Email""#, "button"),
+            &form("textbox", "link"),
+        );
+
+        assert_eq!(
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                r#"-   textbox "Email""#,
+                r#"-   button "Submit""#,
+                "+   textbox",
+                r#"+   link "Submit""#,
+            ]
+        );
+        assert_eq!(changes[0].depth, 1);
+        assert_eq!(changes[0].kind, A11yChangeKind::Removed);
+    }
+
+    #[test]
+    fn states_are_part_of_the_node() {
+        let before = "- checkbox \"Remember me\"\n";
+        let after = "- checkbox \"Remember me\" [checked]\n";
+
+        let changes = diff_snapshots(before, after);
+
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].node, r#"checkbox "Remember me" [checked]"#);
+    }
+
+    #[test]
+    fn nodes_with_children_drop_the_colon() {
+        let snapshot =
+            "- list:\n  - listitem:\n    - link \"Docs\":\n      - /url: /docs\n  - text: Plain";
+
+        assert_eq!(
+            flatten(snapshot),
+            vec![
+                (0, "list".to_string()),
+                (1, "listitem".to_string()),
+                (2, r#"link "Docs""#.to_string()),
+                (3, "/url: /docs".to_string()),
+                (1, "text: Plain".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn compare_snapshots_needs_both_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline = dir.path().join("baseline");
+        let current = dir.path().join("current");
+        std::fs::create_dir_all(&baseline).unwrap();
+        std::fs::create_dir_all(&current).unwrap();
+
+        let write = |dir: &Path, snapshot: String| {
+            std::fs::write(snapshot_path(dir, "form"), snapshot).unwrap();
+        };
+        write(&current, form(r#"textbox "Email""#, "button"));
+        assert_eq!(
+            compare_snapshots(&baseline, &current, "form").unwrap(),
+            None
+        );
+
+        write(&baseline, form(r#"textbox "E-mail""#, "button"));
+        let changes = compare_snapshots(&baseline, &current, "form")
+            .unwrap()
+            .unwrap();
+        assert_eq!(changes.len(), 2);
+    }
+}
@@ -0,0 +1,523 @@
+//! Selecting the shots affected by a change, for `--changed-since`.
+//!
+//! [`changed_files`] asks git which files differ from a ref. [`select_affected`]
+//! follows each shot's `importPath` module through its imports and keeps the
+//! shots that reach a changed file; [`carry_forward`] copies the baselines of
+//! the rest into `current/` so they are reported as unchanged without capture.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use glob::Pattern;
+use regex::Regex;
+use tracing::debug;
+
+use crate::config::{Affected, Config};
+use crate::diff::shot_and_viewport;
+
+/// Files whose changes affect every shot: Storybook's own config, dependencies
+/// and the Pixelguard config.
+const GLOBAL_FILES: &[&str] = &[
+    ".storybook/**",
+    "package.json",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "pixelguard.config.json",
+];
+
+/// Extensions tried, in order, when an import leaves out the file extension.
+const EXTENSIONS: &[&str] = &[
+    "ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts", "vue", "svelte", "json", "css", "scss",
+    "sass", "less",
+];
+
+/// Extensions of files that are scanned for imports.
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "ts", "tsx", "js", "jsx", "mjs", "cjs", "mts", "cts", "vue", "svelte", "mdx", "css", "scss",
+    "sass", "less",
+];
+
+/// Returns the files that differ between the merge base of `git_ref` and the
+/// working tree, including uncommitted and untracked files, as absolute paths.
+pub fn changed_files(working_dir: &Path, git_ref: &str) -> Result<Vec<PathBuf>> {
+    let root = PathBuf::from(git(working_dir, &["rev-parse", "--show-toplevel"])?.trim());
+    let base = git(working_dir, &["merge-base", git_ref, "HEAD"]).with_context(|| {
+        format!(
+            "❌ Could not find the merge base of '{}' and HEAD.\n\n\
+             💡 Solutions:\n  \
+             • Check that the ref exists: git rev-parse {}\n  \
+             • In CI, fetch the full history (e.g. fetch-depth: 0 with actions/checkout)",
+            git_ref, git_ref
+        )
+    })?;
+
+    let diff = git(
+        working_dir,
+        &["diff", "--name-only", "--no-renames", base.trim()],
+    )?;
+    let untracked = git(
+        working_dir,
+        &["ls-files", "--others", "--exclude-standard", "--full-name"],
+    )?;
+
+    Ok(diff
+        .lines()
+        .chain(untracked.lines())
+        .filter(|line| !line.is_empty())
+        .map(|line| root.join(line))
+        .collect())
+}
+
+/// Runs git in `dir` and returns its stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("❌ Failed to run git. Is it installed?")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The configured shots split by whether a change affects them.
+#[derive(Debug, Default, PartialEq)]
+pub struct AffectedShots {
+    /// Shots to capture: reached by a change, or without an `importPath` to trace
+    pub affected: Vec<String>,
+    /// Shots no change reaches, whose baselines can be carried forward
+    pub unaffected: Vec<String>,
+    /// How many of the affected shots have no `importPath`
+    pub untraced: usize,
+}
+
+/// Splits the configured shots into those affected by `changed` files and the rest.
+///
+/// A shot is affected when its `importPath` module, or any module it imports
+/// (transitively, through relative or aliased imports), changed; when a changed
+/// file matches one of its `affected.rules`; or when a global file such as
+/// `package.json` or `.storybook/` changed. Shots without an `importPath` are
+/// always affected, since there is nothing to trace.
+pub fn select_affected(
+    config: &Config,
+    working_dir: &Path,
+    changed: &[PathBuf],
+) -> Result<AffectedShots> {
+    let root = working_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", working_dir.display()))?;
+    let settings = config.affected.clone().unwrap_or_default();
+    let changed: HashSet<PathBuf> = changed.iter().map(|path| normalize(path)).collect();
+
+    // Changed files as paths relative to the project root, for glob rules
+    let relative: Vec<String> = changed
+        .iter()
+        .filter_map(|path| path.strip_prefix(&root).ok())
+        .map(|path| path.to_string_lossy().replace('\\', "/"))
+        .collect();
+    let any_changed = |patterns: &[String]| -> Result<bool> {
+        for pattern in patterns {
+            let pattern = Pattern::new(pattern)
+                .with_context(|| format!("Invalid file pattern in affected.rules: {}", pattern))?;
+            if relative.iter().any(|file| pattern.matches(file)) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    };
+
+    let globals: Vec<String> = GLOBAL_FILES.iter().map(|p| p.to_string()).collect();
+    let everything = any_changed(&globals)?;
+
+    let mut rule_shots = Vec::new();
+    for rule in &settings.rules {
+        if any_changed(&rule.files)? {
+            for pattern in &rule.shots {
+                rule_shots.push(Pattern::new(pattern).with_context(|| {
+                    format!("Invalid shot pattern in affected.rules: {}", pattern)
+                })?);
+            }
+        }
+    }
+
+    let entries: Vec<Option<PathBuf>> = config
+        .shots
+        .iter()
+        .map(|shot| {
+            shot.import_path
+                .as_deref()
+                .map(|import_path| normalize(&root.join(import_path)))
+        })
+        .collect();
+    let reached = if everything {
+        HashSet::new()
+    } else {
+        let graph = ImportGraph::build(entries.iter().flatten().cloned(), &root, &settings)?;
+        graph.dependents(&changed)
+    };
+
+    let mut result = AffectedShots::default();
+    for (shot, entry) in config.shots.iter().zip(&entries) {
+        let affected = match entry {
+            None => {
+                result.untraced += 1;
+                true
+            }
+            Some(entry) => {
+                everything
+                    || reached.contains(entry)
+                    || rule_shots.iter().any(|pattern| pattern.matches(&shot.name))
+            }
+        };
+        if affected {
+            result.affected.push(shot.name.clone());
+        } else {
+            result.unaffected.push(shot.name.clone());
+        }
+    }
+
+    debug!(
+        "🔀 {} changed file(s) reach {} of {} shots",
+        changed.len(),
+        result.affected.len(),
+        config.shots.len()
+    );
+    Ok(result)
+}
+
+/// Which modules import which, for the modules reachable from the shots.
+struct ImportGraph {
+    /// Each module mapped to the modules that import it
+    importers: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+impl ImportGraph {
+    /// Follows the imports of `entries`, reading each module once.
+    fn build(
+        entries: impl IntoIterator<Item = PathBuf>,
+        root: &Path,
+        settings: &Affected,
+    ) -> Result<Self> {
+        let import = Regex::new(
+            r#"(?:\bfrom\s*|\bimport\s*\(?\s*|\brequire\s*\(\s*|@import\s+(?:url\(\s*)?)['"]([^'"\n]+)['"]"#,
+        )?;
+
+        let mut importers: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+        let mut queue: VecDeque<PathBuf> = entries.into_iter().collect();
+        let mut seen: HashSet<PathBuf> = queue.iter().cloned().collect();
+
+        while let Some(module) = queue.pop_front() {
+            let scanned = module
+                .extension()
+                .is_some_and(|ext| SOURCE_EXTENSIONS.iter().any(|e| ext == *e));
+            let Some(source) = scanned
+                .then(|| std::fs::read_to_string(&module).ok())
+                .flatten()
+            else {
+                continue;
+            };
+
+            for capture in import.captures_iter(&source) {
+                let Some(dependency) =
+                    resolve_import(&module, &capture[1], root, &settings.aliases)
+                else {
+                    continue;
+                };
+                importers
+                    .entry(dependency.clone())
+                    .or_default()
+                    .push(module.clone());
+                if seen.insert(dependency.clone()) {
+                    queue.push_back(dependency);
+                }
+            }
+        }
+
+        Ok(Self { importers })
+    }
+
+    /// Returns the `changed` modules and every module that imports one of them,
+    /// directly or indirectly.
+    fn dependents(&self, changed: &HashSet<PathBuf>) -> HashSet<PathBuf> {
+        let mut reached = changed.clone();
+        let mut queue: VecDeque<&PathBuf> = changed.iter().collect();
+        while let Some(module) = queue.pop_front() {
+            for importer in self.importers.get(module).into_iter().flatten() {
+                if reached.insert(importer.clone()) {
+                    queue.push_back(importer);
+                }
+            }
+        }
+        reached
+    }
+}
+
+/// Resolves an import specifier to a file, for relative and aliased imports.
+///
+/// Package imports (e.g. `react`) return None.
+fn resolve_import(
+    module: &Path,
+    specifier: &str,
+    root: &Path,
+    aliases: &BTreeMap<String, String>,
+) -> Option<PathBuf> {
+    // Drop query strings such as `?raw` and `?url`
+    let specifier = specifier.split('?').next()?;
+
+    let base = if specifier.starts_with("./") || specifier.starts_with("../") {
+        module.parent()?.join(specifier)
+    } else {
+        let (alias, target) = aliases
+            .iter()
+            .filter(|(alias, _)| specifier.starts_with(alias.as_str()))
+            .max_by_key(|(alias, _)| alias.len())?;
+        root.join(target)
+            .join(specifier[alias.len()..].trim_start_matches('/'))
+    };
+    let base = normalize(&base);
+    if base.components().any(|c| c.as_os_str() == "node_modules") {
+        return None;
+    }
+
+    if base.is_file() {
+        return Some(base);
+    }
+
+    let with_extension = |ext: &str| {
+        let mut path = base.clone().into_os_string();
+        path.push(".");
+        path.push(ext);
+        PathBuf::from(path)
+    };
+    if let Some(path) = EXTENSIONS
+        .iter()
+        .map(|ext| with_extension(ext))
+        .find(|path| path.is_file())
+    {
+        return Some(path);
+    }
+
+    // TypeScript's ESM style: `./button.js` for `./button.ts`
+    if let Some(ext) = base.extension().and_then(|ext| ext.to_str()) {
+        let typescript: &[&str] = match ext {
+            "js" | "jsx" => &["ts", "tsx"],
+            "mjs" => &["mts"],
+            "cjs" => &["cts"],
+            _ => &[],
+        };
+        if let Some(path) = typescript
+            .iter()
+            .map(|ext| base.with_extension(ext))
+            .find(|path| path.is_file())
+        {
+            return Some(path);
+        }
+    }
+
+    EXTENSIONS
+        .iter()
+        .map(|ext| base.join(format!("index.{}", ext)))
+        .find(|path| path.is_file())
+}
+
+/// Removes `.` and `..` components without touching the file system, so deleted
+/// files still compare equal.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Copies the baselines of `config.carried_forward` shots into `current/`, so the
+/// output directory is complete without capturing them. Returns the number of
+/// screenshots copied.
+///
+/// Only screenshots of the run's shard are copied.
+pub fn carry_forward(config: &Config, working_dir: &Path) -> Result<usize> {
+    if config.carried_forward.is_empty() {
+        return Ok(0);
+    }
+
+    let output_dir = working_dir.join(&config.output_dir);
+    let baseline_dir = output_dir.join("baseline");
+    let current_dir = output_dir.join("current");
+    std::fs::create_dir_all(&current_dir)?;
+    if !baseline_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut copied = 0;
+    for entry in std::fs::read_dir(&baseline_dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "png") {
+            continue;
+        }
+        let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy()) else {
+            continue;
+        };
+        let (shot, viewport) = shot_and_viewport(&name);
+        let in_shard = config
+            .shard
+            .is_none_or(|shard| shard.contains(shot, viewport));
+        if !config.carried_forward.contains(shot) || !in_shard {
+            continue;
+        }
+
+        std::fs::copy(&path, current_dir.join(format!("{}.png", name)))
+            .with_context(|| format!("Failed to carry forward {}", path.display()))?;
+        copied += 1;
+    }
+
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AffectedRule, Shot};
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn story(name: &str, import_path: &str) -> Shot {
+        Shot {
+            name: name.to_string(),
+            path: format!("/iframe.html?id={}", name),
+            import_path: Some(import_path.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// A project with a button and a card story; the card renders the button.
+    fn project() -> (tempfile::TempDir, Config) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "src/Button.stories.tsx",
+            "import { Button } from './Button';\nimport '@/theme/tokens.css';",
+        );
+        write(
+            root,
+            "src/Button.tsx",
+            "import styles from './button.module.css';",
+        );
+        write(root, "src/button.module.css", ".button {}");
+        write(root, "src/theme/tokens.css", ":root {}");
+        write(
+            root,
+            "src/Card.stories.tsx",
+            "import {\n  Card,\n} from './card/index.js';",
+        );
+        write(
+            root,
+            "src/card/index.ts",
+            "export * from \"../Button\";\nconst icon = require('./icon.svg');",
+        );
+        write(root, "src/card/icon.svg", "<svg/>");
+        write(root, "src/Icon.stories.tsx", "import React from 'react';");
+
+        let config = Config {
+            shots: vec![
+                story("button--primary", "./src/Button.stories.tsx"),
+                story("card--default", "./src/Card.stories.tsx"),
+                story("icon--default", "./src/Icon.stories.tsx"),
+                Shot {
+                    name: "home".to_string(),
+                    path: "/".to_string(),
+                    ..Default::default()
+                },
+            ],
+            affected: Some(Affected {
+                aliases: BTreeMap::from([("@/".to_string(), "src/".to_string())]),
+                rules: vec![AffectedRule {
+                    files: vec!["docs/**".to_string()],
+                    shots: vec!["icon--*".to_string()],
+                }],
+            }),
+            ..Default::default()
+        };
+        (dir, config)
+    }
+
+    fn select(config: &Config, root: &Path, changed: &[&str]) -> AffectedShots {
+        let changed: Vec<PathBuf> = changed.iter().map(|path| root.join(path)).collect();
+        select_affected(config, root, &changed).unwrap()
+    }
+
+    #[test]
+    fn traces_changes_through_imports() {
+        let (dir, config) = project();
+        let root = dir.path().canonicalize().unwrap();
+
+        let result = select(&config, &root, &["src/button.module.css"]);
+        assert_eq!(
+            result.affected,
+            vec!["button--primary", "card--default", "home"]
+        );
+        assert_eq!(result.unaffected, vec!["icon--default"]);
+        assert_eq!(result.untraced, 1);
+
+        // Through the alias, only the button imports the tokens
+        let result = select(&config, &root, &["src/theme/tokens.css"]);
+        assert_eq!(result.affected, vec!["button--primary", "home"]);
+
+        let result = select(&config, &root, &["src/card/icon.svg"]);
+        assert_eq!(result.affected, vec!["card--default", "home"]);
+    }
+
+    #[test]
+    fn rules_and_global_files_add_shots() {
+        let (dir, config) = project();
+        let root = dir.path().canonicalize().unwrap();
+
+        let result = select(&config, &root, &["docs/guide.md"]);
+        assert_eq!(result.affected, vec!["icon--default", "home"]);
+
+        let result = select(&config, &root, &["package.json"]);
+        assert!(result.unaffected.is_empty());
+
+        let result = select(&config, &root, &["README.md"]);
+        assert_eq!(result.affected, vec!["home"]);
+    }
+
+    #[test]
+    fn carries_forward_baselines_of_unaffected_shots() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_dir = dir.path().join(".pixelguard/baseline");
+        std::fs::create_dir_all(&baseline_dir).unwrap();
+        for name in ["icon@mobile", "icon+scheme-dark", "button@mobile"] {
+            std::fs::write(baseline_dir.join(format!("{}.png", name)), name).unwrap();
+        }
+
+        let config = Config {
+            carried_forward: ["icon".to_string()].into(),
+            ..Default::default()
+        };
+        assert_eq!(carry_forward(&config, dir.path()).unwrap(), 2);
+
+        let current_dir = dir.path().join(".pixelguard/current");
+        assert!(current_dir.join("icon@mobile.png").exists());
+        assert!(current_dir.join("icon+scheme-dark.png").exists());
+        assert!(!current_dir.join("button@mobile.png").exists());
+    }
+}
@@ -0,0 +1,624 @@
+//! Capture backends.
+//!
+//! A [`CaptureBackend`] fills `{outputDir}/current` with a screenshot per shot,
+//! viewport, browser and emulation:
+//!
+//! - [`PlaywrightBackend`] runs the generated Playwright script (the default)
+//! - [`PluginBackend`] hands the shots to a capture plugin
+//! - [`FixturesBackend`] copies pre-rendered PNGs from a directory, so capture,
+//!   diff and report can run without Node.js or a browser
+//!
+//! [`select_backend`] picks one from the `capture` config and the loaded plugins.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+use anyhow::{Context, Result};
+use tracing::info;
+
+use crate::a11y::A11Y_SNAPSHOT_SUFFIX;
+use crate::capture::{
+    capture_screenshots_with_progress, save_capture_stats, save_masked_regions, save_page_errors,
+    CaptureEvent, CaptureResult, CapturedShot, FailedShot,
+};
+use crate::config::{CaptureBackendConfig, Config, Shot};
+use crate::dom::DOM_SNAPSHOT_SUFFIX;
+use crate::plugins::{
+    executor, CaptureInput, CaptureOutput, CaptureScreenshot, CaptureShot, CaptureViewport,
+    LoadedPlugin, PluginCategory, PluginRegistry,
+};
+
+/// Future returned by [`CaptureBackend::capture`].
+pub type CaptureFuture<'a> = Pin<Box<dyn Future<Output = Result<CaptureResult>> + Send + 'a>>;
+
+/// Callback receiving each [`CaptureEvent`] as a backend reports it.
+pub type EventHandler<'a> = dyn FnMut(&CaptureEvent) + Send + 'a;
+
+/// A way of capturing the configured shots.
+pub trait CaptureBackend: Send + Sync {
+    /// Short name of the backend, for logs (e.g. `playwright`).
+    fn name(&self) -> &str;
+
+    /// Returns true if the backend runs Playwright, so Node.js and Playwright
+    /// must be installed.
+    fn needs_playwright(&self) -> bool {
+        false
+    }
+
+    /// Captures `config.shots` into `{outputDir}/current` under `working_dir`,
+    /// calling `on_event` as each shot is reported.
+    fn capture<'a>(
+        &'a self,
+        config: &'a Config,
+        working_dir: &'a Path,
+        on_event: &'a mut EventHandler<'_>,
+    ) -> CaptureFuture<'a>;
+}
+
+/// Returns the backend named by `capture.backend`. When it is unset, the capture
+/// plugin is used if one is loaded, otherwise Playwright.
+///
+/// # Errors
+///
+/// Returns an error if `capture.backend` is `plugin` but no capture plugin is loaded.
+pub fn select_backend<'a>(
+    config: &Config,
+    working_dir: &Path,
+    registry: &'a PluginRegistry,
+) -> Result<Box<dyn CaptureBackend + 'a>> {
+    let plugin = registry.get(PluginCategory::Capture);
+    Ok(match (&config.capture, plugin) {
+        (Some(CaptureBackendConfig::Fixtures { dir }), _) => {
+            Box::new(FixturesBackend::new(working_dir.join(dir)))
+        }
+        (Some(CaptureBackendConfig::Plugin) | None, Some(plugin)) => {
+            Box::new(PluginBackend::new(plugin))
+        }
+        (Some(CaptureBackendConfig::Plugin), None) => anyhow::bail!(
+            "❌ `capture.backend` is \"plugin\", but no capture plugin is loaded.\n\n\
+             💡 Solutions:\n  \
+             • Add a capture plugin to `plugins` in pixelguard.config.json\n  \
+             • Or set `capture.backend` to \"playwright\""
+        ),
+        (Some(CaptureBackendConfig::Playwright) | None, _) => Box::new(PlaywrightBackend),
+    })
+}
+
+/// Captures with the generated Playwright script, run by Node.js.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlaywrightBackend;
+
+impl CaptureBackend for PlaywrightBackend {
+    fn name(&self) -> &str {
+        "playwright"
+    }
+
+    fn needs_playwright(&self) -> bool {
+        true
+    }
+
+    fn capture<'a>(
+        &'a self,
+        config: &'a Config,
+        working_dir: &'a Path,
+        on_event: &'a mut EventHandler<'_>,
+    ) -> CaptureFuture<'a> {
+        Box::pin(capture_screenshots_with_progress(
+            config,
+            working_dir,
+            on_event,
+        ))
+    }
+}
+
+/// Captures with the `capture` hook of a plugin.
+///
+/// The plugin reports all shots at once when it finishes, so events arrive
+/// together at the end.
+pub struct PluginBackend<'a> {
+    plugin: &'a LoadedPlugin,
+}
+
+impl<'a> PluginBackend<'a> {
+    /// Creates a backend that runs `plugin`.
+    pub fn new(plugin: &'a LoadedPlugin) -> Self {
+        Self { plugin }
+    }
+
+    fn run(&self, config: &Config, working_dir: &Path) -> Result<CaptureResult> {
+        info!("🔌 Using capture plugin: {}", self.plugin.name());
+
+        let current_dir = working_dir.join(&config.output_dir).join("current");
+        std::fs::create_dir_all(&current_dir).context("Failed to create output directory")?;
+
+        let input = capture_input(config, &current_dir)?;
+
+        let output: CaptureOutput =
+            executor::execute_hook(self.plugin, "capture", &input, working_dir)?;
+
+        let mut result = CaptureResult::default();
+        for shot in output.captured {
+            result.apply(CaptureEvent::Captured(CapturedShot {
+                name: shot.name,
+                path: PathBuf::from(shot.path),
+                element: shot.element,
+                masked: shot.masked,
+                stability: shot.stability,
+                duration_ms: None,
+                page_errors: shot.page_errors,
+            }));
+        }
+        for shot in output.failed {
+            result.apply(CaptureEvent::Failed(FailedShot {
+                name: shot.name,
+                error: shot.error,
+                duration_ms: None,
+                page_errors: Default::default(),
+                artifacts: Vec::new(),
+            }));
+        }
+
+        let screenshots = || result.captured.iter().chain(&result.unstable);
+        save_masked_regions(&current_dir, screenshots())?;
+        save_capture_stats(&current_dir, screenshots())?;
+        save_page_errors(&current_dir, &result)?;
+
+        Ok(result)
+    }
+}
+
+impl CaptureBackend for PluginBackend<'_> {
+    fn name(&self) -> &str {
+        self.plugin.name()
+    }
+
+    fn capture<'a>(
+        &'a self,
+        config: &'a Config,
+        working_dir: &'a Path,
+        on_event: &'a mut EventHandler<'_>,
+    ) -> CaptureFuture<'a> {
+        Box::pin(async move {
+            let result = self.run(config, working_dir)?;
+            report_events(&result, on_event);
+            Ok(result)
+        })
+    }
+}
+
+/// Builds the `capture` hook input for `config`, writing into `current_dir`.
+///
+/// `fullPage` is passed at each level unresolved, so plugins pick it per shot and
+/// viewport the way the capture script does.
+fn capture_input(config: &Config, current_dir: &Path) -> Result<CaptureInput> {
+    Ok(CaptureInput {
+        shots: config
+            .shots
+            .iter()
+            .map(|s| {
+                Ok(CaptureShot {
+                    name: s.name.clone(),
+                    path: s.path.clone(),
+                    wait_for: s.wait_for.clone(),
+                    delay: s.delay,
+                    actions: s.actions.clone(),
+                    selector: s.selector.clone(),
+                    padding: s.padding,
+                    full_page: s.full_page,
+                    mask: config.mask.iter().chain(&s.mask).cloned().collect(),
+                    stabilize: config.stabilize_for(s),
+                    routes: config.routes_for(s),
+                    auth: config.auth_role_for(s)?,
+                    stable_capture: config.stable_capture_for(s),
+                    ready: config.ready_for(s),
+                    timeouts: config.timeouts_for(s),
+                    page_errors: config.page_errors_for(s),
+                    a11y: config.a11y_for(s),
+                    dom_snapshot: config.dom_snapshot_for(s),
+                    screenshots: screenshots_of(config, s)?,
+                })
+            })
+            .collect::<Result<_>>()?,
+        base_url: config.base_url.clone(),
+        viewport: CaptureViewport {
+            width: config.viewport.width,
+            height: config.viewport.height,
+        },
+        viewports: if config.viewports.is_empty() {
+            Vec::new()
+        } else {
+            config.effective_viewports()?
+        },
+        browsers: config.effective_browsers(),
+        output_dir: current_dir.to_string_lossy().to_string(),
+        full_page: config.full_page,
+        max_page_height: config.max_page_height,
+        setup: config.setup.clone(),
+        failure_artifacts: config.failure_artifacts,
+        options: serde_json::json!({}),
+    })
+}
+
+/// Copies pre-rendered screenshots from a directory instead of capturing them.
+///
+/// Each screenshot the Playwright backend would write, such as
+/// `button@mobile+scheme-dark.png`, is copied from `{dir}/{name}.png`, along with
+/// its `.a11y.yaml` and `.dom.json` snapshots when `a11y`/`domSnapshot` are on.
+/// Screenshots missing from the directory are reported as failed shots, and
+/// their files from an earlier run are removed from `current`.
+#[derive(Debug, Clone)]
+pub struct FixturesBackend {
+    dir: PathBuf,
+}
+
+impl FixturesBackend {
+    /// Creates a backend that copies screenshots from `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn run(&self, config: &Config, working_dir: &Path) -> Result<CaptureResult> {
+        if !self.dir.is_dir() {
+            anyhow::bail!(
+                "❌ Fixtures directory not found: {}\n\n\
+                 💡 Solution: Point `capture.dir` at a directory of pre-rendered PNGs, \
+                 relative to pixelguard.config.json.",
+                self.dir.display()
+            );
+        }
+
+        info!("📦 Copying screenshots from {}", self.dir.display());
+
+        let current_dir = working_dir.join(&config.output_dir).join("current");
+        std::fs::create_dir_all(&current_dir).context("Failed to create output directory")?;
+
+        let mut result = CaptureResult::default();
+        for (name, shot) in screenshot_names(config)? {
+            let source = self.dir.join(format!("{}.png", name));
+            if !source.is_file() {
+                // Drop files left by an earlier run so they are not compared as this one's
+                for suffix in [".png", A11Y_SNAPSHOT_SUFFIX, DOM_SNAPSHOT_SUFFIX] {
+                    let _ = std::fs::remove_file(current_dir.join(format!("{}{}", name, suffix)));
+                }
+                result.apply(CaptureEvent::Failed(FailedShot {
+                    name,
+                    error: format!("Fixture not found: {}", source.display()),
+                    duration_ms: None,
+                    page_errors: Default::default(),
+                    artifacts: Vec::new(),
+                }));
+                continue;
+            }
+
+            let path = current_dir.join(format!("{}.png", name));
+            std::fs::copy(&source, &path)
+                .with_context(|| format!("Failed to copy fixture {}", source.display()))?;
+
+            let snapshots = [
+                (config.a11y_for(shot), A11Y_SNAPSHOT_SUFFIX),
+                (config.dom_snapshot_for(shot), DOM_SNAPSHOT_SUFFIX),
+            ];
+            for (enabled, suffix) in snapshots {
+                let file = format!("{}{}", name, suffix);
+                if enabled && self.dir.join(&file).is_file() {
+                    std::fs::copy(self.dir.join(&file), current_dir.join(&file))
+                        .with_context(|| format!("Failed to copy fixture {}", file))?;
+                }
+            }
+
+            result.apply(CaptureEvent::Captured(CapturedShot {
+                name,
+                path,
+                element: None,
+                masked: Vec::new(),
+                stability: None,
+                duration_ms: None,
+                page_errors: Default::default(),
+            }));
+        }
+
+        let screenshots = || result.captured.iter().chain(&result.unstable);
+        save_masked_regions(&current_dir, screenshots())?;
+        save_capture_stats(&current_dir, screenshots())?;
+        save_page_errors(&current_dir, &result)?;
+
+        Ok(result)
+    }
+}
+
+impl CaptureBackend for FixturesBackend {
+    fn name(&self) -> &str {
+        "fixtures"
+    }
+
+    fn capture<'a>(
+        &'a self,
+        config: &'a Config,
+        working_dir: &'a Path,
+        on_event: &'a mut EventHandler<'_>,
+    ) -> CaptureFuture<'a> {
+        Box::pin(async move {
+            let result = self.run(config, working_dir)?;
+            report_events(&result, on_event);
+            Ok(result)
+        })
+    }
+}
+
+/// Returns the screenshot name of every shot, viewport, browser and emulation
+/// combination to capture, with its shot, named as the capture script names them.
+fn screenshot_names(config: &Config) -> Result<Vec<(String, &Shot)>> {
+    let mut names = Vec::new();
+    for shot in &config.shots {
+        for screenshot in screenshots_of(config, shot)? {
+            names.push((screenshot.name, shot));
+        }
+    }
+    Ok(names)
+}
+
+/// Returns every viewport, browser and emulation combination to capture `shot` in.
+fn screenshots_of(config: &Config, shot: &Shot) -> Result<Vec<CaptureScreenshot>> {
+    let emulations = config.effective_emulations();
+    let mut screenshots = Vec::new();
+    for viewport in config.viewports_for(shot)? {
+        for browser in config.browsers_for(shot)? {
+            for emulation in &emulations {
+                let mut name = shot.name.clone();
+                if viewport.name != "default" {
+                    name.push_str(&format!("@{}", viewport.name));
+                }
+                if !config.browsers.is_empty() {
+                    name.push_str(&format!("~{}", browser));
+                }
+                name.push_str(&emulation.suffix());
+                screenshots.push(CaptureScreenshot {
+                    name,
+                    viewport: viewport.name.clone(),
+                    browser,
+                    emulation: emulation.clone(),
+                });
+            }
+        }
+    }
+    Ok(screenshots)
+}
+
+/// Reports the shots of a finished capture to `on_event`, in the order a
+/// streaming backend would.
+fn report_events(result: &CaptureResult, on_event: &mut EventHandler<'_>) {
+    let total = result.total();
+    on_event(&CaptureEvent::Started { total });
+    let captured = result.captured.iter().chain(&result.unstable);
+    let events = captured
+        .cloned()
+        .map(CaptureEvent::Captured)
+        .chain(result.failed.iter().cloned().map(CaptureEvent::Failed));
+    for (completed, event) in events.enumerate() {
+        on_event(&event);
+        on_event(&CaptureEvent::Progress {
+            completed: completed + 1,
+            total,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Browser, ColorScheme, NamedViewport};
+
+    fn shot(name: &str) -> Shot {
+        Shot {
+            name: name.to_string(),
+            path: "/".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn screenshot_names_match_capture_script() {
+        let config = Config {
+            viewports: vec![
+                NamedViewport {
+                    name: "desktop".to_string(),
+                    width: 1280,
+                    height: 720,
+                    ..Default::default()
+                },
+                NamedViewport {
+                    name: "mobile".to_string(),
+                    width: 375,
+                    height: 667,
+                    ..Default::default()
+                },
+            ],
+            browsers: vec![Browser::Chromium, Browser::Firefox],
+            color_scheme: vec![ColorScheme::Dark],
+            shots: vec![Shot {
+                viewports: vec!["mobile".to_string()],
+                ..shot("button")
+            }],
+            ..Default::default()
+        };
+
+        let names: Vec<_> = screenshot_names(&config)
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "button@mobile~chromium+scheme-dark",
+                "button@mobile~firefox+scheme-dark"
+            ]
+        );
+
+        let plain = Config {
+            shots: vec![shot("home")],
+            ..Default::default()
+        };
+        assert_eq!(screenshot_names(&plain).unwrap()[0].0, "home");
+    }
+
+    #[tokio::test]
+    async fn fixtures_backend_copies_screenshots_and_reports_missing_ones() {
+        let fixtures = tempfile::tempdir().unwrap();
+        let working_dir = tempfile::tempdir().unwrap();
+        std::fs::write(fixtures.path().join("home.png"), b"png").unwrap();
+        std::fs::write(fixtures.path().join("home.a11y.yaml"), b"{}").unwrap();
+
+        let current = working_dir.path().join(".pixelguard/current");
+        std::fs::create_dir_all(&current).unwrap();
+        for stale in ["about.png", "about.a11y.yaml", "about.dom.json"] {
+            std::fs::write(current.join(stale), b"stale").unwrap();
+        }
+
+        let config = Config {
+            a11y: true,
+            shots: vec![shot("home"), shot("about")],
+            ..Default::default()
+        };
+
+        let mut events = Vec::new();
+        let result = FixturesBackend::new(fixtures.path())
+            .capture(&config, working_dir.path(), &mut |event| {
+                events.push(event.clone())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(current.join("home.png")).unwrap(), b"png");
+        assert!(current.join("home.a11y.yaml").is_file());
+        assert_eq!(result.captured.len(), 1);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].name, "about");
+        assert!(result.failed[0].error.contains("Fixture not found"));
+        assert!(!current.join("about.png").exists());
+        assert!(!current.join("about.a11y.yaml").exists());
+        assert!(!current.join("about.dom.json").exists());
+        assert_eq!(events[0], CaptureEvent::Started { total: 2 });
+        assert_eq!(events.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn fixtures_backend_requires_its_directory() {
+        let working_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            shots: vec![shot("home")],
+            ..Default::default()
+        };
+
+        let err = FixturesBackend::new(working_dir.path().join("missing"))
+            .capture(&config, working_dir.path(), &mut |_| {})
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Fixtures directory not found"));
+    }
+
+    #[test]
+    fn capture_input_passes_full_page_at_every_level() {
+        let config = Config {
+            full_page: true,
+            viewports: vec![NamedViewport {
+                name: "mobile".to_string(),
+                width: 375,
+                height: 667,
+                full_page: Some(false),
+                ..Default::default()
+            }],
+            shots: vec![
+                shot("inherits"),
+                Shot {
+                    full_page: Some(true),
+                    ..shot("overrides")
+                },
+            ],
+            ..Default::default()
+        };
+
+        let input = capture_input(&config, Path::new("current")).unwrap();
+        let json = serde_json::to_value(&input).unwrap();
+
+        assert_eq!(json["fullPage"], true);
+        assert_eq!(json["viewports"][0]["fullPage"], false);
+        assert!(json["shots"][0].get("fullPage").is_none());
+        assert_eq!(json["shots"][1]["fullPage"], true);
+    }
+
+    #[test]
+    fn capture_input_lists_each_shot_screenshot_combination() {
+        let config = Config {
+            browsers: vec![Browser::Chromium, Browser::Firefox],
+            color_scheme: vec![ColorScheme::Dark],
+            a11y: true,
+            shots: vec![
+                shot("home"),
+                Shot {
+                    browsers: vec![Browser::Firefox],
+                    ..shot("button")
+                },
+            ],
+            ..Default::default()
+        };
+
+        let input = capture_input(&config, Path::new("current")).unwrap();
+        let json = serde_json::to_value(&input).unwrap();
+
+        assert_eq!(json["browsers"], serde_json::json!(["chromium", "firefox"]));
+        assert_eq!(
+            json["shots"][0]["screenshots"],
+            serde_json::json!([
+                {
+                    "name": "home~chromium+scheme-dark",
+                    "viewport": "default",
+                    "browser": "chromium",
+                    "emulation": { "colorScheme": "dark" }
+                },
+                {
+                    "name": "home~firefox+scheme-dark",
+                    "viewport": "default",
+                    "browser": "firefox",
+                    "emulation": { "colorScheme": "dark" }
+                }
+            ])
+        );
+        assert_eq!(
+            json["shots"][1]["screenshots"][0]["name"],
+            "button~firefox+scheme-dark"
+        );
+        assert_eq!(json["shots"][1]["screenshots"].as_array().unwrap().len(), 1);
+        assert_eq!(json["shots"][0]["a11y"], true);
+        assert_eq!(json["shots"][0]["pageErrors"]["fail"], false);
+    }
+
+    #[test]
+    fn selects_backend_from_config() {
+        let registry = PluginRegistry::new();
+        let with = |capture| Config {
+            capture: Some(capture),
+            ..Default::default()
+        };
+
+        let fixtures = with(CaptureBackendConfig::Fixtures {
+            dir: "fixtures".to_string(),
+        });
+        let backend = select_backend(&fixtures, Path::new("."), &registry).unwrap();
+        assert_eq!(backend.name(), "fixtures");
+        assert!(!backend.needs_playwright());
+
+        let backend = select_backend(&Config::default(), Path::new("."), &registry).unwrap();
+        assert_eq!(backend.name(), "playwright");
+        assert!(backend.needs_playwright());
+
+        let playwright = with(CaptureBackendConfig::Playwright);
+        let backend = select_backend(&playwright, Path::new("."), &registry).unwrap();
+        assert_eq!(backend.name(), "playwright");
+
+        // Asking for a plugin that isn't loaded is an error, not a silent fallback
+        let plugin = with(CaptureBackendConfig::Plugin);
+        assert!(select_backend(&plugin, Path::new("."), &registry).is_err());
+    }
+}
@@ -3,41 +3,206 @@
 //! This module generates and executes a Node.js script that uses Playwright
 //! to capture screenshots of configured shots.
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::Stdio;
 
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
 use tracing::{debug, info, warn};
 
-use crate::config::Config;
+use crate::a11y::A11Y_SNAPSHOT_SUFFIX;
+use crate::config::{
+    AuthRole, Browser, Config, FailureArtifacts, Region, RouteRule, Shot, ShotAuth, StableCapture,
+    Timeouts, HAR_SHOT_PLACEHOLDER,
+};
+use crate::dom::DOM_SNAPSHOT_SUFFIX;
 use crate::plugins::PluginRegistry;
 use crate::storage::Storage;
 
 /// Result of capturing screenshots.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct CaptureResult {
     /// List of successfully captured screenshots
     pub captured: Vec<CapturedShot>,
+    /// Screenshots taken with `stableCapture` that never stopped changing before the
+    /// timeout (the last frame is saved)
+    pub unstable: Vec<CapturedShot>,
     /// List of shots that failed to capture
     pub failed: Vec<FailedShot>,
+    /// Set when the capture process stopped early; the shots above are the
+    /// ones reported before it did
+    pub error: Option<String>,
+}
+
+impl CaptureResult {
+    /// Records an event reported by the capture process.
+    ///
+    /// Screenshots that never settled under `stableCapture` go to `unstable`.
+    pub fn apply(&mut self, event: CaptureEvent) {
+        match event {
+            CaptureEvent::Captured(shot) => {
+                if shot.stability.is_some_and(|s| !s.stable) {
+                    self.unstable.push(shot);
+                } else {
+                    self.captured.push(shot);
+                }
+            }
+            CaptureEvent::Failed(shot) => self.failed.push(shot),
+            CaptureEvent::Started { .. } | CaptureEvent::Progress { .. } => {}
+        }
+    }
+
+    /// Number of shots reported so far.
+    pub fn total(&self) -> usize {
+        self.captured.len() + self.unstable.len() + self.failed.len()
+    }
+
+    /// Screenshots whose page logged errors, failed or not.
+    pub fn with_page_errors(&self) -> impl Iterator<Item = (&str, &PageErrors)> {
+        self.captured
+            .iter()
+            .chain(&self.unstable)
+            .map(|shot| (shot.name.as_str(), &shot.page_errors))
+            .chain(
+                self.failed
+                    .iter()
+                    .map(|shot| (shot.name.as_str(), &shot.page_errors)),
+            )
+            .filter(|(_, errors)| !errors.is_empty())
+    }
+}
+
+/// An event streamed by the capture process, one JSON object per stdout line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CaptureEvent {
+    /// Capture is about to begin; `total` counts every shot, viewport, browser and emulation
+    Started { total: usize },
+    /// A screenshot (or HAR file) was written
+    Captured(CapturedShot),
+    /// A shot failed to capture
+    Failed(FailedShot),
+    /// Another shot finished, successfully or not
+    Progress { completed: usize, total: usize },
 }
 
 /// A successfully captured screenshot.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CapturedShot {
     /// Name of the shot
     pub name: String,
     /// Path to the captured screenshot file
     pub path: PathBuf,
+    /// Bounding box of the clipped element (including padding) on the page, in
+    /// screenshot pixels, when the shot uses a `selector`
+    #[serde(default)]
+    pub element: Option<Region>,
+    /// Areas painted over by `mask` selectors, in screenshot pixels
+    #[serde(default)]
+    pub masked: Vec<Region>,
+    /// Attempts and time taken, when the shot uses `stableCapture`
+    #[serde(default)]
+    pub stability: Option<Stability>,
+    /// Time from starting the shot to saving its screenshot, in milliseconds
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// Console errors, uncaught exceptions and failed requests of the page, less
+    /// those matching `pageErrors.allow`
+    #[serde(default)]
+    pub page_errors: PageErrors,
+}
+
+/// Errors a page reported while a shot was captured.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageErrors {
+    /// Messages logged with `console.error`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub console: Vec<String>,
+    /// Uncaught exceptions (`pageerror` events)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exceptions: Vec<String>,
+    /// Requests that failed or got an error status, as `METHOD url: reason`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requests: Vec<String>,
+}
+
+impl PageErrors {
+    /// Returns true if the page reported no errors.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of errors of every kind.
+    pub fn len(&self) -> usize {
+        self.console.len() + self.exceptions.len() + self.requests.len()
+    }
+
+    /// Returns every error with its kind ("console", "exception" or "request").
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("console", &self.console),
+            ("exception", &self.exceptions),
+            ("request", &self.requests),
+        ]
+        .into_iter()
+        .flat_map(|(kind, messages)| messages.iter().map(move |m| (kind, m.as_str())))
+    }
 }
 
+/// How a `stableCapture` shot settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stability {
+    /// Number of screenshots taken
+    pub attempts: u32,
+    /// Time spent taking them, in milliseconds
+    pub duration_ms: u64,
+    /// Whether two consecutive screenshots matched before the timeout
+    pub stable: bool,
+}
+
+/// File in the `current/` directory recording the masked regions of each screenshot.
+pub const MASKED_REGIONS_FILE: &str = "masked-regions.json";
+
+/// File in the `current/` directory recording the `stableCapture` attempts of each screenshot.
+pub const CAPTURE_STATS_FILE: &str = "capture-stats.json";
+
+/// File in the `current/` directory recording the page errors of each screenshot.
+pub const PAGE_ERRORS_FILE: &str = "page-errors.json";
+
+/// Directory under `outputDir` where `record_hars_in_dir` writes one HAR file per shot.
+pub const HAR_DIR: &str = "har";
+
+/// Directory under `outputDir` where login setup saves each role's storage state.
+pub const AUTH_DIR: &str = "auth";
+
+/// Directory under `outputDir` where `failureArtifacts` are saved, one directory
+/// per failed screenshot.
+pub const FAILURES_DIR: &str = "failures";
+
 /// A shot that failed to capture.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct FailedShot {
     /// Name of the shot
     pub name: String,
     /// Error message
     pub error: String,
+    /// Time spent on the shot before it failed, in milliseconds
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// The page errors that failed the shot, when `pageErrors.fail` is set
+    #[serde(default)]
+    pub page_errors: PageErrors,
+    /// Files saved to debug the failure with `failureArtifacts`, relative to
+    /// `outputDir` (e.g. `failures/button/page.html`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<String>,
 }
 
 /// Captures screenshots for all configured shots.
@@ -73,6 +238,19 @@ pub async fn capture_screenshots(config: &Config) -> Result<CaptureResult> {
 pub async fn capture_screenshots_in_dir<P: AsRef<Path>>(
     config: &Config,
     working_dir: P,
+) -> Result<CaptureResult> {
+    capture_screenshots_with_progress(config, working_dir, log_event).await
+}
+
+/// Captures screenshots like [`capture_screenshots_in_dir`], calling `on_event` as
+/// each shot is reported by the capture process.
+///
+/// If the process dies part-way through, the shots reported before it did are
+/// kept and returned with [`CaptureResult::error`] set.
+pub async fn capture_screenshots_with_progress<P: AsRef<Path>>(
+    config: &Config,
+    working_dir: P,
+    on_event: impl FnMut(&CaptureEvent),
 ) -> Result<CaptureResult> {
     let working_dir = working_dir.as_ref();
     let output_dir = working_dir.join(&config.output_dir).join("current");
@@ -81,25 +259,304 @@ pub async fn capture_screenshots_in_dir<P: AsRef<Path>>(
     std::fs::create_dir_all(&output_dir).context("Failed to create output directory")?;
 
     if config.shots.is_empty() {
-        return Ok(CaptureResult {
-            captured: Vec::new(),
-            failed: Vec::new(),
-        });
+        return Ok(CaptureResult::default());
     }
 
     // Generate and execute Playwright script
     let script = generate_playwright_script(config, &output_dir, working_dir)?;
-    let result = execute_playwright_script(&script, working_dir).await?;
+    let result = execute_playwright_script(config, &script, working_dir, on_event).await?;
+
+    save_masked_regions(&output_dir, result.captured.iter().chain(&result.unstable))?;
+    save_capture_stats(&output_dir, result.captured.iter().chain(&result.unstable))?;
+    save_page_errors(&output_dir, &result)?;
 
     Ok(result)
 }
 
+/// Records the network traffic of each configured shot to `{outputDir}/har/{shot}.har`.
+///
+/// Each shot is loaded once, at the first viewport, browser and emulation, with
+/// route rules switched off. The recorded files can be replayed with a route rule
+/// such as `{ "url": "**/api/**", "har": ".pixelguard/har/{shot}.har" }`.
+/// Captured entries point at the HAR files rather than screenshots.
+pub async fn record_hars_in_dir<P: AsRef<Path>>(
+    config: &Config,
+    working_dir: P,
+) -> Result<CaptureResult> {
+    let working_dir = working_dir.as_ref();
+    let output_dir = working_dir.join(&config.output_dir).join(HAR_DIR);
+
+    std::fs::create_dir_all(&output_dir).context("Failed to create HAR output directory")?;
+
+    if config.shots.is_empty() {
+        return Ok(CaptureResult::default());
+    }
+
+    let script = build_playwright_script(config, &output_dir, working_dir, ScriptMode::RecordHar)?;
+    execute_playwright_script(config, &script, working_dir, log_event).await
+}
+
+/// Default event handler: logs each shot as it is reported.
+fn log_event(event: &CaptureEvent) {
+    match event {
+        CaptureEvent::Captured(shot) => info!("✅ Captured {}", shot.name),
+        CaptureEvent::Failed(shot) => warn!("❌ {}: {}", shot.name, shot.error),
+        CaptureEvent::Started { .. } | CaptureEvent::Progress { .. } => {}
+    }
+}
+
+/// Records the masked regions of captured screenshots next to them.
+///
+/// Entries for shots that were not captured in this run are kept, so that
+/// filtered runs don't drop the regions of other shots.
+pub fn save_masked_regions<'a>(
+    current_dir: &Path,
+    captured: impl IntoIterator<Item = &'a CapturedShot>,
+) -> Result<()> {
+    let mut regions = load_masked_regions(current_dir);
+    for shot in captured {
+        if shot.masked.is_empty() {
+            regions.remove(&shot.name);
+        } else {
+            regions.insert(shot.name.clone(), shot.masked.clone());
+        }
+    }
+
+    let path = current_dir.join(MASKED_REGIONS_FILE);
+    if regions.is_empty() {
+        let _ = std::fs::remove_file(&path);
+        return Ok(());
+    }
+
+    let content = serde_json::to_string_pretty(&regions)?;
+    std::fs::write(&path, content)
+        .with_context(|| format!("Failed to write masked regions to {}", path.display()))?;
+    Ok(())
+}
+
+/// Loads the masked regions recorded for current screenshots, keyed by screenshot name.
+///
+/// Returns an empty map if no regions were recorded.
+pub fn load_masked_regions(current_dir: &Path) -> BTreeMap<String, Vec<Region>> {
+    std::fs::read_to_string(current_dir.join(MASKED_REGIONS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// What a generated Playwright script does with each shot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScriptMode {
+    /// Capture screenshots across the full viewport, browser and emulation matrix
+    Capture,
+    /// Record a HAR file per shot, without route rules
+    RecordHar,
+}
+
+/// Records the `stableCapture` attempts of captured screenshots next to them.
+///
+/// Like masked regions, entries for shots not captured in this run are kept.
+pub fn save_capture_stats<'a>(
+    current_dir: &Path,
+    captured: impl IntoIterator<Item = &'a CapturedShot>,
+) -> Result<()> {
+    let mut stats = load_capture_stats(current_dir);
+    for shot in captured {
+        match shot.stability {
+            Some(stability) => stats.insert(shot.name.clone(), stability),
+            None => stats.remove(&shot.name),
+        };
+    }
+
+    let path = current_dir.join(CAPTURE_STATS_FILE);
+    if stats.is_empty() {
+        let _ = std::fs::remove_file(&path);
+        return Ok(());
+    }
+
+    let content = serde_json::to_string_pretty(&stats)?;
+    std::fs::write(&path, content)
+        .with_context(|| format!("Failed to write capture stats to {}", path.display()))?;
+    Ok(())
+}
+
+/// Loads the `stableCapture` attempts recorded for current screenshots, keyed by
+/// screenshot name.
+///
+/// Returns an empty map if nothing was recorded.
+pub fn load_capture_stats(current_dir: &Path) -> BTreeMap<String, Stability> {
+    std::fs::read_to_string(current_dir.join(CAPTURE_STATS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Records the page errors of every reported shot, failed ones included, next to
+/// the screenshots.
+///
+/// Like masked regions, entries for shots not captured in this run are kept.
+pub fn save_page_errors(current_dir: &Path, result: &CaptureResult) -> Result<()> {
+    let mut errors = load_page_errors(current_dir);
+    let shots = result
+        .captured
+        .iter()
+        .chain(&result.unstable)
+        .map(|shot| (&shot.name, &shot.page_errors))
+        .chain(
+            result
+                .failed
+                .iter()
+                .map(|shot| (&shot.name, &shot.page_errors)),
+        );
+    for (name, page_errors) in shots {
+        if page_errors.is_empty() {
+            errors.remove(name);
+        } else {
+            errors.insert(name.clone(), page_errors.clone());
+        }
+    }
+
+    let path = current_dir.join(PAGE_ERRORS_FILE);
+    if errors.is_empty() {
+        let _ = std::fs::remove_file(&path);
+        return Ok(());
+    }
+
+    let content = serde_json::to_string_pretty(&errors)?;
+    std::fs::write(&path, content)
+        .with_context(|| format!("Failed to write page errors to {}", path.display()))?;
+    Ok(())
+}
+
+/// Loads the page errors recorded for current screenshots, keyed by screenshot name.
+///
+/// Returns an empty map if nothing was recorded.
+pub fn load_page_errors(current_dir: &Path) -> BTreeMap<String, PageErrors> {
+    std::fs::read_to_string(current_dir.join(PAGE_ERRORS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
 /// Generates a Playwright script for capturing screenshots.
 fn generate_playwright_script(
     config: &Config,
     output_dir: &Path,
     working_dir: &Path,
 ) -> Result<String> {
+    build_playwright_script(config, output_dir, working_dir, ScriptMode::Capture)
+}
+
+/// Converts a path to a forward-slash string for embedding in the script.
+fn script_path(path: &Path) -> Result<String> {
+    Ok(path
+        .to_str()
+        .with_context(|| format!("Path is not valid UTF-8: {}", path.display()))?
+        .replace('\\', "/"))
+}
+
+/// Resolves a shot's route rules for the script.
+///
+/// Rules are validated, file paths are made absolute against the working directory
+/// and `{shot}` in HAR paths is replaced by the shot name. HAR rules whose file does
+/// not exist are skipped with a warning, so shots that were never recorded fall
+/// through to the network.
+fn resolve_routes(config: &Config, shot: &Shot, working_dir: &Path) -> Result<Vec<RouteRule>> {
+    let mut routes = Vec::new();
+    for mut rule in config.routes_for(shot) {
+        rule.validate()
+            .with_context(|| format!("❌ Invalid route rule for shot '{}'", shot.name))?;
+
+        if let Some(fixture) = &rule.fixture {
+            let path = working_dir.join(fixture);
+            if !path.is_file() {
+                anyhow::bail!(
+                    "❌ Route fixture not found for shot '{}': {}\n\n\
+                     💡 Fixture paths are relative to the directory containing pixelguard.config.json.",
+                    shot.name,
+                    path.display()
+                );
+            }
+            rule.fixture = Some(script_path(&path.canonicalize()?)?);
+        }
+
+        if let Some(har) = &rule.har {
+            let path = working_dir.join(har.replace(HAR_SHOT_PLACEHOLDER, &shot.name));
+            if !path.is_file() {
+                warn!(
+                    "⚠️  HAR file for shot '{}' not found, skipping route {}: {}",
+                    shot.name,
+                    rule.matcher(),
+                    path.display()
+                );
+                continue;
+            }
+            rule.har = Some(script_path(&path.canonicalize()?)?);
+        }
+
+        routes.push(rule);
+    }
+    Ok(routes)
+}
+
+/// Resolves the login flows of the auth roles used by `shots`, keyed by role name.
+///
+/// Script paths are made absolute against the working directory.
+fn resolve_auth_roles(
+    config: &Config,
+    shots: &[Shot],
+    working_dir: &Path,
+) -> Result<BTreeMap<String, AuthRole>> {
+    let mut roles = BTreeMap::new();
+    let Some(setup) = &config.setup else {
+        return Ok(roles);
+    };
+    setup.validate()?;
+
+    for shot in shots {
+        let Some(ShotAuth::Role(name)) = &shot.auth else {
+            continue;
+        };
+        if roles.contains_key(name) {
+            continue;
+        }
+        let mut role = setup.roles[name].clone();
+        if let Some(script) = &role.script {
+            let path = working_dir.join(script);
+            if !path.is_file() {
+                anyhow::bail!(
+                    "❌ Login script for role '{}' not found: {}\n\n\
+                     💡 Script paths are relative to the directory containing pixelguard.config.json.",
+                    name,
+                    path.display()
+                );
+            }
+            role.script = Some(script_path(&path.canonicalize()?)?);
+        }
+        roles.insert(name.clone(), role);
+    }
+    Ok(roles)
+}
+
+/// A shot as passed to the capture script.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScriptShot {
+    #[serde(flatten)]
+    shot: Shot,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stable_capture: Option<StableCapture>,
+}
+
+/// Generates a Playwright script for the given mode.
+fn build_playwright_script(
+    config: &Config,
+    output_dir: &Path,
+    working_dir: &Path,
+    mode: ScriptMode,
+) -> Result<String> {
+    let record_har = mode == ScriptMode::RecordHar;
+
     let output_dir_str = output_dir
         .to_str()
         .context("Output directory path is not valid UTF-8")?
@@ -110,124 +567,878 @@ fn generate_playwright_script(
         .context("Working directory path is not valid UTF-8")?
         .replace('\\', "/");
 
-    let shots_json = serde_json::to_string(&config.shots)?;
-
-    let viewports = config.effective_viewports();
+    // Shots carry their resolved stabilization settings, readiness conditions,
+    // timeout overrides, accessibility snapshot switch, auth role and route rules;
+    // recording always goes to the network
+    let shots = config
+        .shots
+        .iter()
+        .map(|shot| {
+            Ok(Shot {
+                stabilize: config.stabilize_for(shot),
+                ready: config.ready_for(shot),
+                timeouts: shot.timeouts.is_some().then(|| config.timeouts_for(shot)),
+                page_errors: shot
+                    .page_errors
+                    .is_some()
+                    .then(|| config.page_errors_for(shot)),
+                a11y: (config.a11y_for(shot) && !record_har).then_some(true),
+                dom_snapshot: (config.dom_snapshot_for(shot) && !record_har).then_some(true),
+                auth: config.auth_role_for(shot)?.map(ShotAuth::Role),
+                routes: if record_har {
+                    Vec::new()
+                } else {
+                    resolve_routes(config, shot, working_dir)?
+                },
+                ..shot.clone()
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // ...their resolved stableCapture settings, in place of the on/off override,
+    // and the viewports (in this shard) and browsers they are captured at, when
+    // not all of them
+    let all_viewports = config.effective_viewports()?.len();
+    let all_browsers = config.effective_browsers().len();
+    let script_shots: Vec<ScriptShot> = shots
+        .iter()
+        .zip(&config.shots)
+        .map(|(shot, configured)| {
+            let mut viewports = Vec::new();
+            let mut browsers = Vec::new();
+            if !record_har {
+                let selected = config.viewports_for(configured)?;
+                if selected.len() < all_viewports {
+                    viewports = selected.into_iter().map(|viewport| viewport.name).collect();
+                }
+                let selected = config.browsers_for(configured)?;
+                if selected.len() < all_browsers {
+                    browsers = selected;
+                }
+            }
+            Ok(ScriptShot {
+                shot: Shot {
+                    stable_capture: None,
+                    viewports,
+                    browsers,
+                    ..shot.clone()
+                },
+                stable_capture: config.stable_capture_for(configured),
+            })
+        })
+        .collect::<Result<_>>()?;
+    let shots_json = serde_json::to_string(&script_shots)?;
+
+    let auth_roles_json = serde_json::to_string(&resolve_auth_roles(config, &shots, working_dir)?)?;
+    let auth_dir = working_dir.join(&config.output_dir).join(AUTH_DIR);
+    let failures_dir = working_dir.join(&config.output_dir).join(FAILURES_DIR);
+    let failure_artifacts = if record_har {
+        FailureArtifacts::Off
+    } else {
+        config.failure_artifacts
+    };
+
+    // Recording loads each shot once, at the first viewport, browser and emulation
+    let mut viewports = config.effective_viewports()?;
+    let mut browsers = config.effective_browsers();
+    let mut effective_emulations = config.effective_emulations();
+    if record_har {
+        viewports.truncate(1);
+        browsers.truncate(1);
+        effective_emulations.truncate(1);
+    }
     let viewports_json = serde_json::to_string(&viewports)?;
 
+    let browsers_json = serde_json::to_string(&browsers)?;
+    config.browser.validate(&browsers)?;
+
+    // Each emulation combination carries its file name suffix
+    let emulations = effective_emulations
+        .iter()
+        .map(|emulation| {
+            let mut value = serde_json::to_value(emulation)?;
+            value["suffix"] = emulation.suffix().into();
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let emulations_json = serde_json::to_string(&emulations)?;
+
     let script = format!(
         r#"
 // Resolve playwright from the project's node_modules
+const fs = require('fs');
 const path = require('path');
 const playwrightPath = require.resolve('playwright', {{ paths: [{working_dir}] }});
-const {{ chromium }} = require(playwrightPath);
+const playwright = require(playwrightPath);
 
 const config = {{
     baseUrl: {base_url},
     viewports: {viewports},
+    browsers: {browsers},
+    browserSuffix: {browser_suffix},
+    emulations: {emulations},
     outputDir: {output_dir},
     shots: {shots},
-    concurrency: {concurrency}
+    mask: {mask},
+    concurrency: {concurrency},
+    reuseContexts: {reuse_contexts},
+    fullPage: {full_page},
+    maxPageHeight: {max_page_height},
+    recordHar: {record_har},
+    authRoles: {auth_roles},
+    authDir: {auth_dir},
+    timeouts: {timeouts},
+    pageErrors: {page_errors},
+    failuresDir: {failures_dir},
+    failureArtifacts: {failure_artifacts},
+    browser: {browser}
+}};
+
+// Write one event to stdout as a line of JSON; Rust reads them as they arrive
+function emit(event) {{
+    process.stdout.write(JSON.stringify(event) + '\n');
+}}
+
+// Generate the screenshot name for a shot+viewport+browser+emulation combination
+function getShotName(shotName, viewportName, browserName, emulation) {{
+    let name = shotName;
+    if (viewportName !== 'default') {{
+        name += `@${{viewportName}}`;
+    }}
+    if (config.browserSuffix) {{
+        name += `~${{browserName}}`;
+    }}
+    return name + emulation.suffix;
+}}
+
+// Run a shot's interaction steps in order
+async function runActions(page, actions, timeout) {{
+    for (const action of actions || []) {{
+        switch (action.type) {{
+            case 'click':
+                await page.click(action.selector, {{ timeout }});
+                break;
+            case 'hover':
+                await page.hover(action.selector, {{ timeout }});
+                break;
+            case 'fill':
+                await page.fill(action.selector, action.value, {{ timeout }});
+                break;
+            case 'press':
+                if (action.selector) {{
+                    await page.press(action.selector, action.key, {{ timeout }});
+                }} else {{
+                    await page.keyboard.press(action.key);
+                }}
+                break;
+            case 'scroll':
+                if (action.selector) {{
+                    await page.locator(action.selector).first().scrollIntoViewIfNeeded({{ timeout }});
+                }} else {{
+                    await page.evaluate(([x, y]) => window.scrollTo(x, y), [action.x || 0, action.y || 0]);
+                }}
+                break;
+            case 'waitForSelector':
+                await page.waitForSelector(action.selector, {{ timeout }});
+                break;
+            case 'evaluate':
+                await page.evaluate(action.script);
+                break;
+            default:
+                throw new Error(`Unknown action type: ${{action.type}}`);
+        }}
+    }}
+}}
+
+// Settle with `promise`, or fail with `message` once `timeout` milliseconds pass
+async function withTimeout(promise, timeout, message) {{
+    let timer;
+    const expired = new Promise((_, reject) => {{
+        timer = setTimeout(() => reject(new Error(`${{message}} after ${{timeout}}ms`)), timeout);
+    }});
+    try {{
+        return await Promise.race([promise, expired]);
+    }} finally {{
+        clearTimeout(timer);
+    }}
+}}
+
+// Wait for a shot's readiness conditions: web fonts, decoded images and the app's
+// ready signal, each within the wait timeout
+async function waitForReady(page, ready, timeout) {{
+    if (!ready) {{
+        return;
+    }}
+    if (ready.fonts) {{
+        await withTimeout(
+            page.evaluate(() => document.fonts.ready.then(() => undefined)),
+            timeout,
+            'Web fonts still loading'
+        );
+    }}
+    if (ready.images) {{
+        // Lazy images outside the viewport only load once scrolled to
+        await withTimeout(
+            page.evaluate(() => Promise.all(Array.from(document.images)
+                .filter(img => img.complete || img.loading !== 'lazy')
+                .map(img => new Promise(resolve => {{
+                    if (img.complete) {{
+                        resolve();
+                        return;
+                    }}
+                    img.addEventListener('load', resolve, {{ once: true }});
+                    img.addEventListener('error', resolve, {{ once: true }});
+                }}).then(() => img.decode().catch(() => {{}}))))
+                .then(() => undefined)),
+            timeout,
+            'Images still loading'
+        );
+    }}
+    if (ready.signal) {{
+        await page.waitForFunction(ready.signal, null, {{ timeout }}).catch(() => {{
+            throw new Error(`Ready signal not set after ${{timeout}}ms: ${{ready.signal}}`);
+        }});
+    }}
+}}
+
+// Compute the clip rectangle for an element, expanded by padding
+async function getElementClip(page, selector, padding, timeout) {{
+    const element = page.locator(selector).first();
+    await element.waitFor({{ state: 'visible', timeout }});
+    await element.scrollIntoViewIfNeeded({{ timeout }});
+    const box = await element.boundingBox();
+    if (!box) {{
+        throw new Error(`Element is not visible: ${{selector}}`);
+    }}
+    const x = Math.max(0, Math.floor(box.x - padding));
+    const y = Math.max(0, Math.floor(box.y - padding));
+    return {{
+        x,
+        y,
+        width: Math.ceil(box.x + box.width + padding) - x,
+        height: Math.ceil(box.y + box.height + padding) - y
+    }};
+}}
+
+// Convert a region in CSS pixels to screenshot pixels, like the PNG at `deviceScaleFactor`
+function toScreenshotPixels(region, scale) {{
+    return {{
+        x: Math.round(region.x * scale),
+        y: Math.round(region.y * scale),
+        width: Math.round(region.width * scale),
+        height: Math.round(region.height * scale)
+    }};
+}}
+
+// Scroll through the page so lazy-loaded content renders, then back to the top
+async function scrollThroughPage(page, maxHeight, timeout) {{
+    await page.evaluate(async (maxHeight) => {{
+        const step = window.innerHeight;
+        let position = 0;
+        while (true) {{
+            const limit = maxHeight ? Math.min(document.documentElement.scrollHeight, maxHeight) : document.documentElement.scrollHeight;
+            if (position + step >= limit) break;
+            position += step;
+            window.scrollTo(0, position);
+            await new Promise(resolve => setTimeout(resolve, 100));
+        }}
+        window.scrollTo(0, 0);
+    }}, maxHeight);
+    await page.waitForLoadState('networkidle', {{ timeout }}).catch(() => {{}});
+}}
+
+// Screenshot options for a full-page capture, capped at maxPageHeight if set
+async function getFullPageOptions(page, viewport, timeout) {{
+    await scrollThroughPage(page, config.maxPageHeight, timeout);
+    if (!config.maxPageHeight) {{
+        return {{ fullPage: true }};
+    }}
+    const pageHeight = await page.evaluate(() => document.documentElement.scrollHeight);
+    return {{
+        fullPage: true,
+        clip: {{ x: 0, y: 0, width: viewport.width, height: Math.min(pageHeight, config.maxPageHeight) }}
+    }};
+}}
+
+// Bounding boxes of all masked elements, relative to the screenshot origin,
+// in screenshot pixels (CSS pixels multiplied by the device scale factor)
+async function getMaskedRegions(page, locators, origin, scale) {{
+    const regions = [];
+    for (const locator of locators) {{
+        for (const element of await locator.all()) {{
+            const box = await element.boundingBox();
+            if (!box) continue;
+            const x = Math.max(0, Math.floor((box.x - origin.x) * scale));
+            const y = Math.max(0, Math.floor((box.y - origin.y) * scale));
+            const width = Math.ceil((box.x + box.width - origin.x) * scale) - x;
+            const height = Math.ceil((box.y + box.height - origin.y) * scale) - y;
+            if (width > 0 && height > 0) {{
+                regions.push({{ x, y, width, height }});
+            }}
+        }}
+    }}
+    return regions;
+}}
+
+// Computed styles recorded for each element of a DOM snapshot
+const DOM_STYLE_PROPERTIES = [
+    'display', 'position', 'top', 'right', 'bottom', 'left', 'z-index',
+    'width', 'height', 'box-sizing', 'overflow',
+    'margin-top', 'margin-right', 'margin-bottom', 'margin-left',
+    'padding-top', 'padding-right', 'padding-bottom', 'padding-left',
+    'border-top-width', 'border-right-width', 'border-bottom-width', 'border-left-width',
+    'border-color', 'border-style', 'border-radius',
+    'flex-direction', 'flex-wrap', 'justify-content', 'align-items', 'gap',
+    'grid-template-columns', 'grid-template-rows',
+    'font-family', 'font-size', 'font-weight', 'font-style', 'line-height',
+    'letter-spacing', 'text-align', 'text-transform', 'text-decoration-line', 'white-space',
+    'color', 'background-color', 'background-image', 'box-shadow',
+    'opacity', 'visibility', 'transform'
+];
+
+// Serialize the shot's root element and its descendants with their boxes and
+// computed styles. Elements are keyed by a path of tag, id and first classes,
+// with a [n] suffix for repeated siblings; hidden subtrees are not descended into.
+async function getDomSnapshot(page, selector) {{
+    return page.evaluate(({{ selector, properties, limit }}) => {{
+        const skipped = new Set(['SCRIPT', 'STYLE', 'LINK', 'META', 'NOSCRIPT', 'TEMPLATE']);
+        const round = value => Math.round(value * 10) / 10;
+        const describe = element => {{
+            let key = element.tagName.toLowerCase();
+            if (element.id) key += `#${{element.id}}`;
+            for (const name of [...element.classList].slice(0, 2)) key += `.${{name}}`;
+            return key;
+        }};
+        const elements = [];
+        const visit = (element, path) => {{
+            if (elements.length >= limit) return;
+            const rect = element.getBoundingClientRect();
+            const style = getComputedStyle(element);
+            const styles = {{}};
+            for (const property of properties) {{
+                styles[property] = style.getPropertyValue(property);
+            }}
+            elements.push({{
+                path,
+                box: {{
+                    x: round(rect.x + window.scrollX),
+                    y: round(rect.y + window.scrollY),
+                    width: round(rect.width),
+                    height: round(rect.height)
+                }},
+                styles
+            }});
+            if (style.display === 'none') return;
+            const seen = {{}};
+            for (const child of element.children) {{
+                if (skipped.has(child.tagName)) continue;
+                const key = describe(child);
+                seen[key] = (seen[key] || 0) + 1;
+                visit(child, `${{path}} > ${{key}}${{seen[key] > 1 ? `[${{seen[key]}}]` : ''}}`);
+            }}
+        }};
+        const root = (selector && document.querySelector(selector)) || document.body;
+        visit(root, describe(root));
+        return {{ elements }};
+    }}, {{ selector, properties: DOM_STYLE_PROPERTIES, limit: 5000 }});
+}}
+
+// Register a shot's route rules. Rules arrive in priority order, but Playwright
+// tries the most recently registered route first, so register them in reverse.
+// Requests the rules abort or fulfil are added to `handled`.
+async function registerRoutes(page, routes, handled) {{
+    for (const rule of [...(routes || [])].reverse()) {{
+        const matcher = rule.urlRegex ? new RegExp(rule.urlRegex) : rule.url;
+        if (rule.har) {{
+            await page.routeFromHAR(rule.har, {{ url: matcher, notFound: 'fallback' }});
+        }} else if (rule.abort) {{
+            await page.route(matcher, route => {{
+                handled.add(route.request());
+                return route.abort();
+            }});
+        }} else if (rule.fixture) {{
+            await page.route(matcher, route => {{
+                handled.add(route.request());
+                return route.fulfill({{
+                    path: rule.fixture,
+                    status: rule.status ?? 200,
+                    contentType: rule.contentType
+                }});
+            }});
+        }} else {{
+            await page.route(matcher, route => {{
+                handled.add(route.request());
+                return route.fulfill({{
+                    status: rule.status ?? 200,
+                    contentType: rule.contentType ?? 'application/json',
+                    body: JSON.stringify(rule.json)
+                }});
+            }});
+        }}
+    }}
+}}
+
+// Collect a page's console errors, uncaught exceptions, and requests that fail or
+// get an error status (except those answered by route rules)
+function collectPageErrors(page, handled) {{
+    const errors = {{ console: [], exceptions: [], requests: [] }};
+    page.on('console', message => {{
+        if (message.type() === 'error') {{
+            errors.console.push(message.text());
+        }}
+    }});
+    page.on('pageerror', error => errors.exceptions.push(error.message));
+    page.on('requestfailed', request => {{
+        if (!handled.has(request)) {{
+            errors.requests.push(`${{request.method()}} ${{request.url()}}: ${{request.failure()?.errorText ?? 'failed'}}`);
+        }}
+    }});
+    page.on('response', response => {{
+        if (response.status() >= 400 && !handled.has(response.request())) {{
+            errors.requests.push(`${{response.request().method()}} ${{response.url()}}: ${{response.status()}}`);
+        }}
+    }});
+    return errors;
+}}
+
+// Page errors less those matching an `allow` pattern (left out of the policy when
+// there are none), or undefined if none are left
+function unexpectedErrors(errors, allow) {{
+    const patterns = (allow || []).map(pattern => new RegExp(pattern));
+    const keep = messages => messages.filter(message => !patterns.some(pattern => pattern.test(message)));
+    const left = {{ console: keep(errors.console), exceptions: keep(errors.exceptions), requests: keep(errors.requests) }};
+    const count = left.console.length + left.exceptions.length + left.requests.length;
+    return count > 0 ? {{ ...left, count }} : undefined;
+}}
+
+// Storage state file written by login setup for an auth role
+function authStatePath(role) {{
+    return `${{config.authDir}}/${{role}}.json`;
+}}
+
+// Log in once per auth role used by the shots and save each role's storage
+// state (cookies and localStorage) for the shots' browser contexts
+async function runSetup(browser) {{
+    const viewport = config.viewports[0];
+    for (const [role, setup] of Object.entries(config.authRoles)) {{
+        console.error(`Logging in: ${{role}}`);
+        const context = await browser.newContext({{
+            viewport: {{ width: viewport.width, height: viewport.height }}
+        }});
+        const page = await context.newPage();
+        try {{
+            if (setup.script) {{
+                const login = require(setup.script);
+                await (login.default || login)(page, {{ baseUrl: config.baseUrl, role }});
+            }} else {{
+                await page.goto(config.baseUrl + (setup.path || '/'), {{
+                    waitUntil: 'networkidle',
+                    timeout: config.timeouts.navigation
+                }});
+                await runActions(page, setup.actions, config.timeouts.wait);
+            }}
+            if (setup.waitFor) {{
+                await page.waitForSelector(setup.waitFor, {{ timeout: config.timeouts.wait }});
+            }}
+            await context.storageState({{ path: authStatePath(role) }});
+        }} catch (error) {{
+            throw new Error(`Login setup failed for role '${{role}}': ${{error.message}}`);
+        }} finally {{
+            await page.close();
+            await context.close();
+        }}
+    }}
+}}
+
+// Take screenshots until two consecutive frames are byte-identical or the timeout
+// elapses, then save the last frame
+async function captureUntilStable(page, screenshotPath, options, settings) {{
+    const start = Date.now();
+    let previous = await page.screenshot(options);
+    let attempts = 1;
+    let stable = false;
+    while (Date.now() - start < settings.timeout) {{
+        await page.waitForTimeout(settings.interval);
+        const next = await page.screenshot(options);
+        attempts++;
+        stable = next.equals(previous);
+        previous = next;
+        if (stable) break;
+    }}
+    fs.writeFileSync(screenshotPath, previous);
+    return {{ attempts, durationMs: Date.now() - start, stable }};
+}}
+
+// CSS injected for each stabilization part
+const STABILIZE_CSS = {{
+    animations: '*, *::before, *::after {{ animation-duration: 0s !important; animation-delay: 0s !important; animation-iteration-count: 1 !important; transition-duration: 0s !important; transition-delay: 0s !important; scroll-behavior: auto !important; }}',
+    caret: '* {{ caret-color: transparent !important; }}',
+    scrollbars: '::-webkit-scrollbar {{ display: none !important; }} * {{ scrollbar-width: none !important; }}'
 }};
 
-// Generate filename for a shot+viewport combination
-function getFilename(shotName, viewportName) {{
-    if (viewportName === 'default') {{
-        return `${{shotName}}.png`;
+// Make rendering deterministic before navigation: inject CSS, freeze the clock
+// and seed Math.random() according to the shot's resolved stabilize settings
+async function stabilizeContext(context, stabilize) {{
+    if (!stabilize) return;
+
+    const css = Object.keys(STABILIZE_CSS)
+        .filter(part => stabilize[part])
+        .map(part => STABILIZE_CSS[part])
+        .join(' ');
+    if (css) {{
+        await context.addInitScript(css => {{
+            const inject = () => {{
+                const style = document.createElement('style');
+                style.setAttribute('data-pixelguard-stabilize', '');
+                style.textContent = css;
+                (document.head || document.documentElement).appendChild(style);
+            }};
+            if (document.readyState === 'loading') {{
+                document.addEventListener('DOMContentLoaded', inject);
+            }} else {{
+                inject();
+            }}
+        }}, css);
+    }}
+
+    if (stabilize.clock) {{
+        if (context.clock) {{
+            await context.clock.setFixedTime(stabilize.time);
+        }} else {{
+            // Playwright < 1.45 has no clock API
+            await context.addInitScript(time => {{
+                const fixed = new Date(time).getTime();
+                const RealDate = Date;
+                function FakeDate(...args) {{
+                    if (!new.target) return new RealDate(fixed).toString();
+                    return new RealDate(...(args.length ? args : [fixed]));
+                }}
+                FakeDate.prototype = RealDate.prototype;
+                FakeDate.now = () => fixed;
+                FakeDate.parse = RealDate.parse;
+                FakeDate.UTC = RealDate.UTC;
+                window.Date = FakeDate;
+            }}, stabilize.time);
+        }}
+    }}
+
+    if (stabilize.random) {{
+        // mulberry32: small, fast and deterministic for a given seed
+        await context.addInitScript(seed => {{
+            let state = seed >>> 0;
+            Math.random = () => {{
+                state = (state + 0x6D2B79F5) >>> 0;
+                let t = state;
+                t = Math.imul(t ^ (t >>> 15), t | 1);
+                t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+                return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+            }};
+        }}, stabilize.seed);
     }}
-    return `${{shotName}}@${{viewportName}}.png`;
 }}
 
-// Capture a single screenshot at a specific viewport
-async function captureOne(browser, shot, viewport, results) {{
+// Create a browser context for a shot's viewport, emulation, login role and stabilize options
+async function createContext(browser, browserName, shot, viewport, emulation, harPath) {{
     const context = await browser.newContext({{
         viewport: {{ width: viewport.width, height: viewport.height }},
-        deviceScaleFactor: 1
+        deviceScaleFactor: viewport.deviceScaleFactor ?? 1,
+        // Firefox does not support mobile emulation
+        isMobile: browserName === 'firefox' ? undefined : viewport.isMobile,
+        hasTouch: viewport.hasTouch,
+        userAgent: viewport.userAgent,
+        colorScheme: emulation.colorScheme,
+        reducedMotion: emulation.reducedMotion,
+        locale: emulation.locale,
+        timezoneId: emulation.timezoneId,
+        storageState: shot.auth ? authStatePath(shot.auth) : undefined,
+        // The HAR file is written when the context closes
+        recordHar: harPath ? {{ path: harPath, content: 'embed' }} : undefined
     }});
-    const page = await context.newPage();
+    await stabilizeContext(context, shot.stabilize);
+    return context;
+}}
+
+// Save what's needed to debug a failed shot under failures/<name>/: the page HTML,
+// a screenshot and, with `trace`, the Playwright trace, each within `timeout`.
+// Returns the saved files relative to the output directory.
+async function saveFailureArtifacts(page, context, displayName, timeout) {{
+    if (config.failureArtifacts === 'off') return [];
+    const dir = `${{config.failuresDir}}/${{displayName}}`;
+    fs.mkdirSync(dir, {{ recursive: true }});
+    const saved = [];
+    const save = async (file, write) => {{
+        try {{
+            await withTimeout(write(`${{dir}}/${{file}}`), timeout, `Saving ${{file}} timed out`);
+            saved.push(`failures/${{displayName}}/${{file}}`);
+        }} catch (error) {{
+            console.error(`Could not save ${{file}} for ${{displayName}}: ${{error.message}}`);
+        }}
+    }};
+    if (page) {{
+        await save('page.html', async file => fs.writeFileSync(file, await page.content()));
+        await save('screenshot.png', file => page.screenshot({{ path: file, fullPage: true }}));
+    }}
+    if (context && config.failureArtifacts === 'trace') {{
+        await save('trace.zip', file => context.tracing.stop({{ path: file }}));
+    }}
+    return saved;
+}}
+
+// Share one context between shots with the same context options; pages are
+// still per shot, but cookies and storage are shared
+function getContext(contexts, browser, browserName, shot, viewport, emulation) {{
+    const key = JSON.stringify([viewport, emulation, shot.auth ?? null, shot.stabilize ?? null]);
+    if (!contexts.has(key)) {{
+        const created = createContext(browser, browserName, shot, viewport, emulation);
+        // Don't cache a failed context; the next shot with these options tries again
+        created.catch(() => contexts.delete(key));
+        contexts.set(key, created);
+    }}
+    return contexts.get(key);
+}}
 
-    const filename = getFilename(shot.name, viewport.name);
-    const displayName = viewport.name === 'default' ? shot.name : `${{shot.name}}@${{viewport.name}}`;
+// Capture a single screenshot at a specific viewport and emulation in a specific browser
+async function captureOne(browser, browserName, shot, viewport, emulation, contexts) {{
+    const started = Date.now();
+    const harPath = config.recordHar ? `${{config.outputDir}}/${{shot.name}}.har` : undefined;
+    const timeouts = shot.timeouts || config.timeouts;
+    const pageErrorPolicy = shot.pageErrors || config.pageErrors;
+    const displayName = getShotName(shot.name, viewport.name, browserName, emulation);
+    const filename = `${{displayName}}.png`;
+    // Shots share contexts unless `reuseContexts` is off. HAR recording and tracing
+    // always need a context of their own: the HAR file is written when it closes,
+    // and a trace records everything in its context
+    const ownContext = !config.reuseContexts || harPath || config.failureArtifacts === 'trace';
+    let context;
+    let page;
+
+    // Artifacts of an earlier failure are stale whatever happens now
+    fs.rmSync(`${{config.failuresDir}}/${{displayName}}`, {{ recursive: true, force: true }});
 
     try {{
+        context = ownContext
+            ? await createContext(browser, browserName, shot, viewport, emulation, harPath)
+            : await getContext(contexts, browser, browserName, shot, viewport, emulation);
+        if (config.failureArtifacts === 'trace') {{
+            await context.tracing.start({{ screenshots: true, snapshots: true }});
+        }}
+        page = await context.newPage();
+        if (emulation.media) {{
+            await page.emulateMedia({{ media: emulation.media }});
+        }}
+
         const url = config.baseUrl + shot.path;
         console.error(`Capturing: ${{displayName}}`);
 
+        const handled = new Set();
+        const collected = collectPageErrors(page, handled);
+        await registerRoutes(page, shot.routes, handled);
+
         await page.goto(url, {{
             waitUntil: 'networkidle',
-            timeout: 30000
+            timeout: timeouts.navigation
         }});
 
         if (shot.waitFor) {{
-            await page.waitForSelector(shot.waitFor, {{ timeout: 10000 }});
+            await page.waitForSelector(shot.waitFor, {{ timeout: timeouts.wait }});
         }}
 
+        await waitForReady(page, shot.ready, timeouts.wait);
+
+        await runActions(page, shot.actions, timeouts.wait);
+
         if (shot.delay) {{
             await new Promise(resolve => setTimeout(resolve, shot.delay));
         }}
 
+        if (harPath) {{
+            emit({{ type: 'captured', name: shot.name, path: harPath, durationMs: Date.now() - started }});
+            return;
+        }}
+
+        // Fail before taking the screenshot, and drop one left by an earlier run,
+        // so the shot isn't compared
+        const a11yPath = `${{config.outputDir}}/${{displayName}}.a11y.yaml`;
+        const domPath = `${{config.outputDir}}/${{displayName}}.dom.json`;
+        const pageErrors = unexpectedErrors(collected, pageErrorPolicy.allow);
+        if (pageErrors && pageErrorPolicy.fail) {{
+            fs.rmSync(`${{config.outputDir}}/${{filename}}`, {{ force: true }});
+            fs.rmSync(a11yPath, {{ force: true }});
+            fs.rmSync(domPath, {{ force: true }});
+            const first = [...pageErrors.exceptions, ...pageErrors.console, ...pageErrors.requests][0];
+            const error = new Error(`${{pageErrors.count}} page error(s): ${{first}}`);
+            error.pageErrors = pageErrors;
+            throw error;
+        }}
+
         const screenshotPath = `${{config.outputDir}}/${{filename}}`;
-        await page.screenshot({{
-            path: screenshotPath,
-            fullPage: false
-        }});
+        const fullPage = shot.fullPage ?? viewport.fullPage ?? config.fullPage;
+        let clip;
+        let options = {{ fullPage: false }};
+        if (shot.selector) {{
+            clip = await getElementClip(page, shot.selector, shot.padding || 0, timeouts.wait);
+            options = {{ fullPage: false, clip }};
+        }} else if (fullPage) {{
+            options = await getFullPageOptions(page, viewport, timeouts.wait);
+        }}
+        const mask = [...config.mask, ...(shot.mask || [])].map(selector => page.locator(selector));
+        const masked = await getMaskedRegions(page, mask, options.clip || {{ x: 0, y: 0 }}, viewport.deviceScaleFactor ?? 1);
+        const screenshotOptions = {{
+            ...options,
+            mask,
+            animations: shot.stabilize?.animations ? 'disabled' : 'allow'
+        }};
+        let stability;
+        if (shot.stableCapture) {{
+            stability = await captureUntilStable(page, screenshotPath, screenshotOptions, shot.stableCapture);
+        }} else {{
+            await page.screenshot({{ path: screenshotPath, ...screenshotOptions }});
+        }}
+
+        // The ARIA snapshot of the page (or the selected element), next to the screenshot
+        if (shot.a11y) {{
+            const root = page.locator(shot.selector || 'body').first();
+            if (typeof root.ariaSnapshot !== 'function') {{
+                throw new Error('a11y snapshots need Playwright 1.49 or later (locator.ariaSnapshot)');
+            }}
+            fs.writeFileSync(a11yPath, await root.ariaSnapshot({{ timeout: timeouts.wait }}));
+        }} else {{
+            fs.rmSync(a11yPath, {{ force: true }});
+        }}
+
+        // The DOM and computed styles under the shot's root element
+        if (shot.domSnapshot) {{
+            fs.writeFileSync(domPath, JSON.stringify(await getDomSnapshot(page, shot.selector)));
+        }} else {{
+            fs.rmSync(domPath, {{ force: true }});
+        }}
 
-        results.captured.push({{
+        if (stability && !stability.stable) {{
+            console.error(`Unstable: ${{displayName}} kept changing for ${{stability.durationMs}}ms (${{stability.attempts}} attempts)`);
+        }}
+        emit({{
+            type: 'captured',
             name: displayName,
-            path: screenshotPath
+            path: screenshotPath,
+            element: clip && toScreenshotPixels(clip, viewport.deviceScaleFactor ?? 1),
+            masked,
+            stability,
+            pageErrors,
+            durationMs: Date.now() - started
         }});
     }} catch (error) {{
-        results.failed.push({{
+        const artifacts = await saveFailureArtifacts(page, context, displayName, timeouts.wait);
+        emit({{
+            type: 'failed',
             name: displayName,
-            error: error.message
+            error: error.message,
+            pageErrors: error.pageErrors,
+            artifacts,
+            durationMs: Date.now() - started
         }});
         console.error(`Failed to capture ${{displayName}}: ${{error.message}}`);
     }} finally {{
-        await page.close();
-        await context.close();
+        if (page) {{
+            await page.close();
+        }}
+        if (context && ownContext) {{
+            await context.close();
+        }}
     }}
 }}
 
-// Build list of all shot+viewport combinations
-function buildCaptureList(shots, viewports) {{
+// Build list of all shot+viewport+emulation combinations
+function buildCaptureList(shots, viewports, emulations) {{
     const captureList = [];
     for (const shot of shots) {{
         for (const viewport of viewports) {{
-            captureList.push({{ shot, viewport }});
+            if (shot.viewports && !shot.viewports.includes(viewport.name)) {{
+                continue;
+            }}
+            for (const emulation of emulations) {{
+                captureList.push({{ shot, viewport, emulation }});
+            }}
         }}
     }}
     return captureList;
 }}
 
-// Process items in batches for parallel capture
-async function processBatch(browser, items, results) {{
-    await Promise.all(items.map(item => captureOne(browser, item.shot, item.viewport, results)));
-}}
-
-async function captureScreenshots() {{
-    const results = {{ captured: [], failed: [] }};
-
-    const browser = await chromium.launch({{ headless: true }});
+// Capture items with `concurrency` workers, each taking the next item as soon as it is free
+async function processQueue(browser, browserName, items, progress) {{
+    const contexts = new Map();
+    let next = 0;
+    const worker = async () => {{
+        while (next < items.length) {{
+            const item = items[next++];
+            await captureOne(browser, browserName, item.shot, item.viewport, item.emulation, contexts);
+            progress.completed++;
+            emit({{ type: 'progress', completed: progress.completed, total: progress.total }});
+        }}
+    }};
 
     try {{
-        // Build list of all shot+viewport combinations
-        const captureList = buildCaptureList(config.shots, config.viewports);
+        const workers = Math.max(1, Math.min(config.concurrency, items.length));
+        await Promise.all(Array.from({{ length: workers }}, worker));
+    }} finally {{
+        const settled = await Promise.allSettled(contexts.values());
+        await Promise.all(settled
+            .filter(result => result.status === 'fulfilled')
+            .map(result => result.value.close()));
+    }}
+}}
+
+// Connect to the configured browser endpoint, or launch the engine locally
+async function openBrowser(browserName) {{
+    const options = config.browser;
+    if (options.cdpEndpoint) {{
+        console.error(`Connecting to ${{options.cdpEndpoint}} over CDP`);
+        return playwright.chromium.connectOverCDP(options.cdpEndpoint, {{ timeout: config.timeouts.navigation }});
+    }}
+    if (options.wsEndpoint) {{
+        console.error(`Connecting to ${{browserName}} at ${{options.wsEndpoint}}`);
+        return playwright[browserName].connect(options.wsEndpoint, {{ timeout: config.timeouts.navigation }});
+    }}
+    return playwright[browserName].launch({{
+        headless: true,
+        args: options.launchArgs,
+        executablePath: options.executablePath,
+        channel: options.channel
+    }});
+}}
 
-        // Split into batches based on concurrency
-        const batches = [];
-        for (let i = 0; i < captureList.length; i += config.concurrency) {{
-            batches.push(captureList.slice(i, i + config.concurrency));
+async function captureScreenshots() {{
+    // Build list of all shot+viewport+emulation combinations
+    const captureList = buildCaptureList(config.shots, config.viewports, config.emulations);
+
+    // Each browser engine captures the shots that don't leave it out
+    const itemsByBrowser = config.browsers.map(browserName => [
+        browserName,
+        captureList.filter(item => !item.shot.browsers || item.shot.browsers.includes(browserName)),
+    ]);
+
+    const total = itemsByBrowser.reduce((sum, [, items]) => sum + items.length, 0);
+    const progress = {{ completed: 0, total }};
+    emit({{ type: 'started', total: progress.total }});
+
+    // Capture the list once per browser engine, logging in with the first
+    let loggedIn = false;
+    for (const [browserName, items] of itemsByBrowser) {{
+        if (items.length === 0) {{
+            continue;
         }}
+        const browser = await openBrowser(browserName);
+
+        try {{
+            if (!loggedIn) {{
+                await runSetup(browser);
+                loggedIn = true;
+            }}
 
-        // Process batches sequentially, items within batch in parallel
-        for (const batch of batches) {{
-            await processBatch(browser, batch, results);
+            await processQueue(browser, browserName, items, progress);
+        }} finally {{
+            await browser.close();
         }}
-    }} finally {{
-        await browser.close();
     }}
-
-    console.log(JSON.stringify(results));
 }}
 
 captureScreenshots().catch(error => {{
@@ -238,47 +1449,108 @@ captureScreenshots().catch(error => {{
         working_dir = serde_json::to_string(&working_dir_str)?,
         base_url = serde_json::to_string(&config.base_url)?,
         viewports = viewports_json,
+        browsers = browsers_json,
+        browser_suffix = !record_har && !config.browsers.is_empty(),
+        emulations = emulations_json,
         output_dir = serde_json::to_string(&output_dir_str)?,
         shots = shots_json,
+        mask = serde_json::to_string(&config.mask)?,
         concurrency = config.concurrency,
+        reuse_contexts = config.reuse_contexts,
+        full_page = config.full_page,
+        max_page_height = serde_json::to_string(&config.max_page_height)?,
+        record_har = record_har,
+        auth_roles = auth_roles_json,
+        auth_dir = serde_json::to_string(&script_path(&auth_dir)?)?,
+        timeouts = serde_json::to_string(&config.timeouts.resolve(&Timeouts::default()))?,
+        page_errors = serde_json::to_string(&config.page_errors_for(&Shot::default()))?,
+        failures_dir = serde_json::to_string(&script_path(&failures_dir)?)?,
+        failure_artifacts = serde_json::to_string(&failure_artifacts)?,
+        browser = serde_json::to_string(&config.browser)?,
     );
 
     Ok(script)
 }
 
-/// Executes the Playwright script via Node.js.
-async fn execute_playwright_script(script: &str, working_dir: &Path) -> Result<CaptureResult> {
-    // Write script to temp file
-    let temp_dir = tempfile::tempdir()?;
+/// Executes the Playwright script via Node.js, reading its events as they arrive.
+async fn execute_playwright_script(
+    config: &Config,
+    script: &str,
+    working_dir: &Path,
+    mut on_event: impl FnMut(&CaptureEvent),
+) -> Result<CaptureResult> {
+    // Write script to temp file
+    let temp_dir = tempfile::tempdir()?;
     let script_path = temp_dir.path().join("capture.js");
     std::fs::write(&script_path, script)?;
 
     debug!("🎬 Executing Playwright script at {:?}", script_path);
 
     // Execute with Node.js
-    let output = Command::new("node")
+    let mut child = Command::new("node")
         .arg(&script_path)
         .current_dir(working_dir)
-        .output()
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
         .context(
             "Failed to execute Node.js. Make sure Node.js is installed and available in PATH.",
         )?;
 
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    if !stderr.is_empty() {
-        for line in stderr.lines() {
-            if line.starts_with("Capturing:") {
-                info!("📸 {}", line);
-            } else if line.starts_with("Failed to capture") {
-                tracing::warn!("❌ {}", line);
-            } else {
-                debug!("Playwright: {}", line);
+    // Drain stderr alongside stdout so neither pipe fills up and blocks the script
+    let stderr_pipe = child
+        .stderr
+        .take()
+        .context("Failed to read Node.js stderr")?;
+    let stderr_task = tokio::spawn(async move {
+        let mut lines = BufReader::new(stderr_pipe).lines();
+        let mut stderr = String::new();
+        while let Ok(Some(line)) = lines.next_line().await {
+            log_stderr_line(&line);
+            stderr.push_str(&line);
+            stderr.push('\n');
+        }
+        stderr
+    });
+
+    let mut result = CaptureResult::default();
+    let stdout_pipe = child
+        .stdout
+        .take()
+        .context("Failed to read Node.js stdout")?;
+    let mut lines = BufReader::new(stdout_pipe).lines();
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .context("Failed to read Playwright output")?
+    {
+        match parse_event(&line) {
+            Some(event) => {
+                on_event(&event);
+                result.apply(event);
             }
+            None => debug!("Playwright: {}", line),
         }
     }
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    let status = child.wait().await.context("Failed to wait for Node.js")?;
+    let stderr = stderr_task.await.unwrap_or_default();
+
+    if !status.success() {
+        if result.total() > 0 {
+            warn!(
+                "⚠️  Playwright script exited with code {:?} after {} shots; keeping partial results",
+                status.code(),
+                result.total()
+            );
+            result.error = Some(format!(
+                "Playwright script exited with code {:?}: {}",
+                status.code(),
+                stderr.trim()
+            ));
+            return Ok(result);
+        }
         if stderr.contains("Cannot find module 'playwright'") {
             anyhow::bail!(
                 "❌ Playwright is not installed.\n\n\
@@ -289,6 +1561,32 @@ async fn execute_playwright_script(script: &str, working_dir: &Path) -> Result<C
                  🔗 Docs: https://playwright.dev/docs/intro"
             );
         }
+        if stderr.contains("Login setup failed") {
+            anyhow::bail!(
+                "❌ Login setup failed before capture.\n\n\
+                 💡 Solutions:\n  \
+                 • Check the login `actions` or `script` of the role in `setup`\n  \
+                 • Make sure `waitFor` matches an element shown after login\n  \
+                 • Verify the test account credentials\n\n\
+                 🔍 Error details:\n{}",
+                stderr
+            );
+        }
+        if stderr.contains("Executable doesn't exist") {
+            let browsers = config
+                .effective_browsers()
+                .iter()
+                .map(Browser::as_str)
+                .collect::<Vec<_>>()
+                .join(" ");
+            anyhow::bail!(
+                "❌ A browser to capture in is not installed.\n\n\
+                 💡 Solution: Run: npx playwright install {}\n\n\
+                 🔍 Error details:\n{}",
+                browsers,
+                stderr
+            );
+        }
         anyhow::bail!(
             "❌ Playwright script failed with exit code {:?}.\n\n\
              🔍 Error details:\n{}\n\n\
@@ -296,60 +1594,44 @@ async fn execute_playwright_script(script: &str, working_dir: &Path) -> Result<C
              • Dev server is not running\n  \
              • Incorrect baseUrl in config\n  \
              • Network connectivity issues\n  \
-             • Page load timeout (increase delay in shot config)",
-            output.status.code(),
+             • Page load timeout (raise timeouts.navigation in the config)",
+            status.code(),
             stderr
         );
     }
 
-    // Parse output
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let result: serde_json::Value = serde_json::from_str(stdout.trim()).context(
-        "❌ Failed to parse Playwright output.\n\n\
-         🔍 This indicates a Playwright script error.\n\n\
-         💡 Possible solutions:\n  \
-         • Check that your dev server is running\n  \
-         • Verify browser is installed: npx playwright install chromium\n  \
-         • Review error messages above for details",
-    )?;
-
-    let captured: Vec<CapturedShot> = result
-        .get("captured")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| {
-                    Some(CapturedShot {
-                        name: v.get("name")?.as_str()?.to_string(),
-                        path: PathBuf::from(v.get("path")?.as_str()?),
-                    })
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-
-    let failed: Vec<FailedShot> = result
-        .get("failed")
-        .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|v| {
-                    Some(FailedShot {
-                        name: v.get("name")?.as_str()?.to_string(),
-                        error: v.get("error")?.as_str()?.to_string(),
-                    })
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-
     info!(
-        "✅ Captured {} screenshots, {} failed",
-        captured.len(),
-        failed.len()
+        "✅ Captured {} screenshots, {} unstable, {} failed",
+        result.captured.len(),
+        result.unstable.len(),
+        result.failed.len()
     );
 
-    Ok(CaptureResult { captured, failed })
+    Ok(result)
+}
+
+/// Parses one line of capture output, ignoring anything that isn't an event.
+fn parse_event(line: &str) -> Option<CaptureEvent> {
+    let line = line.trim();
+    if !line.starts_with('{') {
+        return None;
+    }
+    serde_json::from_str(line).ok()
+}
+
+/// Logs a line the capture script wrote to stderr.
+fn log_stderr_line(line: &str) {
+    if line.starts_with("Capturing:") {
+        debug!("📸 {}", line);
+    } else if line.starts_with("Logging in:") {
+        info!("🔑 {}", line);
+    } else if line.starts_with("Unstable:") {
+        warn!("〰️  {}", line);
+    } else if line.starts_with("Failed to capture") {
+        warn!("❌ {}", line);
+    } else {
+        debug!("Playwright: {}", line);
+    }
 }
 
 /// Copies current screenshots to the baseline directory.
@@ -421,9 +1703,12 @@ pub fn update_baseline<P: AsRef<Path>>(
         // If filter is provided, skip shots that don't match
         if let Some(filter_names) = filter {
             let matches = filter_names.iter().any(|filter_name| {
-                // Match exact name or name with viewport suffix (e.g., "button" matches "button@desktop")
+                // Match exact name or name with viewport/browser/emulation suffix
+                // (e.g., "button" matches "button@desktop", "button~firefox" and "button+scheme-dark")
                 name == filter_name.as_str()
                     || name.starts_with(&format!("{}@", filter_name))
+                    || name.starts_with(&format!("{}~", filter_name))
+                    || name.starts_with(&format!("{}+", filter_name))
                     || filter_name == name.as_ref()
             });
             if !matches {
@@ -436,6 +1721,17 @@ pub fn update_baseline<P: AsRef<Path>>(
         let baseline_path = format!("baseline/{}", filename);
 
         storage.copy(&current_path, &baseline_path)?;
+
+        // Accessibility and DOM snapshots are approved along with the screenshot
+        for suffix in [A11Y_SNAPSHOT_SUFFIX, DOM_SNAPSHOT_SUFFIX] {
+            let snapshot = format!("{}{}", name, suffix);
+            if current_dir.join(&snapshot).exists() {
+                storage.copy(
+                    &format!("current/{}", snapshot),
+                    &format!("baseline/{}", snapshot),
+                )?;
+            }
+        }
         debug!("✅ Updated baseline: {}", name);
         updated_count += 1;
     }
@@ -480,6 +1776,7 @@ mod tests {
                 path: "/iframe.html?id=button--primary".to_string(),
                 wait_for: Some("#storybook-root".to_string()),
                 delay: Some(100),
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -495,6 +1792,1278 @@ mod tests {
         assert!(script.contains("720"));
     }
 
+    #[test]
+    fn generate_script_uses_worker_pool_and_shared_contexts() {
+        let config = Config {
+            concurrency: 3,
+            shots: vec![crate::config::Shot {
+                name: "card".to_string(),
+                path: "/card".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains("concurrency: 3,"));
+        assert!(script.contains("await processQueue(browser, browserName, items, progress);"));
+        assert!(script
+            .contains("getContext(contexts, browser, browserName, shot, viewport, emulation)"));
+        assert!(script.contains("durationMs: Date.now() - started"));
+    }
+
+    #[test]
+    fn generate_script_passes_selected_viewports_and_browsers() {
+        let named = |name: &str| crate::config::NamedViewport {
+            name: name.to_string(),
+            width: 800,
+            height: 600,
+            ..Default::default()
+        };
+        let shot = |name: &str| crate::config::Shot {
+            name: name.to_string(),
+            path: format!("/{}", name),
+            ..Default::default()
+        };
+        let config = Config {
+            viewports: vec![named("mobile"), named("tablet"), named("desktop")],
+            browsers: vec![
+                crate::config::Browser::Chromium,
+                crate::config::Browser::Firefox,
+            ],
+            matrix: vec![crate::config::MatrixRule {
+                include: vec!["icon--*".to_string()],
+                viewports: vec!["desktop".to_string()],
+                browsers: vec![crate::config::Browser::Chromium],
+                ..Default::default()
+            }],
+            shots: vec![
+                shot("icon--star"),
+                crate::config::Shot {
+                    viewports: vec!["mobile".to_string(), "desktop".to_string()],
+                    ..shot("icon--arrow")
+                },
+                shot("page--home"),
+            ],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(
+            r#""name":"icon--star","path":"/icon--star","viewports":["desktop"],"browsers":["chromium"]"#
+        ));
+        assert!(script.contains(
+            r#""name":"icon--arrow","path":"/icon--arrow","viewports":["mobile","desktop"],"browsers":["chromium"]"#
+        ));
+        assert!(script.contains(r#"{"name":"page--home","path":"/page--home"}"#));
+        assert!(script.contains(
+            "captureList.filter(item => !item.shot.browsers || item.shot.browsers.includes(browserName))"
+        ));
+
+        let unknown = Config {
+            shots: vec![crate::config::Shot {
+                viewports: vec!["watch".to_string()],
+                ..shot("icon--star")
+            }],
+            ..config
+        };
+        let err =
+            generate_playwright_script(&unknown, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap_err();
+        assert!(err.to_string().contains("unknown viewport 'watch'"));
+    }
+
+    #[test]
+    fn generate_script_passes_device_emulation_to_context() {
+        let config = Config {
+            viewports: vec![crate::config::NamedViewport {
+                name: "phone".to_string(),
+                device: Some("Pixel 7".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(r#""deviceScaleFactor":2.625"#));
+        assert!(script.contains(r#""isMobile":true"#));
+        assert!(script.contains(r#""hasTouch":true"#));
+        assert!(script.contains("Pixel 7) AppleWebKit"));
+        assert!(script.contains("deviceScaleFactor: viewport.deviceScaleFactor ?? 1"));
+        assert!(script.contains("userAgent: viewport.userAgent"));
+    }
+
+    #[test]
+    fn generate_script_rejects_unknown_device() {
+        let config = Config {
+            viewports: vec![crate::config::NamedViewport {
+                name: "phone".to_string(),
+                device: Some("Unknown Phone".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert!(generate_playwright_script(
+            &config,
+            Path::new("/tmp/output"),
+            Path::new("/project")
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn generate_script_expands_emulation_matrix() {
+        let config = Config {
+            color_scheme: vec![
+                crate::config::ColorScheme::Light,
+                crate::config::ColorScheme::Dark,
+            ],
+            locale: vec!["ar-SA".to_string()],
+            media: vec![crate::config::Media::Print],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(
+            r#"{"colorScheme":"dark","locale":"ar-SA","media":"print","suffix":"+scheme-dark+locale-ar-SA+media-print"}"#
+        ));
+        assert!(script.contains("colorScheme: emulation.colorScheme"));
+        assert!(script.contains("page.emulateMedia({ media: emulation.media })"));
+    }
+
+    #[test]
+    fn generate_script_has_single_empty_emulation_by_default() {
+        let script = generate_playwright_script(
+            &Config::default(),
+            Path::new("/tmp/output"),
+            Path::new("/project"),
+        )
+        .unwrap();
+
+        assert!(script.contains(r#"emulations: [{"suffix":""}]"#));
+    }
+
+    #[test]
+    fn generate_script_resolves_stabilize_per_shot() {
+        let config = Config {
+            stabilize: Some(crate::config::Stabilize::all(true)),
+            shots: vec![
+                Shot {
+                    name: "clock".to_string(),
+                    path: "/clock".to_string(),
+                    stabilize: Some(crate::config::Stabilize {
+                        clock: Some(false),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                Shot {
+                    name: "live".to_string(),
+                    path: "/live".to_string(),
+                    stabilize: Some(crate::config::Stabilize::all(false)),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(
+            r#""stabilize":{"animations":true,"caret":true,"scrollbars":true,"clock":false,"random":true,"time":"2024-01-01T00:00:00.000Z","seed":42}"#
+        ));
+        assert!(!script.contains(r#""stabilize":{"animations":false"#));
+        assert!(script.contains("await stabilizeContext(context, shot.stabilize);"));
+        assert!(script.contains("animation-duration: 0s !important"));
+    }
+
+    #[test]
+    fn generate_script_resolves_route_rules_per_shot() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("fixtures")).unwrap();
+        std::fs::write(dir.path().join("fixtures/users.json"), "[]").unwrap();
+        std::fs::create_dir_all(dir.path().join("hars")).unwrap();
+        std::fs::write(dir.path().join("hars/recorded.har"), "{}").unwrap();
+
+        let config = Config {
+            routes: vec![
+                RouteRule {
+                    url: Some("**/api/users".to_string()),
+                    fixture: Some("fixtures/users.json".to_string()),
+                    ..Default::default()
+                },
+                RouteRule {
+                    url: Some("**/api/**".to_string()),
+                    har: Some(format!("hars/{HAR_SHOT_PLACEHOLDER}.har")),
+                    ..Default::default()
+                },
+            ],
+            shots: vec![
+                Shot {
+                    name: "recorded".to_string(),
+                    path: "/".to_string(),
+                    routes: vec![RouteRule {
+                        url_regex: Some("analytics".to_string()),
+                        abort: true,
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                },
+                Shot {
+                    name: "unrecorded".to_string(),
+                    path: "/other".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), dir.path()).unwrap();
+
+        let root = dir.path().canonicalize().unwrap();
+        let root = script_path(&root).unwrap();
+        assert!(script.contains(&format!(
+            r#""routes":[{{"urlRegex":"analytics","abort":true}},{{"url":"**/api/users","fixture":"{root}/fixtures/users.json"}},{{"url":"**/api/**","har":"{root}/hars/recorded.har"}}]"#
+        )));
+        // The second shot has no recording, so its HAR rule is skipped
+        assert!(script.contains(&format!(
+            r#""name":"unrecorded","path":"/other","routes":[{{"url":"**/api/users","fixture":"{root}/fixtures/users.json"}}]"#
+        )));
+        assert!(script.contains("await registerRoutes(page, shot.routes, handled);"));
+        assert!(script.contains("recordHar: false"));
+    }
+
+    #[test]
+    fn generate_script_rejects_invalid_route_rules() {
+        let missing_fixture = Config {
+            routes: vec![RouteRule {
+                url: Some("**/api".to_string()),
+                fixture: Some("missing.json".to_string()),
+                ..Default::default()
+            }],
+            shots: vec![Shot {
+                name: "home".to_string(),
+                path: "/".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let no_action = Config {
+            routes: vec![RouteRule {
+                url: Some("**/api".to_string()),
+                ..Default::default()
+            }],
+            ..missing_fixture.clone()
+        };
+
+        for config in [missing_fixture, no_action] {
+            assert!(generate_playwright_script(
+                &config,
+                Path::new("/tmp/output"),
+                Path::new("/project")
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn record_har_script_uses_first_combination_without_routes() {
+        let config = Config {
+            browsers: vec![
+                crate::config::Browser::Firefox,
+                crate::config::Browser::Webkit,
+            ],
+            color_scheme: vec![
+                crate::config::ColorScheme::Light,
+                crate::config::ColorScheme::Dark,
+            ],
+            routes: vec![RouteRule {
+                url: Some("**/api".to_string()),
+                abort: true,
+                ..Default::default()
+            }],
+            shots: vec![Shot {
+                name: "home".to_string(),
+                path: "/".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let script = build_playwright_script(
+            &config,
+            Path::new("/tmp/output/har"),
+            Path::new("/project"),
+            ScriptMode::RecordHar,
+        )
+        .unwrap();
+
+        assert!(script.contains("recordHar: true"));
+        assert!(script.contains(r#"browsers: ["firefox"]"#));
+        assert!(script.contains("browserSuffix: false"));
+        assert!(
+            script.contains(r#"emulations: [{"colorScheme":"light","suffix":"+scheme-light"}]"#)
+        );
+        assert!(!script.contains(r#""routes""#));
+        assert!(
+            script.contains("recordHar: harPath ? { path: harPath, content: 'embed' } : undefined")
+        );
+    }
+
+    #[test]
+    fn generate_script_logs_in_once_per_used_role() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("login.js"),
+            "module.exports = async () => {};",
+        )
+        .unwrap();
+
+        let config: Config = serde_json::from_str(
+            r#"{
+                "setup": {
+                    "roles": {
+                        "admin": { "actions": [{ "type": "click", "selector": "button" }] },
+                        "viewer": { "script": "login.js" },
+                        "unused": { "script": "missing.js" }
+                    },
+                    "defaultRole": "admin"
+                },
+                "shots": [
+                    { "name": "dashboard", "path": "/" },
+                    { "name": "report", "path": "/report", "auth": "viewer" },
+                    { "name": "login", "path": "/login", "auth": false }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), dir.path()).unwrap();
+
+        let root = script_path(&dir.path().canonicalize().unwrap()).unwrap();
+        assert!(script.contains(&format!(
+            r#"authRoles: {{"admin":{{"actions":[{{"type":"click","selector":"button"}}]}},"viewer":{{"script":"{root}/login.js"}}}}"#
+        )));
+        assert!(script.contains(r#""name":"dashboard","path":"/","auth":"admin""#));
+        assert!(script.contains(r#""name":"login","path":"/login"}"#));
+        assert!(script.contains("storageState: shot.auth ? authStatePath(shot.auth) : undefined"));
+        assert!(script.contains("await runSetup(browser);"));
+    }
+
+    #[test]
+    fn generate_script_rejects_unknown_auth_role() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "setup": { "roles": { "admin": { "script": "login.js" } } },
+                "shots": [{ "name": "home", "path": "/", "auth": "viewer" }]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(generate_playwright_script(
+            &config,
+            Path::new("/tmp/output"),
+            Path::new("/project")
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn generate_script_launches_each_configured_browser() {
+        let config = Config {
+            browsers: vec![
+                crate::config::Browser::Chromium,
+                crate::config::Browser::Firefox,
+                crate::config::Browser::Webkit,
+            ],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(r#"browsers: ["chromium","firefox","webkit"]"#));
+        assert!(script.contains("browserSuffix: true"));
+        assert!(script.contains("playwright[browserName].launch"));
+    }
+
+    #[test]
+    fn generate_script_connects_to_configured_browser() {
+        let config = Config {
+            browser: crate::config::BrowserOptions {
+                ws_endpoint: Some("ws://browser:3000/".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(r#"browser: {"wsEndpoint":"ws://browser:3000/"}"#));
+        assert!(script.contains("playwright[browserName].connect(options.wsEndpoint"));
+        assert!(script.contains("playwright.chromium.connectOverCDP(options.cdpEndpoint"));
+        assert!(script.contains("await openBrowser(browserName)"));
+
+        let launch = Config {
+            browser: crate::config::BrowserOptions {
+                launch_args: vec!["--font-render-hinting=none".to_string()],
+                channel: Some("chrome".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let script =
+            generate_playwright_script(&launch, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+        assert!(script.contains(
+            r#"browser: {"launchArgs":["--font-render-hinting=none"],"channel":"chrome"}"#
+        ));
+
+        let invalid = Config {
+            browsers: vec![crate::config::Browser::Firefox],
+            browser: crate::config::BrowserOptions {
+                cdp_endpoint: Some("http://browser:9222".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(generate_playwright_script(
+            &invalid,
+            Path::new("/tmp/output"),
+            Path::new("/project")
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn generate_script_omits_browser_suffix_by_default() {
+        let config = Config::default();
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(r#"browsers: ["chromium"]"#));
+        assert!(script.contains("browserSuffix: false"));
+    }
+
+    #[test]
+    fn generate_script_includes_shot_actions() {
+        let config = Config {
+            base_url: "http://localhost:3000".to_string(),
+            shots: vec![crate::config::Shot {
+                name: "menu--open".to_string(),
+                path: "/menu".to_string(),
+                actions: vec![
+                    crate::config::ShotAction::Click {
+                        selector: "#menu-toggle".to_string(),
+                    },
+                    crate::config::ShotAction::WaitForSelector {
+                        selector: ".menu-panel".to_string(),
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains("await runActions(page, shot.actions, timeouts.wait)"));
+        assert!(script.contains(r##""actions":[{"type":"click","selector":"#menu-toggle"}"##));
+        assert!(script.contains(r#"{"type":"waitForSelector","selector":".menu-panel"}"#));
+    }
+
+    #[test]
+    fn generate_script_clips_to_selector() {
+        let config = Config {
+            base_url: "http://localhost:6006".to_string(),
+            shots: vec![crate::config::Shot {
+                name: "button--primary".to_string(),
+                path: "/iframe.html?id=button--primary".to_string(),
+                selector: Some("#storybook-root > *".to_string()),
+                padding: Some(8),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script
+            .contains("getElementClip(page, shot.selector, shot.padding || 0, timeouts.wait)"));
+        assert!(script.contains(r##""selector":"#storybook-root > *","padding":8"##));
+    }
+
+    #[test]
+    fn generate_script_resolves_full_page_per_shot_and_viewport() {
+        let config = Config {
+            base_url: "http://localhost:3000".to_string(),
+            full_page: true,
+            max_page_height: Some(8000),
+            viewports: vec![crate::config::NamedViewport {
+                name: "mobile".to_string(),
+                width: 375,
+                height: 667,
+                full_page: Some(false),
+                ..Default::default()
+            }],
+            shots: vec![crate::config::Shot {
+                name: "home".to_string(),
+                path: "/".to_string(),
+                full_page: Some(true),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains("fullPage: true,"));
+        assert!(script.contains("maxPageHeight: 8000"));
+        assert!(script.contains("shot.fullPage ?? viewport.fullPage ?? config.fullPage"));
+        assert!(script.contains(r#""fullPage":false"#));
+    }
+
+    #[test]
+    fn generate_script_applies_config_and_shot_masks() {
+        let config = Config {
+            base_url: "http://localhost:3000".to_string(),
+            mask: vec![".timestamp".to_string()],
+            shots: vec![crate::config::Shot {
+                name: "feed".to_string(),
+                path: "/feed".to_string(),
+                mask: vec![".avatar".to_string()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(r#"mask: [".timestamp"]"#));
+        assert!(script.contains(r#""mask":[".avatar"]"#));
+        assert!(script.contains("[...config.mask, ...(shot.mask || [])]"));
+    }
+
+    #[test]
+    fn masked_regions_round_trip_and_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let region = Region {
+            x: 1,
+            y: 2,
+            width: 3,
+            height: 4,
+        };
+        let shot = |name: &str, masked: Vec<Region>| CapturedShot {
+            name: name.to_string(),
+            path: PathBuf::new(),
+            element: None,
+            masked,
+            stability: None,
+            duration_ms: None,
+            page_errors: PageErrors::default(),
+        };
+
+        save_masked_regions(
+            dir.path(),
+            &[shot("a", vec![region]), shot("b", vec![region])],
+        )
+        .unwrap();
+        // A later run that only captured "b" without masks keeps "a"
+        save_masked_regions(dir.path(), &[shot("b", Vec::new())]).unwrap();
+
+        let regions = load_masked_regions(dir.path());
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions["a"], vec![region]);
+    }
+
+    /// A fake `playwright` module that counts browser contexts and the most shots
+    /// open at once (overall and in one context), written to `stats.json` when the
+    /// browser closes.
+    const FAKE_PLAYWRIGHT: &str = r#"
+const fs = require('fs');
+const stats = { contexts: 0, closedContexts: 0, maxActive: 0, maxPagesPerContext: 0 };
+let active = 0;
+const page = context => ({
+    on: () => {}, route: async () => {}, emulateMedia: async () => {},
+    waitForSelector: async () => {}, evaluate: async () => ({}), $: async () => null,
+    viewportSize: () => ({ width: 1, height: 1 }),
+    locator: selector => ({
+        first() { return this; }, all: async () => [],
+        waitFor: async () => {}, scrollIntoViewIfNeeded: async () => {},
+        boundingBox: async () => ({ x: 10.5, y: 20, width: 30, height: 40 }),
+        ariaSnapshot: async () => `- region "${selector}"`
+    }),
+    goto: async () => {
+        active++;
+        stats.maxActive = Math.max(stats.maxActive, active);
+        await new Promise(resolve => setTimeout(resolve, 20));
+        active--;
+    },
+    screenshot: async ({ path }) => fs.writeFileSync(path, 'png'),
+    close: async () => { context.pages--; }
+});
+const browser = {
+    newContext: async () => {
+        stats.contexts++;
+        const context = {
+            pages: 0,
+            newPage: async () => {
+                context.pages++;
+                stats.maxPagesPerContext = Math.max(stats.maxPagesPerContext, context.pages);
+                return page(context);
+            },
+            close: async () => { stats.closedContexts++; },
+            addInitScript: async () => {}
+        };
+        return context;
+    },
+    close: async () => fs.writeFileSync('stats.json', JSON.stringify(stats))
+};
+module.exports = { chromium: { launch: async () => browser } };
+"#;
+
+    /// Captures `config` with [`FAKE_PLAYWRIGHT`] and returns the capture result,
+    /// the module's stats and the project directory, or None if Node.js is missing.
+    async fn capture_with_fake_playwright(
+        config: &Config,
+    ) -> Option<(CaptureResult, serde_json::Value, tempfile::TempDir)> {
+        if std::process::Command::new("node")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            return None;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let module_dir = dir.path().join("node_modules/playwright");
+        std::fs::create_dir_all(&module_dir).unwrap();
+        std::fs::write(module_dir.join("index.js"), FAKE_PLAYWRIGHT).unwrap();
+
+        let result = capture_screenshots_in_dir(config, dir.path())
+            .await
+            .unwrap();
+        assert!(result.failed.is_empty(), "{:?}", result.failed);
+        let stats = std::fs::read_to_string(dir.path().join("stats.json")).unwrap();
+        Some((result, serde_json::from_str(&stats).unwrap(), dir))
+    }
+
+    #[tokio::test]
+    async fn missing_browser_error_installs_the_configured_browsers() {
+        if std::process::Command::new("node")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let module_dir = dir.path().join("node_modules/playwright");
+        std::fs::create_dir_all(&module_dir).unwrap();
+        std::fs::write(
+            module_dir.join("index.js"),
+            r#"const launch = async () => { throw new Error("Executable doesn't exist"); };
+module.exports = { chromium: { launch }, firefox: { launch }, webkit: { launch } };"#,
+        )
+        .unwrap();
+        let config = Config {
+            base_url: "http://localhost:9".to_string(),
+            browsers: vec![Browser::Firefox, Browser::Webkit],
+            shots: vec![crate::config::Shot {
+                name: "home".to_string(),
+                path: "/".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let error = capture_screenshots_in_dir(&config, dir.path())
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(
+            error.contains("Run: npx playwright install firefox webkit\n"),
+            "{}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn worker_pool_shares_contexts_unless_reuse_is_off() {
+        let shots: Vec<Shot> = (0..6)
+            .map(|i| Shot {
+                name: format!("shot-{}", i),
+                path: "/".to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let config = Config {
+            base_url: "http://localhost:9".to_string(),
+            concurrency: 3,
+            shots,
+            ..Default::default()
+        };
+
+        let Some((result, stats, _)) = capture_with_fake_playwright(&config).await else {
+            return;
+        };
+        assert_eq!(result.captured.len(), 6);
+        assert_eq!(stats["maxActive"], 3);
+        assert_eq!(stats["contexts"], 1);
+        assert_eq!(stats["maxPagesPerContext"], 3);
+
+        let isolated = Config {
+            reuse_contexts: false,
+            ..config
+        };
+        let (result, stats, _) = capture_with_fake_playwright(&isolated).await.unwrap();
+        assert_eq!(result.captured.len(), 6);
+        assert_eq!(stats["maxActive"], 3);
+        assert_eq!(stats["contexts"], 6);
+        assert_eq!(stats["closedContexts"], 6);
+        assert_eq!(stats["maxPagesPerContext"], 1);
+    }
+
+    #[tokio::test]
+    async fn element_box_is_in_screenshot_pixels() {
+        let config = Config {
+            base_url: "http://localhost:9".to_string(),
+            viewports: vec![crate::config::NamedViewport {
+                name: "retina".to_string(),
+                width: 640,
+                height: 480,
+                device_scale_factor: Some(2.0),
+                ..Default::default()
+            }],
+            shots: vec![Shot {
+                name: "card".to_string(),
+                path: "/".to_string(),
+                selector: Some(".card".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let Some((result, _, _)) = capture_with_fake_playwright(&config).await else {
+            return;
+        };
+
+        // The fake element is at 10.5,20 and 30×40 CSS pixels, so the clip is
+        // 10,20 and 31×40, doubled like the PNG
+        assert_eq!(
+            result.captured[0].element,
+            Some(Region {
+                x: 20,
+                y: 40,
+                width: 62,
+                height: 80
+            })
+        );
+    }
+
+    #[test]
+    fn capture_stats_round_trip_and_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let shot = |name: &str, stability: Option<Stability>| CapturedShot {
+            name: name.to_string(),
+            path: PathBuf::new(),
+            element: None,
+            masked: Vec::new(),
+            stability,
+            duration_ms: None,
+            page_errors: PageErrors::default(),
+        };
+        let unstable = Stability {
+            attempts: 12,
+            duration_ms: 5000,
+            stable: false,
+        };
+
+        save_capture_stats(
+            dir.path(),
+            &[shot("chart", Some(unstable)), shot("static", None)],
+        )
+        .unwrap();
+        save_capture_stats(dir.path(), &[shot("static", None)]).unwrap();
+
+        let stats = load_capture_stats(dir.path());
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats["chart"], unstable);
+        assert!(std::fs::read_to_string(dir.path().join(CAPTURE_STATS_FILE))
+            .unwrap()
+            .contains(r#""durationMs": 5000"#));
+    }
+
+    #[test]
+    fn page_errors_are_saved_for_captured_and_failed_shots() {
+        let dir = tempfile::tempdir().unwrap();
+        let errors = |message: &str| PageErrors {
+            console: vec![message.to_string()],
+            ..PageErrors::default()
+        };
+        let captured = |name: &str, page_errors: PageErrors| CapturedShot {
+            name: name.to_string(),
+            path: PathBuf::new(),
+            element: None,
+            masked: Vec::new(),
+            stability: None,
+            duration_ms: None,
+            page_errors,
+        };
+
+        let result = CaptureResult {
+            captured: vec![
+                captured("noisy", errors("Warning: key prop")),
+                captured("clean", PageErrors::default()),
+            ],
+            failed: vec![FailedShot {
+                name: "broken".to_string(),
+                error: "1 page error(s)".to_string(),
+                duration_ms: None,
+                page_errors: errors("boom"),
+                artifacts: Vec::new(),
+            }],
+            ..CaptureResult::default()
+        };
+        save_page_errors(dir.path(), &result).unwrap();
+        assert_eq!(
+            result
+                .with_page_errors()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>(),
+            vec!["noisy", "broken"]
+        );
+
+        // Recapturing "noisy" without errors clears its entry and keeps "broken"
+        let rerun = CaptureResult {
+            captured: vec![captured("noisy", PageErrors::default())],
+            ..CaptureResult::default()
+        };
+        save_page_errors(dir.path(), &rerun).unwrap();
+
+        let saved = load_page_errors(dir.path());
+        assert_eq!(saved.len(), 1);
+        assert_eq!(
+            saved["broken"].iter().collect::<Vec<_>>(),
+            vec![("console", "boom")]
+        );
+    }
+
+    #[test]
+    fn parse_event_reads_ndjson_lines() {
+        assert_eq!(
+            parse_event(r#"{"type":"started","total":4}"#),
+            Some(CaptureEvent::Started { total: 4 })
+        );
+        assert_eq!(
+            parse_event(r#"{"type":"progress","completed":1,"total":4}"#),
+            Some(CaptureEvent::Progress {
+                completed: 1,
+                total: 4
+            })
+        );
+        assert_eq!(
+            parse_event(r#"{"type":"failed","name":"button","error":"Timeout","durationMs":812}"#),
+            Some(CaptureEvent::Failed(FailedShot {
+                name: "button".to_string(),
+                error: "Timeout".to_string(),
+                duration_ms: Some(812),
+                page_errors: PageErrors::default(),
+                artifacts: Vec::new(),
+            }))
+        );
+
+        let Some(CaptureEvent::Captured(shot)) = parse_event(
+            r#"{"type":"captured","name":"card","path":"/tmp/card.png","element":null}"#,
+        ) else {
+            panic!("expected a captured event");
+        };
+        assert_eq!(shot.name, "card");
+        assert_eq!(shot.path, PathBuf::from("/tmp/card.png"));
+        assert!(shot.masked.is_empty());
+        assert!(shot.stability.is_none());
+        assert!(shot.page_errors.is_empty());
+
+        let Some(CaptureEvent::Failed(shot)) = parse_event(
+            r#"{"type":"failed","name":"modal","error":"1 page error(s)","pageErrors":{"exceptions":["TypeError: x is undefined"],"requests":["GET /api/user: 500"]}}"#,
+        ) else {
+            panic!("expected a failed event");
+        };
+        assert_eq!(shot.page_errors.len(), 2);
+        assert_eq!(shot.page_errors.requests, vec!["GET /api/user: 500"]);
+
+        let Some(CaptureEvent::Failed(shot)) = parse_event(
+            r#"{"type":"failed","name":"card","error":"Timeout 10000ms exceeded","artifacts":["failures/card/page.html","failures/card/screenshot.png"]}"#,
+        ) else {
+            panic!("expected a failed event");
+        };
+        assert_eq!(
+            shot.artifacts,
+            vec!["failures/card/page.html", "failures/card/screenshot.png"]
+        );
+
+        // Stray output from user scripts is skipped
+        assert_eq!(parse_event("Loaded fixtures"), None);
+        assert_eq!(parse_event(r#"{"unrelated":true}"#), None);
+    }
+
+    #[test]
+    fn apply_event_sorts_shots_into_result() {
+        let captured = |name: &str, stable: bool| {
+            CaptureEvent::Captured(CapturedShot {
+                name: name.to_string(),
+                path: PathBuf::new(),
+                element: None,
+                masked: Vec::new(),
+                stability: Some(Stability {
+                    attempts: 2,
+                    duration_ms: 100,
+                    stable,
+                }),
+                duration_ms: None,
+                page_errors: PageErrors::default(),
+            })
+        };
+
+        let mut result = CaptureResult::default();
+        result.apply(CaptureEvent::Started { total: 3 });
+        result.apply(captured("steady", true));
+        result.apply(captured("chart", false));
+        result.apply(CaptureEvent::Failed(FailedShot {
+            name: "modal".to_string(),
+            error: "Timeout".to_string(),
+            duration_ms: None,
+            page_errors: PageErrors::default(),
+            artifacts: Vec::new(),
+        }));
+        result.apply(CaptureEvent::Progress {
+            completed: 3,
+            total: 3,
+        });
+
+        assert_eq!(result.captured[0].name, "steady");
+        assert_eq!(result.unstable[0].name, "chart");
+        assert_eq!(result.failed[0].name, "modal");
+        assert_eq!(result.total(), 3);
+        assert!(result.error.is_none());
+    }
+
+    #[test]
+    fn generate_script_passes_stable_capture_per_shot() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "stableCapture": { "timeout": 3000 },
+                "shots": [
+                    { "name": "chart", "path": "/chart" },
+                    { "name": "static", "path": "/static", "stableCapture": false }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(
+            r#""name":"chart","path":"/chart","stableCapture":{"timeout":3000,"interval":100}"#
+        ));
+        assert!(script.contains(r#""name":"static","path":"/static"}"#));
+        assert!(script.contains("if (shot.stableCapture) {"));
+        assert!(script.contains("stability = await captureUntilStable("));
+    }
+
+    #[test]
+    fn generate_script_waits_for_readiness_with_configured_timeouts() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "ready": { "signal": true },
+                "timeouts": { "wait": 5000 },
+                "shots": [
+                    { "name": "home", "path": "/" },
+                    { "name": "slow", "path": "/slow", "ready": false, "timeouts": { "navigation": 90000 } }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(
+            r#""name":"home","path":"/","ready":{"fonts":true,"images":true,"signal":"window.__PIXELGUARD_READY__ === true"}"#
+        ));
+        assert!(script.contains(
+            r#""name":"slow","path":"/slow","timeouts":{"navigation":90000,"wait":5000}}"#
+        ));
+        assert!(script.contains(r#"timeouts: {"navigation":30000,"wait":5000}"#));
+        assert!(script.contains("const timeouts = shot.timeouts || config.timeouts;"));
+        assert!(script.contains("await waitForReady(page, shot.ready, timeouts.wait);"));
+        assert!(script.contains("timeout: timeouts.navigation"));
+        assert!(!script.contains("10000"));
+    }
+
+    #[test]
+    fn generate_script_collects_page_errors() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "pageErrors": { "allow": ["favicon"] },
+                "shots": [
+                    { "name": "home", "path": "/" },
+                    { "name": "form", "path": "/form", "pageErrors": { "fail": true } }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(r#""name":"home","path":"/"}"#));
+        assert!(script.contains(
+            r#""name":"form","path":"/form","pageErrors":{"fail":true,"allow":["favicon"]}"#
+        ));
+        assert!(script.contains(r#"pageErrors: {"fail":false,"allow":["favicon"]}"#));
+        assert!(script.contains("const collected = collectPageErrors(page, handled);"));
+        assert!(script.contains("if (pageErrors && pageErrorPolicy.fail) {"));
+    }
+
+    /// Returns the value of a top-level `config` field in a generated script.
+    fn script_config_field<'a>(script: &'a str, field: &str) -> &'a str {
+        let prefix = format!("    {}: ", field);
+        let line = script
+            .lines()
+            .find(|line| line.starts_with(&prefix))
+            .unwrap_or_else(|| panic!("no `{}` in the script config", field));
+        line[prefix.len()..].trim_end_matches(',')
+    }
+
+    /// Runs `call` with Node.js against the definition of the script function
+    /// `name`, returning what it prints as JSON, or None if Node.js is missing.
+    fn eval_script_function(script: &str, name: &str, call: &str) -> Option<String> {
+        let start = script
+            .find(&format!("\nfunction {}(", name))
+            .expect("function not found")
+            + 1;
+        let end = start + script[start..].find("\n}\n").unwrap() + 2;
+        let source = format!(
+            "{}\nconsole.log(JSON.stringify({}));",
+            &script[start..end],
+            call
+        );
+        let output = std::process::Command::new("node")
+            .args(["-e", &source])
+            .output()
+            .ok()?;
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Some(String::from_utf8(output.stdout).unwrap().trim().to_string())
+    }
+
+    #[test]
+    fn page_error_filter_handles_policy_without_allow_list() {
+        let config = Config {
+            shots: vec![Shot {
+                name: "home".to_string(),
+                path: "/".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        // An empty `allow` is left out of the policy the script receives
+        let policy = script_config_field(&script, "pageErrors");
+        assert_eq!(policy, r#"{"fail":false}"#);
+
+        let errors = "{ console: ['boom'], exceptions: [], requests: [] }";
+        let Some(left) = eval_script_function(
+            &script,
+            "unexpectedErrors",
+            &format!("unexpectedErrors({}, ({}).allow)", errors, policy),
+        ) else {
+            return;
+        };
+        assert_eq!(
+            left,
+            r#"{"console":["boom"],"exceptions":[],"requests":[],"count":1}"#
+        );
+
+        let allowed = eval_script_function(
+            &script,
+            "unexpectedErrors",
+            &format!("unexpectedErrors({}, ['bo+m'])", errors),
+        )
+        .unwrap();
+        assert_eq!(allowed, "undefined");
+    }
+
+    #[test]
+    fn generate_script_saves_failure_artifacts_when_enabled() {
+        let config: Config = serde_json::from_str(
+            r#"{ "failureArtifacts": "trace", "shots": [{ "name": "home", "path": "/" }] }"#,
+        )
+        .unwrap();
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(r#"failuresDir: "/project/.pixelguard/failures","#));
+        assert!(script.contains(r#"failureArtifacts: "trace""#));
+        assert!(script
+            .contains("const artifacts = await saveFailureArtifacts(page, context, displayName, timeouts.wait);"));
+        assert!(
+            script.contains("await context.tracing.start({ screenshots: true, snapshots: true });")
+        );
+
+        // Recording HARs has nothing to debug
+        let script = build_playwright_script(
+            &config,
+            Path::new("/tmp/output"),
+            Path::new("/project"),
+            ScriptMode::RecordHar,
+        )
+        .unwrap();
+        assert!(script.contains(r#"failureArtifacts: "off""#));
+    }
+
+    #[test]
+    fn generate_script_saves_a11y_snapshots_when_enabled() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "a11y": true,
+                "shots": [
+                    { "name": "form", "path": "/form" },
+                    { "name": "chart", "path": "/chart", "a11y": false }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(r#""name":"form","path":"/form","a11y":true}"#));
+        assert!(script.contains(r#""name":"chart","path":"/chart"}"#));
+    }
+
+    #[tokio::test]
+    async fn a11y_snapshot_is_the_aria_snapshot_of_the_shot_root() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "baseUrl": "http://localhost:9",
+                "a11y": true,
+                "shots": [
+                    { "name": "form", "path": "/form" },
+                    { "name": "card", "path": "/card", "selector": ".card" },
+                    { "name": "chart", "path": "/chart", "a11y": false }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let Some((_, _, dir)) = capture_with_fake_playwright(&config).await else {
+            return;
+        };
+
+        let current = dir.path().join(".pixelguard/current");
+        let snapshot = |name: &str| {
+            std::fs::read_to_string(crate::a11y::snapshot_path(&current, name)).unwrap()
+        };
+        assert_eq!(snapshot("form"), "- region \"body\"");
+        assert_eq!(snapshot("card"), "- region \".card\"");
+        assert!(!crate::a11y::snapshot_path(&current, "chart").exists());
+    }
+
+    #[test]
+    fn generate_script_saves_dom_snapshots_when_enabled() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "shots": [
+                    { "name": "card", "path": "/card", "selector": ".card", "domSnapshot": true },
+                    { "name": "home", "path": "/" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let script =
+            generate_playwright_script(&config, Path::new("/tmp/output"), Path::new("/project"))
+                .unwrap();
+
+        assert!(script.contains(r#""selector":".card","domSnapshot":true}"#));
+        assert!(script.contains(r#""name":"home","path":"/"}"#));
+        assert!(script.contains("await getDomSnapshot(page, shot.selector)"));
+        assert!(script.contains("const domPath = `${config.outputDir}/${displayName}.dom.json`;"));
+    }
+
+    #[test]
+    fn update_baseline_copies_snapshots_with_screenshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let current_dir = dir.path().join(".pixelguard/current");
+        std::fs::create_dir_all(&current_dir).unwrap();
+        for name in ["form", "chart"] {
+            std::fs::write(current_dir.join(format!("{}.png", name)), name).unwrap();
+            std::fs::write(current_dir.join(format!("{}.a11y.yaml", name)), "{}").unwrap();
+            std::fs::write(current_dir.join(format!("{}.dom.json", name)), "{}").unwrap();
+        }
+
+        let updated = update_baseline(
+            &Config::default(),
+            dir.path(),
+            None,
+            Some(&["form".to_string()]),
+        )
+        .unwrap();
+
+        let baseline_dir = dir.path().join(".pixelguard/baseline");
+        assert_eq!(updated, 1);
+        assert!(baseline_dir.join("form.png").exists());
+        assert!(baseline_dir.join("form.a11y.yaml").exists());
+        assert!(!baseline_dir.join("chart.a11y.yaml").exists());
+        assert!(baseline_dir.join("form.dom.json").exists());
+        assert!(!baseline_dir.join("chart.dom.json").exists());
+    }
+
     #[test]
     fn get_screenshot_dirs_returns_correct_paths() {
         let config = Config {
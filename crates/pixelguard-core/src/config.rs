@@ -3,20 +3,27 @@
 //! This module handles loading, saving, and managing the `pixelguard.config.json` file.
 //! All configuration fields are optional with sensible defaults.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::Path;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::shard::Shard;
+
 /// Main configuration for Pixelguard.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
-    /// Source type (e.g., "storybook", "nextjs", "vite", "manual")
+    /// Source type (e.g., "storybook", "storybook-static", "nextjs", "vite", "manual")
     #[serde(default)]
     pub source: String,
 
+    /// Directory of a static build to serve on an ephemeral port instead of using
+    /// `baseUrl` (defaults to `storybook-static` for the `storybook-static` source)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub static_dir: Option<String>,
+
     /// Base URL for the dev server
     #[serde(default)]
     pub base_url: String,
@@ -25,6 +32,10 @@ pub struct Config {
     #[serde(default)]
     pub port: Option<u16>,
 
+    /// Dev server that `pixelguard test` starts before capture and stops afterwards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_server: Option<WebServer>,
+
     /// Glob patterns to include
     #[serde(default = "default_include")]
     pub include: Vec<String>,
@@ -44,6 +55,134 @@ pub struct Config {
     #[serde(default)]
     pub viewports: Vec<NamedViewport>,
 
+    /// Browser engines to capture with.
+    ///
+    /// When set, each shot is captured in each browser.
+    /// Screenshots are named `{shot}~{browser}.png` (or `{shot}@{viewport}~{browser}.png`).
+    #[serde(default)]
+    pub browsers: Vec<Browser>,
+
+    /// Rules restricting shots matched by name to some of the `viewports` and
+    /// `browsers` (a shot's own `viewports`/`browsers` take precedence)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matrix: Vec<MatrixRule>,
+
+    /// Colour schemes to emulate (`prefers-color-scheme`); a single value or a list.
+    ///
+    /// Like the other emulation dimensions, each value multiplies the capture
+    /// matrix and adds a `+scheme-{value}` suffix to screenshot names.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub color_scheme: Vec<ColorScheme>,
+
+    /// Reduced-motion preferences to emulate (`prefers-reduced-motion`)
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub reduced_motion: Vec<ReducedMotion>,
+
+    /// Locales to emulate (e.g., "en-US", "ar-SA")
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub locale: Vec<String>,
+
+    /// Time zones to emulate (e.g., "Europe/Berlin")
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub timezone_id: Vec<String>,
+
+    /// CSS media types to emulate (screen or print)
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub media: Vec<Media>,
+
+    /// Capture the full scrollable page instead of only the visible viewport.
+    ///
+    /// Can be overridden per viewport and per shot.
+    #[serde(default)]
+    pub full_page: bool,
+
+    /// Maximum height in pixels for full-page screenshots (unlimited when unset)
+    #[serde(default)]
+    pub max_page_height: Option<u32>,
+
+    /// CSS selectors of elements painted over with a solid colour in every shot
+    #[serde(default)]
+    pub mask: Vec<String>,
+
+    /// Regions excluded from the pixel comparison in every shot
+    #[serde(default)]
+    pub ignore_regions: Vec<Region>,
+
+    /// Deterministic rendering: freeze animations, carets, scrollbars, clocks and
+    /// randomness. `true` enables every part; an object switches parts individually.
+    #[serde(
+        default,
+        deserialize_with = "stabilize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stabilize: Option<Stabilize>,
+
+    /// Recapture each shot until two consecutive screenshots are identical.
+    /// `true` uses the default timeout and interval.
+    #[serde(
+        default,
+        deserialize_with = "stable_capture_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stable_capture: Option<StableCapture>,
+
+    /// Conditions to wait for after a page loads, before the shot's actions run.
+    /// `true` waits for web fonts and images; an object also sets the app's ready signal.
+    #[serde(
+        default,
+        deserialize_with = "ready_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ready: Option<Ready>,
+
+    /// Timeouts for page loads and for waiting on selectors, actions and readiness
+    #[serde(default, skip_serializing_if = "Timeouts::is_empty")]
+    pub timeouts: Timeouts,
+
+    /// Whether console errors, uncaught exceptions and failed requests fail a shot,
+    /// and the messages to ignore
+    #[serde(default, skip_serializing_if = "PageErrorPolicy::is_empty")]
+    pub page_errors: PageErrorPolicy,
+
+    /// Save each page's accessibility tree next to its screenshot and report
+    /// structural changes to it as `a11yChanged`
+    #[serde(default)]
+    pub a11y: bool,
+
+    /// Save each shot's root element and descendants with their boxes and key
+    /// computed styles, and list the elements that changed when a shot changes
+    #[serde(default)]
+    pub dom_snapshot: bool,
+
+    /// What to save under `failures/<shot>/` when a shot fails to capture
+    #[serde(default)]
+    pub failure_artifacts: FailureArtifacts,
+
+    /// Network route rules applied to every shot (mocked responses, aborted
+    /// requests or HAR replay)
+    #[serde(default)]
+    pub routes: Vec<RouteRule>,
+
+    /// Login flows run once before capture; their cookies and localStorage are
+    /// reused by every shot that uses the role
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup: Option<Setup>,
+
+    /// How the browser is obtained: launched locally (with optional arguments,
+    /// executable or channel) or connected to at `wsEndpoint`/`cdpEndpoint`
+    #[serde(default, skip_serializing_if = "BrowserOptions::is_empty")]
+    pub browser: BrowserOptions,
+
+    /// Where screenshots come from: Playwright, a capture plugin or pre-rendered
+    /// PNGs copied from a directory. When unset, a loaded capture plugin is used,
+    /// otherwise Playwright.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<CaptureBackendConfig>,
+
+    /// How `--changed-since` traces changed files to the shots they affect
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub affected: Option<Affected>,
+
     /// Diff threshold (0.0 to 1.0)
     #[serde(default = "default_threshold")]
     pub threshold: f64,
@@ -56,6 +195,12 @@ pub struct Config {
     #[serde(default = "default_concurrency")]
     pub concurrency: usize,
 
+    /// Share one browser context between shots with the same viewport, emulation,
+    /// login role and stabilize settings (default: true). Cookies and storage
+    /// written by one shot are seen by the next; set false to give each shot its own.
+    #[serde(default = "default_reuse_contexts")]
+    pub reuse_contexts: bool,
+
     /// List of shots to capture
     #[serde(default)]
     pub shots: Vec<Shot>,
@@ -67,6 +212,16 @@ pub struct Config {
     /// Plugin-specific options keyed by plugin name
     #[serde(default)]
     pub plugin_options: HashMap<String, serde_json::Value>,
+
+    /// The shard of shot × viewport combinations to test, set by `--shard` rather
+    /// than the config file (see [`Config::set_shard`])
+    #[serde(skip)]
+    pub shard: Option<Shard>,
+
+    /// Shots left out by `--changed-since`, whose baselines are carried forward
+    /// as unchanged instead of being captured and compared
+    #[serde(skip)]
+    pub carried_forward: BTreeSet<String>,
 }
 
 /// A plugin entry in the configuration.
@@ -123,20 +278,230 @@ pub struct Viewport {
 ///
 /// When multiple viewports are configured, each shot is captured at each viewport size,
 /// with screenshots named `{shot}@{viewport}.png`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// A viewport can name a built-in [`DevicePreset`] via `device`; any field set explicitly
+/// overrides the preset's value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct NamedViewport {
     /// Unique name for this viewport (e.g., "desktop", "mobile")
     pub name: String,
 
-    /// Width in pixels
+    /// Name of a built-in device preset (e.g., "iPhone 13", "Pixel 7")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+
+    /// Width in pixels (taken from `device` when unset)
+    #[serde(default)]
     pub width: u32,
 
-    /// Height in pixels
+    /// Height in pixels (taken from `device` when unset)
+    #[serde(default)]
+    pub height: u32,
+
+    /// Device pixel ratio (e.g., 2 for retina displays)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_scale_factor: Option<f64>,
+
+    /// Whether to emulate a mobile browser (meta viewport tag, mobile layout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_mobile: Option<bool>,
+
+    /// Whether to emulate touch support
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_touch: Option<bool>,
+
+    /// User agent string to send
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+
+    /// Override the config-level `fullPage` setting for this viewport
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_page: Option<bool>,
+}
+
+impl NamedViewport {
+    /// Returns this viewport with its device preset applied.
+    ///
+    /// Fields set on the viewport take precedence over the preset. Fails if the
+    /// device is unknown or if the viewport ends up without a width and height.
+    pub fn resolve(&self) -> Result<NamedViewport> {
+        let mut viewport = self.clone();
+
+        if let Some(device) = &self.device {
+            let Some(preset) = DevicePreset::find(device) else {
+                let known: Vec<_> = DEVICE_PRESETS.iter().map(|p| p.name).collect();
+                anyhow::bail!(
+                    "❌ Unknown device '{}' in viewport '{}'.\n\n\
+                     💡 Available devices:\n  {}",
+                    device,
+                    self.name,
+                    known.join("\n  ")
+                );
+            };
+
+            if viewport.width == 0 {
+                viewport.width = preset.width;
+            }
+            if viewport.height == 0 {
+                viewport.height = preset.height;
+            }
+            viewport
+                .device_scale_factor
+                .get_or_insert(preset.device_scale_factor);
+            viewport.is_mobile.get_or_insert(preset.is_mobile);
+            viewport.has_touch.get_or_insert(preset.has_touch);
+            if viewport.user_agent.is_none() {
+                viewport.user_agent = preset.user_agent.map(String::from);
+            }
+        }
+
+        if viewport.width == 0 || viewport.height == 0 {
+            anyhow::bail!(
+                "❌ Viewport '{}' has no size.\n\n\
+                 💡 Solution: Set 'width' and 'height', or use a 'device' preset.",
+                self.name
+            );
+        }
+
+        Ok(viewport)
+    }
+}
+
+/// A built-in device emulation preset, referenced by name from `NamedViewport::device`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DevicePreset {
+    /// Preset name (matched case-insensitively)
+    pub name: &'static str,
+
+    /// Viewport width in CSS pixels
+    pub width: u32,
+
+    /// Viewport height in CSS pixels
     pub height: u32,
+
+    /// Device pixel ratio
+    pub device_scale_factor: f64,
+
+    /// Whether the device is a mobile browser
+    pub is_mobile: bool,
+
+    /// Whether the device supports touch
+    pub has_touch: bool,
+
+    /// User agent string, None to keep the browser's default
+    pub user_agent: Option<&'static str>,
+}
+
+impl DevicePreset {
+    /// Looks up a preset by name, ignoring case.
+    pub fn find(name: &str) -> Option<&'static DevicePreset> {
+        DEVICE_PRESETS
+            .iter()
+            .find(|preset| preset.name.eq_ignore_ascii_case(name))
+    }
 }
 
+const IOS_15_USER_AGENT: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 15_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.0 Mobile/15E148 Safari/604.1";
+const IOS_17_USER_AGENT: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1";
+const IPADOS_USER_AGENT: &str = "Mozilla/5.0 (iPad; CPU OS 15_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/15.0 Mobile/15E148 Safari/604.1";
+
+/// Built-in device presets. Sizes are the visible viewport in CSS pixels.
+pub const DEVICE_PRESETS: &[DevicePreset] = &[
+    DevicePreset {
+        name: "iPhone SE",
+        width: 375,
+        height: 667,
+        device_scale_factor: 2.0,
+        is_mobile: true,
+        has_touch: true,
+        user_agent: Some(IOS_15_USER_AGENT),
+    },
+    DevicePreset {
+        name: "iPhone 13",
+        width: 390,
+        height: 664,
+        device_scale_factor: 3.0,
+        is_mobile: true,
+        has_touch: true,
+        user_agent: Some(IOS_15_USER_AGENT),
+    },
+    DevicePreset {
+        name: "iPhone 13 Pro Max",
+        width: 428,
+        height: 746,
+        device_scale_factor: 3.0,
+        is_mobile: true,
+        has_touch: true,
+        user_agent: Some(IOS_15_USER_AGENT),
+    },
+    DevicePreset {
+        name: "iPhone 15",
+        width: 393,
+        height: 659,
+        device_scale_factor: 3.0,
+        is_mobile: true,
+        has_touch: true,
+        user_agent: Some(IOS_17_USER_AGENT),
+    },
+    DevicePreset {
+        name: "iPad Mini",
+        width: 768,
+        height: 1024,
+        device_scale_factor: 2.0,
+        is_mobile: true,
+        has_touch: true,
+        user_agent: Some(IPADOS_USER_AGENT),
+    },
+    DevicePreset {
+        name: "iPad Pro 11",
+        width: 834,
+        height: 1194,
+        device_scale_factor: 2.0,
+        is_mobile: true,
+        has_touch: true,
+        user_agent: Some(IPADOS_USER_AGENT),
+    },
+    DevicePreset {
+        name: "Pixel 5",
+        width: 393,
+        height: 727,
+        device_scale_factor: 2.75,
+        is_mobile: true,
+        has_touch: true,
+        user_agent: Some("Mozilla/5.0 (Linux; Android 11; Pixel 5) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36"),
+    },
+    DevicePreset {
+        name: "Pixel 7",
+        width: 412,
+        height: 839,
+        device_scale_factor: 2.625,
+        is_mobile: true,
+        has_touch: true,
+        user_agent: Some("Mozilla/5.0 (Linux; Android 14; Pixel 7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36"),
+    },
+    DevicePreset {
+        name: "Galaxy S9+",
+        width: 320,
+        height: 658,
+        device_scale_factor: 4.5,
+        is_mobile: true,
+        has_touch: true,
+        user_agent: Some("Mozilla/5.0 (Linux; Android 8.0.0; SM-G965U) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36"),
+    },
+    DevicePreset {
+        name: "Desktop HiDPI",
+        width: 1280,
+        height: 720,
+        device_scale_factor: 2.0,
+        is_mobile: false,
+        has_touch: false,
+        user_agent: None,
+    },
+];
+
 /// A single screenshot configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Shot {
     /// Unique name for this shot
@@ -152,377 +517,2641 @@ pub struct Shot {
     /// Optional delay in milliseconds after page load
     #[serde(skip_serializing_if = "Option::is_none")]
     pub delay: Option<u64>,
-}
 
-fn default_include() -> Vec<String> {
-    vec!["**/*".to_string()]
-}
+    /// Interaction steps to run in order before capturing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<ShotAction>,
 
-fn default_threshold() -> f64 {
-    0.01
-}
+    /// Optional CSS selector to clip the screenshot to a single element
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
 
-fn default_output_dir() -> String {
-    ".pixelguard".to_string()
-}
+    /// Padding in pixels around the `selector` element's bounding box
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding: Option<u32>,
 
-fn default_concurrency() -> usize {
-    4
-}
+    /// Names of the viewports to capture this shot at (all of them when empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub viewports: Vec<String>,
 
-fn default_viewport_width() -> u32 {
-    1280
+    /// Browsers to capture this shot in (all of them when empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub browsers: Vec<Browser>,
+
+    /// Override the viewport and config-level `fullPage` setting for this shot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_page: Option<bool>,
+
+    /// CSS selectors of elements to paint over, in addition to the config-level `mask`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mask: Vec<String>,
+
+    /// Regions to exclude from comparison, in addition to the config-level `ignoreRegions`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_regions: Vec<Region>,
+
+    /// Override parts of the config-level `stabilize` setting for this shot
+    /// (`false` turns stabilization off)
+    #[serde(
+        default,
+        deserialize_with = "stabilize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stabilize: Option<Stabilize>,
+
+    /// Turn the config-level `stableCapture` on or off for this shot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stable_capture: Option<bool>,
+
+    /// Override parts of the config-level `ready` conditions for this shot
+    /// (`false` turns waiting off)
+    #[serde(
+        default,
+        deserialize_with = "ready_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ready: Option<Ready>,
+
+    /// Override the config-level `timeouts` for this shot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeouts: Option<Timeouts>,
+
+    /// Override whether page errors fail this shot, and more messages to ignore
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_errors: Option<PageErrorPolicy>,
+
+    /// Turn the config-level `a11y` snapshot on or off for this shot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub a11y: Option<bool>,
+
+    /// Turn the config-level `domSnapshot` on or off for this shot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dom_snapshot: Option<bool>,
+
+    /// Route rules for this shot, matched before the config-level `routes`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<RouteRule>,
+
+    /// Auth role to capture as: a role name from `setup`, `true` for the default
+    /// role, or `false` to capture without logging in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<ShotAuth>,
+
+    /// Module that renders this shot, relative to the project root (taken from
+    /// Storybook's index for discovered stories); `--changed-since` traces its imports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import_path: Option<String>,
 }
 
-fn default_viewport_height() -> u32 {
-    720
+/// A Playwright browser engine used for capture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Browser {
+    /// Chromium (Chrome, Edge)
+    #[default]
+    Chromium,
+
+    /// Firefox
+    Firefox,
+
+    /// WebKit (Safari)
+    Webkit,
 }
 
-impl Default for Viewport {
-    fn default() -> Self {
-        Self {
-            width: default_viewport_width(),
-            height: default_viewport_height(),
+impl Browser {
+    /// Returns the Playwright name of this browser, as used in screenshot file names.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Browser::Chromium => "chromium",
+            Browser::Firefox => "firefox",
+            Browser::Webkit => "webkit",
         }
     }
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            source: String::new(),
-            base_url: String::new(),
-            port: None,
-            include: default_include(),
-            exclude: Vec::new(),
-            viewport: Viewport::default(),
-            viewports: Vec::new(),
-            threshold: default_threshold(),
-            output_dir: default_output_dir(),
-            concurrency: default_concurrency(),
-            shots: Vec::new(),
-            plugins: Vec::new(),
-            plugin_options: HashMap::new(),
-        }
+impl std::fmt::Display for Browser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
-impl Config {
-    /// Creates a new Config with default values.
-    pub fn new() -> Self {
-        Self::default()
-    }
+/// Viewports and browsers for the shots whose names match a rule.
+///
+/// A shot matches when its name matches any `include` pattern (or `include` is
+/// empty) and no `exclude` pattern. For each dimension, the first matching rule
+/// that sets it wins; dimensions no rule sets are captured in full.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MatrixRule {
+    /// Glob patterns of shot names the rule applies to (e.g., `icon--*`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
 
-    /// Loads configuration from a file path.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the file cannot be read or parsed.
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let path = path.as_ref();
-        let content = std::fs::read_to_string(path).map_err(|e| {
-            anyhow::anyhow!(
-                "❌ Could not read config file at '{}'.\n\n\
-                 💡 Solutions:\n  \
-                 1️⃣ Run 'pixelguard init' to create a configuration\n  \
-                 2️⃣ Check file permissions\n  \
-                 3️⃣ Verify the file exists at the expected location\n\n\
-                 🔍 Error details: {}",
-                path.display(),
-                e
-            )
-        })?;
+    /// Glob patterns of shot names the rule skips
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
 
-        let config: Config = serde_json::from_str(&content).map_err(|e| {
-            anyhow::anyhow!(
-                "❌ Invalid JSON in config file '{}'.\n\n\
-                 🔍 Error details: {}\n\n\
-                 💡 Common issues:\n  \
-                 • Missing commas between properties\n  \
-                 • Trailing commas (not allowed in JSON)\n  \
-                 • Unquoted property names\n  \
-                 • Invalid escape sequences\n\n\
-                 🛠️  Use a JSON validator to check syntax: https://jsonlint.com",
-                path.display(),
-                e
-            )
-        })?;
+    /// Names of the viewports to capture matching shots at
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub viewports: Vec<String>,
 
-        Ok(config)
-    }
+    /// Browsers to capture matching shots in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub browsers: Vec<Browser>,
+}
 
-    /// Loads configuration from the default location in a directory,
-    /// or returns default configuration if the file doesn't exist.
+impl MatrixRule {
+    /// Returns true if the rule applies to the shot named `shot_name`.
     ///
     /// # Errors
     ///
-    /// Returns an error if the file exists but cannot be parsed.
-    pub fn load_or_default<P: AsRef<Path>>(dir: P) -> Result<Self> {
-        let config_path = dir.as_ref().join("pixelguard.config.json");
-
-        if config_path.exists() {
-            Self::load(&config_path)
-        } else {
-            Ok(Self::default())
-        }
+    /// Returns an error if a pattern is not a valid glob.
+    pub fn matches(&self, shot_name: &str) -> Result<bool> {
+        let matches_any = |patterns: &[String]| -> Result<bool> {
+            for pattern in patterns {
+                let glob = glob::Pattern::new(pattern).map_err(|e| {
+                    anyhow::anyhow!(
+                        "❌ Invalid pattern '{}' in `matrix`: {}\n\n\
+                         💡 Solution: Use a glob like \"button--*\" (`*` matches any characters).",
+                        pattern,
+                        e
+                    )
+                })?;
+                if glob.matches(shot_name) {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        };
+        Ok(
+            (self.include.is_empty() || matches_any(&self.include)?)
+                && !matches_any(&self.exclude)?,
+        )
     }
+}
 
-    /// Saves configuration to a file path.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the file cannot be written.
-    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let path = path.as_ref();
-        let content = serde_json::to_string_pretty(self)?;
-        std::fs::write(path, content)?;
-        Ok(())
-    }
+/// A `prefers-color-scheme` value to emulate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorScheme {
+    /// Light mode
+    Light,
 
-    /// Saves configuration to the default location in a directory.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the file cannot be written.
-    pub fn save_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
-        let config_path = dir.as_ref().join("pixelguard.config.json");
-        self.save(&config_path)
-    }
+    /// Dark mode
+    Dark,
 
-    /// Returns the path to the config file in a directory.
-    pub fn config_path<P: AsRef<Path>>(dir: P) -> std::path::PathBuf {
-        dir.as_ref().join("pixelguard.config.json")
-    }
+    /// No preference expressed
+    NoPreference,
+}
 
-    /// Checks if a config file exists in the given directory.
-    pub fn exists<P: AsRef<Path>>(dir: P) -> bool {
-        Self::config_path(dir).exists()
+impl ColorScheme {
+    /// Returns the CSS name of this colour scheme.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColorScheme::Light => "light",
+            ColorScheme::Dark => "dark",
+            ColorScheme::NoPreference => "no-preference",
+        }
     }
+}
 
-    /// Returns the viewports to use for testing.
-    ///
-    /// If `viewports` is configured, returns those. Otherwise, returns a single
-    /// viewport named "default" based on the legacy `viewport` field.
-    ///
-    /// This provides backward compatibility: configs with only `viewport` work
-    /// as before, while configs with `viewports` enable multi-viewport testing.
-    pub fn effective_viewports(&self) -> Vec<NamedViewport> {
-        if !self.viewports.is_empty() {
-            self.viewports.clone()
-        } else {
-            vec![NamedViewport {
-                name: "default".to_string(),
-                width: self.viewport.width,
-                height: self.viewport.height,
-            }]
+/// A `prefers-reduced-motion` value to emulate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReducedMotion {
+    /// Motion should be reduced
+    Reduce,
+
+    /// No preference expressed
+    NoPreference,
+}
+
+impl ReducedMotion {
+    /// Returns the CSS name of this reduced-motion preference.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReducedMotion::Reduce => "reduce",
+            ReducedMotion::NoPreference => "no-preference",
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::tempdir;
+/// A CSS media type to emulate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Media {
+    /// Screen media
+    Screen,
 
-    #[test]
-    fn default_config_has_sensible_values() {
-        let config = Config::default();
+    /// Print media
+    Print,
+}
 
-        assert_eq!(config.viewport.width, 1280);
-        assert_eq!(config.viewport.height, 720);
-        assert_eq!(config.threshold, 0.01);
-        assert_eq!(config.output_dir, ".pixelguard");
-        assert_eq!(config.include, vec!["**/*"]);
+impl Media {
+    /// Returns the CSS name of this media type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Media::Screen => "screen",
+            Media::Print => "print",
+        }
     }
+}
 
-    #[test]
-    fn config_serializes_to_json() {
+/// One combination of the emulation dimensions (`colorScheme`, `reducedMotion`,
+/// `locale`, `timezoneId` and `media`).
+///
+/// Dimensions that are not configured are None and leave the browser default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Emulation {
+    /// Colour scheme to emulate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color_scheme: Option<ColorScheme>,
+
+    /// Reduced-motion preference to emulate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reduced_motion: Option<ReducedMotion>,
+
+    /// Locale to emulate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+
+    /// Time zone to emulate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone_id: Option<String>,
+
+    /// Media type to emulate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media: Option<Media>,
+}
+
+/// Screenshot name suffix prefixes of each emulation dimension, with the dimension's
+/// config key.
+pub const EMULATION_PREFIXES: &[(&str, &str)] = &[
+    ("scheme-", "colorScheme"),
+    ("motion-", "reducedMotion"),
+    ("locale-", "locale"),
+    ("tz-", "timezoneId"),
+    ("media-", "media"),
+];
+
+impl Emulation {
+    /// Returns the screenshot name suffix for this combination.
+    ///
+    /// Each set dimension adds a `+{prefix}{value}` token in a fixed order, e.g.
+    /// `+scheme-dark+locale-ar-SA`. Returns an empty string when nothing is emulated.
+    pub fn suffix(&self) -> String {
+        let values = [
+            self.color_scheme.map(|c| c.as_str().to_string()),
+            self.reduced_motion.map(|m| m.as_str().to_string()),
+            self.locale.clone(),
+            self.timezone_id.clone(),
+            self.media.map(|m| m.as_str().to_string()),
+        ];
+
+        EMULATION_PREFIXES
+            .iter()
+            .zip(values)
+            .filter_map(|((prefix, _), value)| {
+                value.map(|v| format!("+{}{}", prefix, suffix_token(&v)))
+            })
+            .collect()
+    }
+}
+
+/// Makes a value safe for use in a file name (e.g., "Etc/GMT+5" becomes "Etc-GMTplus5").
+fn suffix_token(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '+' => "plus".to_string(),
+            c if c.is_ascii_alphanumeric() || c == '-' || c == '_' => c.to_string(),
+            _ => "-".to_string(),
+        })
+        .collect()
+}
+
+/// Fixed time used by the stabilized fake clock when `stabilize.time` is not set.
+pub const DEFAULT_STABILIZE_TIME: &str = "2024-01-01T00:00:00.000Z";
+
+/// Seed used for stabilized `Math.random()` when `stabilize.seed` is not set.
+pub const DEFAULT_STABILIZE_SEED: u32 = 42;
+
+/// Parts of deterministic rendering to switch on or off.
+///
+/// In the config, `"stabilize": true` enables every part and `false` disables every
+/// part. In an object, parts that are not mentioned are enabled unless another level
+/// (config or shot) says otherwise.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Stabilize {
+    /// Disable CSS animations and transitions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animations: Option<bool>,
+
+    /// Hide the blinking text caret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caret: Option<bool>,
+
+    /// Hide scrollbars
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrollbars: Option<bool>,
+
+    /// Freeze `Date.now()` and `new Date()` at `time`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<bool>,
+
+    /// Replace `Math.random()` with a generator seeded by `seed`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub random: Option<bool>,
+
+    /// Fixed time for the fake clock (ISO 8601)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+
+    /// Seed for `Math.random()`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+}
+
+impl Stabilize {
+    /// Returns a setting with every part switched on or off.
+    pub fn all(enabled: bool) -> Self {
+        Self {
+            animations: Some(enabled),
+            caret: Some(enabled),
+            scrollbars: Some(enabled),
+            clock: Some(enabled),
+            random: Some(enabled),
+            time: None,
+            seed: None,
+        }
+    }
+
+    /// Returns true if any part is switched on.
+    pub fn is_enabled(&self) -> bool {
+        [
+            self.animations,
+            self.caret,
+            self.scrollbars,
+            self.clock,
+            self.random,
+        ]
+        .iter()
+        .any(|part| part.unwrap_or(false))
+    }
+}
+
+/// Deserializes `stabilize` from either a boolean or an object of parts.
+fn stabilize_option<'de, D>(deserializer: D) -> Result<Option<Stabilize>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StabilizeValue {
+        All(bool),
+        Parts(Stabilize),
+    }
+
+    Ok(Some(match StabilizeValue::deserialize(deserializer)? {
+        StabilizeValue::All(enabled) => Stabilize::all(enabled),
+        StabilizeValue::Parts(parts) => parts,
+    }))
+}
+
+/// JavaScript expression of the app-defined ready signal used by `"signal": true`.
+pub const DEFAULT_READY_SIGNAL: &str = "window.__PIXELGUARD_READY__ === true";
+
+/// Conditions a page must meet before a shot's actions run and it is captured.
+///
+/// In the config, `"ready": true` waits for fonts and images and `false` waits for
+/// nothing. In an object, `fonts` and `images` are enabled unless another level
+/// (config or shot) says otherwise; the ready signal is only awaited when set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Ready {
+    /// Wait for `document.fonts.ready`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fonts: Option<bool>,
+
+    /// Wait until every `<img>` has loaded and decoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<bool>,
+
+    /// Wait until the app signals it is ready: `true` for
+    /// `window.__PIXELGUARD_READY__ === true`, or a JavaScript expression
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<ReadySignal>,
+}
+
+impl Ready {
+    /// Returns conditions with fonts and images switched on or off, and no signal.
+    pub fn all(enabled: bool) -> Self {
+        Self {
+            fonts: Some(enabled),
+            images: Some(enabled),
+            signal: (!enabled).then_some(ReadySignal::Enabled(false)),
+        }
+    }
+
+    /// Returns true if anything is waited for.
+    pub fn is_enabled(&self) -> bool {
+        self.fonts.unwrap_or(false)
+            || self.images.unwrap_or(false)
+            || self.signal_expression().is_some()
+    }
+
+    /// Returns the JavaScript expression of the ready signal, if one is awaited.
+    pub fn signal_expression(&self) -> Option<&str> {
+        match &self.signal {
+            Some(ReadySignal::Enabled(true)) => Some(DEFAULT_READY_SIGNAL),
+            Some(ReadySignal::Expression(expression)) => Some(expression),
+            Some(ReadySignal::Enabled(false)) | None => None,
+        }
+    }
+}
+
+/// The app-defined ready signal: on/off for the default global, or an expression.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ReadySignal {
+    /// `true` waits for `window.__PIXELGUARD_READY__ === true`; `false` waits for nothing
+    Enabled(bool),
+
+    /// JavaScript expression evaluated in the page until it is truthy
+    Expression(String),
+}
+
+/// Deserializes `ready` from either a boolean or an object of conditions.
+fn ready_option<'de, D>(deserializer: D) -> Result<Option<Ready>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ReadyValue {
+        All(bool),
+        Conditions(Ready),
+    }
+
+    Ok(Some(match ReadyValue::deserialize(deserializer)? {
+        ReadyValue::All(enabled) => Ready::all(enabled),
+        ReadyValue::Conditions(conditions) => conditions,
+    }))
+}
+
+/// Default time in milliseconds to wait for a page to load.
+pub const DEFAULT_NAVIGATION_TIMEOUT: u64 = 30_000;
+
+/// Default time in milliseconds to wait for a selector, action or readiness condition.
+pub const DEFAULT_WAIT_TIMEOUT: u64 = 10_000;
+
+/// Capture timeouts in milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Timeouts {
+    /// Time to wait for a page to load (default 30000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub navigation: Option<u64>,
+
+    /// Time to wait for `waitFor`, each action, the `selector` element and each
+    /// readiness condition (default 10000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait: Option<u64>,
+}
+
+impl Timeouts {
+    /// Returns true if no timeout is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns these timeouts with unset values taken from `fallback`, then from
+    /// [`DEFAULT_NAVIGATION_TIMEOUT`] and [`DEFAULT_WAIT_TIMEOUT`].
+    pub fn resolve(&self, fallback: &Timeouts) -> Timeouts {
+        Timeouts {
+            navigation: Some(
+                self.navigation
+                    .or(fallback.navigation)
+                    .unwrap_or(DEFAULT_NAVIGATION_TIMEOUT),
+            ),
+            wait: Some(self.wait.or(fallback.wait).unwrap_or(DEFAULT_WAIT_TIMEOUT)),
+        }
+    }
+}
+
+/// How console errors, uncaught exceptions and failed requests are treated.
+///
+/// Page errors are always collected and reported; `fail` turns them into capture
+/// failures. Messages matching an `allow` pattern are ignored entirely.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PageErrorPolicy {
+    /// Fail shots whose page reports errors (default false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail: Option<bool>,
+
+    /// Regular expressions (JavaScript syntax) of error messages and request URLs
+    /// to ignore
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+}
+
+impl PageErrorPolicy {
+    /// Returns true if neither `fail` nor `allow` is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Debugging files saved for a shot that fails to capture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailureArtifacts {
+    /// Save nothing
+    #[default]
+    Off,
+
+    /// Save the page HTML and a full-page screenshot
+    Page,
+
+    /// Also record a Playwright trace of the shot
+    Trace,
+}
+
+/// How capture gets its browser.
+///
+/// By default each engine in `browsers` is launched locally. Setting `wsEndpoint`
+/// connects to a Playwright browser server instead (`npx playwright run-server`),
+/// and `cdpEndpoint` attaches to a running Chromium over the Chrome DevTools
+/// Protocol, e.g. one in a dedicated container with pinned fonts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BrowserOptions {
+    /// WebSocket endpoint of a Playwright browser server, e.g. `ws://browser:3000/`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ws_endpoint: Option<String>,
+
+    /// Chrome DevTools Protocol endpoint of a running Chromium, e.g. `http://browser:9222`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cdp_endpoint: Option<String>,
+
+    /// Extra command-line arguments for locally launched browsers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub launch_args: Vec<String>,
+
+    /// Browser executable to launch instead of the one bundled with Playwright
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executable_path: Option<String>,
+
+    /// Chromium distribution channel to launch, e.g. `chrome` or `msedge`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+}
+
+impl BrowserOptions {
+    /// Returns true if no option is set, so browsers are launched with defaults.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the endpoint to connect to, if any.
+    pub fn endpoint(&self) -> Option<&str> {
+        self.ws_endpoint.as_deref().or(self.cdp_endpoint.as_deref())
+    }
+
+    /// Checks that the options fit together and with the engines in `browsers`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first problem found.
+    pub fn validate(&self, browsers: &[Browser]) -> Result<()> {
+        if self.ws_endpoint.is_some() && self.cdp_endpoint.is_some() {
+            anyhow::bail!(
+                "❌ `browser` has both `wsEndpoint` and `cdpEndpoint`.\n\n\
+                 💡 Solution: Keep `wsEndpoint` for a Playwright browser server, or \
+                 `cdpEndpoint` for a Chromium DevTools endpoint."
+            );
+        }
+        if let Some(endpoint) = &self.ws_endpoint {
+            if !has_scheme(endpoint, &["ws", "wss"]) {
+                anyhow::bail!(
+                    "❌ `browser.wsEndpoint` must be a ws:// or wss:// URL, got '{}'.\n\n\
+                     💡 Solution: Use the URL printed by `npx playwright run-server`, \
+                     e.g. ws://localhost:3000/",
+                    endpoint
+                );
+            }
+        }
+        if let Some(endpoint) = &self.cdp_endpoint {
+            if !has_scheme(endpoint, &["http", "https", "ws", "wss"]) {
+                anyhow::bail!(
+                    "❌ `browser.cdpEndpoint` must be an http(s):// or ws(s):// URL, got '{}'.\n\n\
+                     💡 Solution: Use the remote debugging address of Chromium, \
+                     e.g. http://localhost:9222",
+                    endpoint
+                );
+            }
+            if browsers.iter().any(|b| *b != Browser::Chromium) {
+                anyhow::bail!(
+                    "❌ `browser.cdpEndpoint` only works with chromium.\n\n\
+                     💡 Solutions:\n  \
+                     • Set `browsers` to [\"chromium\"]\n  \
+                     • Or connect to a Playwright browser server with `wsEndpoint`"
+                );
+            }
+        }
+        if self.endpoint().is_some() {
+            if browsers.len() > 1 {
+                anyhow::bail!(
+                    "❌ A browser endpoint serves a single engine, but `browsers` lists {}.\n\n\
+                     💡 Solution: Keep the one engine the endpoint runs in `browsers`, \
+                     or run one job per engine.",
+                    browsers.len()
+                );
+            }
+            if !self.launch_args.is_empty()
+                || self.executable_path.is_some()
+                || self.channel.is_some()
+            {
+                anyhow::bail!(
+                    "❌ `launchArgs`, `executablePath` and `channel` only apply to launched \
+                     browsers, not to `wsEndpoint`/`cdpEndpoint`.\n\n\
+                     💡 Solution: Remove them, and pass them to the browser server instead."
+                );
+            }
+        }
+        if self.channel.is_some() && browsers.iter().any(|b| *b != Browser::Chromium) {
+            anyhow::bail!(
+                "❌ `browser.channel` only works with chromium.\n\n\
+                 💡 Solutions:\n  \
+                 • Set `browsers` to [\"chromium\"]\n  \
+                 • Or remove `channel` to launch Playwright's own browsers"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The capture backend to use, selected by its `backend` field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "camelCase", deny_unknown_fields)]
+pub enum CaptureBackendConfig {
+    /// Run the generated Playwright script, even if a capture plugin is loaded
+    Playwright,
+
+    /// Run the loaded capture plugin
+    Plugin,
+
+    /// Copy pre-rendered screenshots from `dir`, relative to the config file
+    /// ([`Config::load`] resolves it)
+    Fixtures {
+        /// Directory holding `{screenshot name}.png` files
+        dir: String,
+    },
+}
+
+/// Returns true if `url` starts with one of `schemes` followed by `://`.
+fn has_scheme(url: &str, schemes: &[&str]) -> bool {
+    url.split_once("://")
+        .is_some_and(|(scheme, rest)| !rest.is_empty() && schemes.contains(&scheme))
+}
+
+/// Default time in milliseconds to wait for a `webServer` to respond.
+pub const DEFAULT_WEB_SERVER_TIMEOUT: u64 = 60_000;
+
+/// A dev server started by Pixelguard for the duration of a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WebServer {
+    /// Shell command that starts the server, e.g. `npm run storybook -- --ci`
+    pub command: String,
+
+    /// URL polled until it responds (defaults to `baseUrl`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Milliseconds to wait for the URL to respond before giving up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+
+    /// Use a server that is already running at the URL instead of starting one
+    #[serde(default)]
+    pub reuse_existing: bool,
+}
+
+impl WebServer {
+    /// Returns the URL to poll, falling back to the config's `baseUrl`.
+    pub fn url<'a>(&'a self, base_url: &'a str) -> &'a str {
+        self.url.as_deref().unwrap_or(base_url)
+    }
+
+    /// Returns the startup timeout, falling back to [`DEFAULT_WEB_SERVER_TIMEOUT`].
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.timeout.unwrap_or(DEFAULT_WEB_SERVER_TIMEOUT))
+    }
+}
+
+/// How `--changed-since` maps changed files to shots.
+///
+/// Each shot's `importPath` module is traced through its relative imports (and
+/// imports starting with an alias); a shot is affected when any module it reaches
+/// changed. Rules add mappings the import graph can't see, such as global styles.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Affected {
+    /// Import prefixes mapped to directories relative to the project root, like
+    /// `paths` in tsconfig.json (e.g., `"@/": "src/"`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+
+    /// Extra file-to-shot mappings
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<AffectedRule>,
+}
+
+/// Shots to test whenever a matching file changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AffectedRule {
+    /// Glob patterns of files relative to the project root (e.g., `src/theme/**`)
+    pub files: Vec<String>,
+
+    /// Glob patterns of shot names affected by those files (e.g., `button--*`)
+    pub shots: Vec<String>,
+}
+
+/// Default time in milliseconds `stableCapture` waits for a shot to stop changing.
+pub const DEFAULT_STABLE_CAPTURE_TIMEOUT: u64 = 5000;
+
+/// Default pause in milliseconds between `stableCapture` screenshots.
+pub const DEFAULT_STABLE_CAPTURE_INTERVAL: u64 = 100;
+
+/// Settings for recapturing a shot until consecutive screenshots match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct StableCapture {
+    /// Give up after this many milliseconds and report the shot as unstable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+
+    /// Pause between screenshots in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<u64>,
+}
+
+/// Deserializes `stableCapture` from either a boolean or an object of settings.
+fn stable_capture_option<'de, D>(deserializer: D) -> Result<Option<StableCapture>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StableCaptureValue {
+        Enabled(bool),
+        Settings(StableCapture),
+    }
+
+    Ok(match StableCaptureValue::deserialize(deserializer)? {
+        StableCaptureValue::Enabled(enabled) => enabled.then(StableCapture::default),
+        StableCaptureValue::Settings(settings) => Some(settings),
+    })
+}
+
+/// A rectangular area of a screenshot, in image pixels from the top-left corner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    /// Left edge in pixels
+    pub x: u32,
+
+    /// Top edge in pixels
+    pub y: u32,
+
+    /// Width in pixels
+    pub width: u32,
+
+    /// Height in pixels
+    pub height: u32,
+}
+
+impl Region {
+    /// Returns true if the pixel at (x, y) lies inside this region.
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x
+            && y >= self.y
+            && u64::from(x) < u64::from(self.x) + u64::from(self.width)
+            && u64::from(y) < u64::from(self.y) + u64::from(self.height)
+    }
+}
+
+/// Login setup shared by all shots.
+///
+/// Each role logs in once before capture, either by running `actions` on a login
+/// page or by running a user-provided `script`. The resulting Playwright storage
+/// state is loaded into every browser context of shots that use the role.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Setup {
+    /// Login flows keyed by role name (e.g., "admin", "viewer")
+    pub roles: BTreeMap<String, AuthRole>,
+
+    /// Role used by shots that don't name one. Defaults to the only role when
+    /// exactly one is configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_role: Option<String>,
+}
+
+impl Setup {
+    /// Returns the role used by shots that don't name one, if any.
+    pub fn default_role(&self) -> Option<&str> {
+        match &self.default_role {
+            Some(role) => Some(role),
+            None if self.roles.len() == 1 => self.roles.keys().next().map(String::as_str),
+            None => None,
+        }
+    }
+
+    /// Checks role names, `defaultRole` and each role's login flow.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first problem found.
+    pub fn validate(&self) -> Result<()> {
+        if self.roles.is_empty() {
+            anyhow::bail!(
+                "❌ `setup.roles` is empty.\n\n\
+                 💡 Solution: Define at least one role with login `actions` or a `script`, \
+                 or remove `setup`."
+            );
+        }
+        for (name, role) in &self.roles {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                anyhow::bail!(
+                    "❌ Invalid role name '{}' in `setup.roles`.\n\n\
+                     💡 Solution: Use only letters, digits, '-' and '_' (e.g. \"admin\").",
+                    name
+                );
+            }
+            role.validate(name)?;
+        }
+        if let Some(role) = &self.default_role {
+            if !self.roles.contains_key(role) {
+                anyhow::bail!(
+                    "❌ `setup.defaultRole` names unknown role '{}'.\n\n\
+                     💡 Solution: Use one of the roles in `setup.roles`: {}",
+                    role,
+                    self.roles.keys().cloned().collect::<Vec<_>>().join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How a single auth role logs in.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AuthRole {
+    /// URL path of the login page that `actions` run on (default: "/")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Interaction steps that log in (e.g., fill the form and submit it)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<ShotAction>,
+
+    /// CSS selector that appears once login has succeeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_for: Option<String>,
+
+    /// Node.js module (relative to the config) exporting
+    /// `async (page, { baseUrl, role }) => {}` that logs in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+}
+
+impl AuthRole {
+    /// Checks that the role named `name` has either `actions` or a `script`, but
+    /// not both.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the problem.
+    pub fn validate(&self, name: &str) -> Result<()> {
+        match (self.actions.is_empty(), self.script.is_some()) {
+            (true, false) => anyhow::bail!(
+                "❌ Auth role '{}' has no way to log in.\n\n\
+                 💡 Solutions:\n  \
+                 • Add login `actions` that fill in and submit the login form\n  \
+                 • Or point `script` at a Node.js module that logs in",
+                name
+            ),
+            (false, true) => anyhow::bail!(
+                "❌ Auth role '{}' has both `actions` and `script`.\n\n\
+                 💡 Solution: Keep one of them; a login `script` can run any actions itself.",
+                name
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A shot's choice of auth role.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ShotAuth {
+    /// `true` uses the default role; `false` captures without logging in
+    Enabled(bool),
+
+    /// Name of a role in `setup.roles`
+    Role(String),
+}
+
+/// Placeholder in a route rule's `har` path replaced by the shot name.
+pub const HAR_SHOT_PLACEHOLDER: &str = "{shot}";
+
+/// A network route rule registered with `page.route` before a shot is captured.
+///
+/// A rule matches requests by URL glob (`url`) or regular expression (`urlRegex`)
+/// and either serves a `fixture` file, returns an inline `json` body, aborts the
+/// request, or replays responses from a recorded `har` file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RouteRule {
+    /// URL glob to match (e.g., "**/api/users*")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// JavaScript regular expression to match URLs against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_regex: Option<String>,
+
+    /// Path of a file to serve as the response body (relative to the config)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixture: Option<String>,
+
+    /// Inline JSON response body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json: Option<serde_json::Value>,
+
+    /// HTTP status of a `fixture` or `json` response (default: 200)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+
+    /// Content type of a `fixture` or `json` response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+
+    /// Abort matching requests
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub abort: bool,
+
+    /// HAR file to replay matching requests from (relative to the config;
+    /// `{shot}` is replaced by the shot name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub har: Option<String>,
+}
+
+impl RouteRule {
+    /// Checks that the rule has exactly one URL matcher and exactly one action.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first problem found.
+    pub fn validate(&self) -> Result<()> {
+        let matchers = [self.url.is_some(), self.url_regex.is_some()];
+        match matchers.iter().filter(|m| **m).count() {
+            0 => anyhow::bail!("Route rule needs a `url` glob or a `urlRegex`"),
+            1 => {}
+            _ => anyhow::bail!("Route rule has both `url` and `urlRegex`; use only one"),
+        }
+
+        let actions = [
+            self.fixture.is_some(),
+            self.json.is_some(),
+            self.abort,
+            self.har.is_some(),
+        ];
+        match actions.iter().filter(|a| **a).count() {
+            0 => anyhow::bail!(
+                "Route rule for {} needs one of `fixture`, `json`, `abort` or `har`",
+                self.matcher()
+            ),
+            1 => {}
+            _ => anyhow::bail!(
+                "Route rule for {} has more than one of `fixture`, `json`, `abort` and `har`",
+                self.matcher()
+            ),
+        }
+
+        if (self.abort || self.har.is_some())
+            && (self.status.is_some() || self.content_type.is_some())
+        {
+            anyhow::bail!(
+                "Route rule for {}: `status` and `contentType` only apply to `fixture` and `json`",
+                self.matcher()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns the URL matcher for display in messages.
+    pub fn matcher(&self) -> &str {
+        self.url
+            .as_deref()
+            .or(self.url_regex.as_deref())
+            .unwrap_or("<no url>")
+    }
+}
+
+/// An interaction step performed on the page before a shot is captured.
+///
+/// Actions are declared as objects tagged by `type`, e.g.
+/// `{ "type": "click", "selector": "button" }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ShotAction {
+    /// Click the element matching the selector
+    Click {
+        /// CSS selector of the element to click
+        selector: String,
+    },
+
+    /// Move the mouse over the element matching the selector
+    Hover {
+        /// CSS selector of the element to hover
+        selector: String,
+    },
+
+    /// Fill an input element with a value
+    Fill {
+        /// CSS selector of the input element
+        selector: String,
+        /// Value to type into the element
+        value: String,
+    },
+
+    /// Press a keyboard key, optionally focused on an element
+    Press {
+        /// Key to press (e.g., "Enter", "Tab", "Control+A")
+        key: String,
+        /// Optional CSS selector of the element to focus first
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selector: Option<String>,
+    },
+
+    /// Scroll the page, or an element into view when a selector is given
+    Scroll {
+        /// Optional CSS selector of the element to scroll into view
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selector: Option<String>,
+        /// Horizontal scroll offset in pixels
+        #[serde(default)]
+        x: i64,
+        /// Vertical scroll offset in pixels
+        #[serde(default)]
+        y: i64,
+    },
+
+    /// Wait for an element matching the selector to appear
+    WaitForSelector {
+        /// CSS selector to wait for
+        selector: String,
+    },
+
+    /// Evaluate a JavaScript expression in the page
+    Evaluate {
+        /// JavaScript source to evaluate
+        script: String,
+    },
+}
+
+/// Deserializes either a single value or an array of values into a `Vec`.
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
+fn default_include() -> Vec<String> {
+    vec!["**/*".to_string()]
+}
+
+fn default_threshold() -> f64 {
+    0.01
+}
+
+fn default_output_dir() -> String {
+    ".pixelguard".to_string()
+}
+
+fn default_concurrency() -> usize {
+    4
+}
+
+fn default_reuse_contexts() -> bool {
+    true
+}
+
+fn default_viewport_width() -> u32 {
+    1280
+}
+
+fn default_viewport_height() -> u32 {
+    720
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            width: default_viewport_width(),
+            height: default_viewport_height(),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            source: String::new(),
+            static_dir: None,
+            base_url: String::new(),
+            port: None,
+            web_server: None,
+            include: default_include(),
+            exclude: Vec::new(),
+            viewport: Viewport::default(),
+            viewports: Vec::new(),
+            browsers: Vec::new(),
+            matrix: Vec::new(),
+            color_scheme: Vec::new(),
+            reduced_motion: Vec::new(),
+            locale: Vec::new(),
+            timezone_id: Vec::new(),
+            media: Vec::new(),
+            full_page: false,
+            max_page_height: None,
+            mask: Vec::new(),
+            ignore_regions: Vec::new(),
+            stabilize: None,
+            stable_capture: None,
+            ready: None,
+            timeouts: Timeouts::default(),
+            page_errors: PageErrorPolicy::default(),
+            a11y: false,
+            dom_snapshot: false,
+            failure_artifacts: FailureArtifacts::default(),
+            routes: Vec::new(),
+            setup: None,
+            browser: BrowserOptions::default(),
+            capture: None,
+            affected: None,
+            threshold: default_threshold(),
+            output_dir: default_output_dir(),
+            concurrency: default_concurrency(),
+            reuse_contexts: default_reuse_contexts(),
+            shots: Vec::new(),
+            plugins: Vec::new(),
+            plugin_options: HashMap::new(),
+            shard: None,
+            carried_forward: BTreeSet::new(),
+        }
+    }
+}
+
+/// Default `staticDir` for the `storybook-static` source.
+pub const DEFAULT_STORYBOOK_STATIC_DIR: &str = "storybook-static";
+
+impl Config {
+    /// Creates a new Config with default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if shots are discovered from Storybook, running or static.
+    pub fn is_storybook(&self) -> bool {
+        matches!(self.source.as_str(), "storybook" | "storybook-static")
+    }
+
+    /// Returns the static build directory to serve, if any.
+    ///
+    /// `staticDir` wins; the `storybook-static` source falls back to
+    /// [`DEFAULT_STORYBOOK_STATIC_DIR`].
+    pub fn static_dir(&self) -> Option<&str> {
+        self.static_dir
+            .as_deref()
+            .or_else(|| (self.source == "storybook-static").then_some(DEFAULT_STORYBOOK_STATIC_DIR))
+    }
+
+    /// Loads configuration from a file path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            anyhow::anyhow!(
+                "❌ Could not read config file at '{}'.\n\n\
+                 💡 Solutions:\n  \
+                 1️⃣ Run 'pixelguard init' to create a configuration\n  \
+                 2️⃣ Check file permissions\n  \
+                 3️⃣ Verify the file exists at the expected location\n\n\
+                 🔍 Error details: {}",
+                path.display(),
+                e
+            )
+        })?;
+
+        let mut config: Config = serde_json::from_str(&content).map_err(|e| {
+            anyhow::anyhow!(
+                "❌ Invalid JSON in config file '{}'.\n\n\
+                 🔍 Error details: {}\n\n\
+                 💡 Common issues:\n  \
+                 • Missing commas between properties\n  \
+                 • Trailing commas (not allowed in JSON)\n  \
+                 • Unquoted property names\n  \
+                 • Invalid escape sequences\n\n\
+                 🛠️  Use a JSON validator to check syntax: https://jsonlint.com",
+                path.display(),
+                e
+            )
+        })?;
+
+        // Fixture paths are relative to the config file, wherever it is run from
+        if let (Some(CaptureBackendConfig::Fixtures { dir }), Some(config_dir)) =
+            (&mut config.capture, path.parent())
+        {
+            *dir = config_dir.join(&*dir).to_string_lossy().into_owned();
+        }
+
+        Ok(config)
+    }
+
+    /// Loads configuration from the default location in a directory,
+    /// or returns default configuration if the file doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be parsed.
+    pub fn load_or_default<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let config_path = dir.as_ref().join("pixelguard.config.json");
+
+        if config_path.exists() {
+            Self::load(&config_path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Saves configuration to a file path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Saves configuration to the default location in a directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save_to_dir<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let config_path = dir.as_ref().join("pixelguard.config.json");
+        self.save(&config_path)
+    }
+
+    /// Returns the path to the config file in a directory.
+    pub fn config_path<P: AsRef<Path>>(dir: P) -> std::path::PathBuf {
+        dir.as_ref().join("pixelguard.config.json")
+    }
+
+    /// Checks if a config file exists in the given directory.
+    pub fn exists<P: AsRef<Path>>(dir: P) -> bool {
+        Self::config_path(dir).exists()
+    }
+
+    /// Returns the viewports to use for testing.
+    ///
+    /// If `viewports` is configured, returns those. Otherwise, returns a single
+    /// viewport named "default" based on the legacy `viewport` field.
+    ///
+    /// This provides backward compatibility: configs with only `viewport` work
+    /// as before, while configs with `viewports` enable multi-viewport testing.
+    ///
+    /// Device presets are applied to each configured viewport; fails if a viewport
+    /// names an unknown device or has no size.
+    pub fn effective_viewports(&self) -> Result<Vec<NamedViewport>> {
+        if !self.viewports.is_empty() {
+            self.viewports.iter().map(NamedViewport::resolve).collect()
+        } else {
+            Ok(vec![NamedViewport {
+                name: "default".to_string(),
+                width: self.viewport.width,
+                height: self.viewport.height,
+                ..Default::default()
+            }])
+        }
+    }
+
+    /// Returns the viewports to capture `shot` at: those selected by the shot's
+    /// `viewports` or the first `matrix` rule that sets them (every effective
+    /// viewport otherwise), less those whose combination with the shot belongs to
+    /// another [`Shard`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a viewport name is not configured or a `matrix` pattern
+    /// is invalid.
+    pub fn viewports_for(&self, shot: &Shot) -> Result<Vec<NamedViewport>> {
+        let mut viewports = self.effective_viewports()?;
+        if let Some(selected) = self.select(shot, &shot.viewports, |rule| &rule.viewports)? {
+            for name in selected {
+                if !viewports.iter().any(|viewport| &viewport.name == name) {
+                    anyhow::bail!(
+                        "❌ Shot '{}' selects unknown viewport '{}'.\n\n\
+                         💡 Solutions:\n  \
+                         • Use one of the configured viewports: {}\n  \
+                         • Or add a viewport named '{}' to `viewports` in pixelguard.config.json",
+                        shot.name,
+                        name,
+                        viewports
+                            .iter()
+                            .map(|viewport| viewport.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        name
+                    );
+                }
+            }
+            viewports.retain(|viewport| selected.contains(&viewport.name));
+        }
+        if let Some(shard) = &self.shard {
+            viewports.retain(|viewport| shard.contains(&shot.name, &viewport.name));
+        }
+        Ok(viewports)
+    }
+
+    /// Restricts the run to one shard, dropping shots with no viewport in it.
+    pub fn set_shard(&mut self, shard: Shard) -> Result<()> {
+        self.shard = Some(shard);
+        let mut shots = Vec::new();
+        for shot in std::mem::take(&mut self.shots) {
+            if !self.viewports_for(&shot)?.is_empty() {
+                shots.push(shot);
+            }
+        }
+        self.shots = shots;
+        Ok(())
+    }
+
+    /// Returns the browsers to capture with.
+    ///
+    /// If `browsers` is empty, returns Chromium only.
+    pub fn effective_browsers(&self) -> Vec<Browser> {
+        if !self.browsers.is_empty() {
+            self.browsers.clone()
+        } else {
+            vec![Browser::Chromium]
+        }
+    }
+
+    /// Returns the browsers to capture `shot` in: those selected by the shot's
+    /// `browsers` or the first `matrix` rule that sets them, or every effective
+    /// browser otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a selected browser is not in `browsers` or a `matrix`
+    /// pattern is invalid.
+    pub fn browsers_for(&self, shot: &Shot) -> Result<Vec<Browser>> {
+        let mut browsers = self.effective_browsers();
+        if let Some(selected) = self.select(shot, &shot.browsers, |rule| &rule.browsers)? {
+            if let Some(browser) = selected.iter().find(|b| !browsers.contains(b)) {
+                anyhow::bail!(
+                    "❌ Shot '{}' selects browser '{}', which is not in `browsers`.\n\n\
+                     💡 Solutions:\n  \
+                     • Use one of the configured browsers: {}\n  \
+                     • Or add \"{}\" to `browsers` in pixelguard.config.json",
+                    shot.name,
+                    browser,
+                    browsers
+                        .iter()
+                        .map(Browser::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    browser
+                );
+            }
+            browsers.retain(|browser| selected.contains(browser));
+        }
+        Ok(browsers)
+    }
+
+    /// Returns the selection of one matrix dimension for `shot`: its own list if
+    /// set, else that of the first matching `matrix` rule that sets one.
+    fn select<'a, T>(
+        &'a self,
+        shot: &'a Shot,
+        own: &'a [T],
+        dimension: impl Fn(&'a MatrixRule) -> &'a [T],
+    ) -> Result<Option<&'a [T]>> {
+        if !own.is_empty() {
+            return Ok(Some(own));
+        }
+        for rule in &self.matrix {
+            let selected = dimension(rule);
+            if !selected.is_empty() && rule.matches(&shot.name)? {
+                return Ok(Some(selected));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns true unless the screenshot of `shot_name` at `viewport` (and in
+    /// `browser`, for multi-browser runs) is left out by the shot's `viewports`,
+    /// `browsers` or a `matrix` rule. Shots that are not configured are captured
+    /// in full.
+    pub fn selects(&self, shot_name: &str, viewport: &str, browser: Option<&str>) -> Result<bool> {
+        let Some(shot) = self.shots.iter().find(|s| s.name == shot_name) else {
+            return Ok(true);
+        };
+        if let Some(selected) = self.select(shot, &shot.viewports, |rule| &rule.viewports)? {
+            if !selected.iter().any(|name| name == viewport) {
+                return Ok(false);
+            }
+        }
+        if let (Some(browser), Some(selected)) = (
+            browser,
+            self.select(shot, &shot.browsers, |rule| &rule.browsers)?,
+        ) {
+            if !selected.iter().any(|b| b.as_str() == browser) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns every combination of the configured emulation dimensions.
+    ///
+    /// Dimensions left empty are not emulated. If none are configured, returns a
+    /// single empty `Emulation`, whose suffix is empty.
+    pub fn effective_emulations(&self) -> Vec<Emulation> {
+        let mut emulations = vec![Emulation::default()];
+
+        fn expand<T: Clone>(
+            emulations: Vec<Emulation>,
+            values: &[T],
+            set: impl Fn(&mut Emulation, T),
+        ) -> Vec<Emulation> {
+            if values.is_empty() {
+                return emulations;
+            }
+            let mut expanded = Vec::with_capacity(emulations.len() * values.len());
+            for emulation in emulations {
+                for value in values {
+                    let mut emulation = emulation.clone();
+                    set(&mut emulation, value.clone());
+                    expanded.push(emulation);
+                }
+            }
+            expanded
+        }
+
+        emulations = expand(emulations, &self.color_scheme, |e, v| {
+            e.color_scheme = Some(v)
+        });
+        emulations = expand(emulations, &self.reduced_motion, |e, v| {
+            e.reduced_motion = Some(v)
+        });
+        emulations = expand(emulations, &self.locale, |e, v| e.locale = Some(v));
+        emulations = expand(emulations, &self.timezone_id, |e, v| {
+            e.timezone_id = Some(v)
+        });
+        expand(emulations, &self.media, |e, v| e.media = Some(v))
+    }
+
+    /// Returns the resolved stabilization settings for a shot, or None if nothing
+    /// is stabilized.
+    ///
+    /// Each part is taken from the shot, then the config; parts neither level
+    /// mentions are enabled. The returned settings have every field set.
+    pub fn stabilize_for(&self, shot: &Shot) -> Option<Stabilize> {
+        let levels = [shot.stabilize.as_ref(), self.stabilize.as_ref()];
+        if levels.iter().all(Option::is_none) {
+            return None;
+        }
+
+        let part = |get: fn(&Stabilize) -> Option<bool>| {
+            levels
+                .iter()
+                .flatten()
+                .find_map(|level| get(level))
+                .unwrap_or(true)
+        };
+        let resolved = Stabilize {
+            animations: Some(part(|s| s.animations)),
+            caret: Some(part(|s| s.caret)),
+            scrollbars: Some(part(|s| s.scrollbars)),
+            clock: Some(part(|s| s.clock)),
+            random: Some(part(|s| s.random)),
+            time: Some(
+                levels
+                    .iter()
+                    .flatten()
+                    .find_map(|level| level.time.clone())
+                    .unwrap_or_else(|| DEFAULT_STABILIZE_TIME.to_string()),
+            ),
+            seed: Some(
+                levels
+                    .iter()
+                    .flatten()
+                    .find_map(|level| level.seed)
+                    .unwrap_or(DEFAULT_STABILIZE_SEED),
+            ),
+        };
+
+        resolved.is_enabled().then_some(resolved)
+    }
+
+    /// Returns the resolved readiness conditions for a shot, or None if nothing is
+    /// waited for.
+    ///
+    /// Each condition is taken from the shot's `ready` override if set there,
+    /// otherwise from the config-level `ready`. Fonts and images default to on
+    /// once either level sets `ready`; the signal is resolved to its expression.
+    pub fn ready_for(&self, shot: &Shot) -> Option<Ready> {
+        let levels = [shot.ready.as_ref(), self.ready.as_ref()];
+        if levels.iter().all(Option::is_none) {
+            return None;
+        }
+
+        let part = |get: fn(&Ready) -> Option<bool>| {
+            levels
+                .iter()
+                .flatten()
+                .find_map(|level| get(level))
+                .unwrap_or(true)
+        };
+        let resolved = Ready {
+            fonts: Some(part(|r| r.fonts)),
+            images: Some(part(|r| r.images)),
+            signal: levels
+                .iter()
+                .flatten()
+                .find(|level| level.signal.is_some())
+                .and_then(|level| level.signal_expression())
+                .map(|expression| ReadySignal::Expression(expression.to_string())),
+        };
+
+        resolved.is_enabled().then_some(resolved)
+    }
+
+    /// Returns true if the shot's accessibility tree is saved and compared.
+    pub fn a11y_for(&self, shot: &Shot) -> bool {
+        shot.a11y.unwrap_or(self.a11y)
+    }
+
+    /// Returns true if the shot's DOM and computed styles are saved and compared.
+    pub fn dom_snapshot_for(&self, shot: &Shot) -> bool {
+        shot.dom_snapshot.unwrap_or(self.dom_snapshot)
+    }
+
+    /// Returns the page error policy for a shot: `fail` from the shot if set there,
+    /// else from the config (off by default), and the `allow` patterns of both.
+    pub fn page_errors_for(&self, shot: &Shot) -> PageErrorPolicy {
+        let own = shot.page_errors.as_ref();
+        PageErrorPolicy {
+            fail: Some(
+                own.and_then(|policy| policy.fail)
+                    .or(self.page_errors.fail)
+                    .unwrap_or(false),
+            ),
+            allow: self
+                .page_errors
+                .allow
+                .iter()
+                .chain(own.map(|policy| &policy.allow).into_iter().flatten())
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Returns the timeouts for a shot: the shot's `timeouts`, then the
+    /// config-level `timeouts`, then the defaults.
+    pub fn timeouts_for(&self, shot: &Shot) -> Timeouts {
+        shot.timeouts.unwrap_or_default().resolve(&self.timeouts)
+    }
+
+    /// Returns the route rules for a shot in priority order: the shot's own rules
+    /// first, then the config-level `routes`.
+    pub fn routes_for(&self, shot: &Shot) -> Vec<RouteRule> {
+        shot.routes.iter().chain(&self.routes).cloned().collect()
+    }
+
+    /// Returns the resolved `stableCapture` settings for a shot, or None if the
+    /// shot is captured once.
+    ///
+    /// A shot's `stableCapture: true` enables it with the config settings (or the
+    /// defaults); `false` turns it off. The returned settings have every field set.
+    pub fn stable_capture_for(&self, shot: &Shot) -> Option<StableCapture> {
+        let settings = match (shot.stable_capture, self.stable_capture) {
+            (Some(false), _) | (None, None) => return None,
+            (_, Some(settings)) => settings,
+            (Some(true), None) => StableCapture::default(),
+        };
+        Some(StableCapture {
+            timeout: Some(settings.timeout.unwrap_or(DEFAULT_STABLE_CAPTURE_TIMEOUT)),
+            interval: Some(settings.interval.unwrap_or(DEFAULT_STABLE_CAPTURE_INTERVAL)),
+        })
+    }
+
+    /// Returns the auth role a shot is captured as, or None to capture without
+    /// logging in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the shot names a role that `setup` does not define, or
+    /// asks for the default role when there is none.
+    pub fn auth_role_for(&self, shot: &Shot) -> Result<Option<String>> {
+        let Some(setup) = &self.setup else {
+            if let Some(ShotAuth::Role(role)) = &shot.auth {
+                anyhow::bail!(
+                    "❌ Shot '{}' uses auth role '{}', but no `setup` is configured.\n\n\
+                     💡 Solution: Define the role under `setup.roles` in pixelguard.config.json.",
+                    shot.name,
+                    role
+                );
+            }
+            return Ok(None);
+        };
+
+        match &shot.auth {
+            Some(ShotAuth::Enabled(false)) => Ok(None),
+            Some(ShotAuth::Role(role)) => {
+                if !setup.roles.contains_key(role) {
+                    anyhow::bail!(
+                        "❌ Shot '{}' uses unknown auth role '{}'.\n\n\
+                         💡 Solutions:\n  \
+                         • Use one of the roles in `setup.roles`: {}\n  \
+                         • Or add a role named '{}' to `setup.roles`",
+                        shot.name,
+                        role,
+                        setup.roles.keys().cloned().collect::<Vec<_>>().join(", "),
+                        role
+                    );
+                }
+                Ok(Some(role.clone()))
+            }
+            Some(ShotAuth::Enabled(true)) => match setup.default_role() {
+                Some(role) => Ok(Some(role.to_string())),
+                None => anyhow::bail!(
+                    "❌ Shot '{}' sets `auth: true`, but `setup` has several roles and no \
+                     `defaultRole`.\n\n\
+                     💡 Solutions:\n  \
+                     • Name the role in the shot, e.g. \"auth\": \"admin\"\n  \
+                     • Or set `setup.defaultRole`",
+                    shot.name
+                ),
+            },
+            None => Ok(setup.default_role().map(str::to_string)),
+        }
+    }
+
+    /// Returns the regions to exclude from comparison for a shot.
+    ///
+    /// Combines the config-level `ignoreRegions` with those of the configured shot
+    /// named `shot_name`, if any.
+    pub fn ignore_regions_for(&self, shot_name: &str) -> Vec<Region> {
+        let mut regions = self.ignore_regions.clone();
+        if let Some(shot) = self.shots.iter().find(|s| s.name == shot_name) {
+            regions.extend(shot.ignore_regions.iter().copied());
+        }
+        regions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn default_config_has_sensible_values() {
+        let config = Config::default();
+
+        assert_eq!(config.viewport.width, 1280);
+        assert_eq!(config.viewport.height, 720);
+        assert_eq!(config.threshold, 0.01);
+        assert_eq!(config.output_dir, ".pixelguard");
+        assert_eq!(config.include, vec!["**/*"]);
+    }
+
+    #[test]
+    fn config_serializes_to_json() {
+        let config = Config::default();
+        let json = serde_json::to_string_pretty(&config).unwrap();
+
+        assert!(json.contains("\"viewport\""));
+        assert!(json.contains("\"threshold\""));
+    }
+
+    #[test]
+    fn config_deserializes_from_json() {
+        let json = r#"{
+            "source": "storybook",
+            "baseUrl": "http://localhost:6006",
+            "viewport": {
+                "width": 1920,
+                "height": 1080
+            }
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.source, "storybook");
+        assert_eq!(config.base_url, "http://localhost:6006");
+        assert_eq!(config.viewport.width, 1920);
+        assert_eq!(config.viewport.height, 1080);
+    }
+
+    #[test]
+    fn config_uses_defaults_for_missing_fields() {
+        let json = r#"{"source": "storybook"}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.source, "storybook");
+        assert_eq!(config.viewport.width, 1280);
+        assert_eq!(config.threshold, 0.01);
+    }
+
+    #[test]
+    fn config_saves_and_loads() {
+        let dir = tempdir().unwrap();
+        let config = Config {
+            source: "storybook".to_string(),
+            base_url: "http://localhost:6006".to_string(),
+            ..Default::default()
+        };
+
+        config.save_to_dir(dir.path()).unwrap();
+
+        let loaded = Config::load_or_default(dir.path()).unwrap();
+
+        assert_eq!(loaded.source, "storybook");
+        assert_eq!(loaded.base_url, "http://localhost:6006");
+    }
+
+    #[test]
+    fn load_or_default_returns_default_when_missing() {
+        let dir = tempdir().unwrap();
+        let config = Config::load_or_default(dir.path()).unwrap();
+
+        assert_eq!(config.source, "");
+        assert_eq!(config.viewport.width, 1280);
+    }
+
+    #[test]
+    fn config_exists_returns_false_when_missing() {
+        let dir = tempdir().unwrap();
+        assert!(!Config::exists(dir.path()));
+    }
+
+    #[test]
+    fn config_exists_returns_true_when_present() {
+        let dir = tempdir().unwrap();
         let config = Config::default();
-        let json = serde_json::to_string_pretty(&config).unwrap();
+        config.save_to_dir(dir.path()).unwrap();
+
+        assert!(Config::exists(dir.path()));
+    }
+
+    #[test]
+    fn config_parses_simple_plugin_names() {
+        let json = r#"{
+            "plugins": ["pixelguard-plugin-s3", "pixelguard-plugin-slack"]
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.plugins.len(), 2);
+        assert_eq!(config.plugins[0].name(), "pixelguard-plugin-s3");
+        assert_eq!(config.plugins[1].name(), "pixelguard-plugin-slack");
+    }
+
+    #[test]
+    fn config_parses_plugin_with_options() {
+        let json = r#"{
+            "plugins": [
+                {
+                    "name": "pixelguard-plugin-slack",
+                    "options": {
+                        "webhookUrl": "https://hooks.slack.com/test"
+                    }
+                }
+            ]
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.plugins.len(), 1);
+        assert_eq!(config.plugins[0].name(), "pixelguard-plugin-slack");
+
+        let options = config.plugins[0].options().unwrap();
+        assert_eq!(options["webhookUrl"], "https://hooks.slack.com/test");
+    }
+
+    #[test]
+    fn config_parses_mixed_plugin_entries() {
+        let json = r##"{
+            "plugins": [
+                "pixelguard-plugin-s3",
+                {
+                    "name": "pixelguard-plugin-slack",
+                    "options": { "channel": "#testing" }
+                }
+            ],
+            "pluginOptions": {
+                "pixelguard-plugin-s3": {
+                    "bucket": "my-bucket",
+                    "region": "us-east-1"
+                }
+            }
+        }"##;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.plugins.len(), 2);
+        assert_eq!(config.plugins[0].name(), "pixelguard-plugin-s3");
+        assert!(config.plugins[0].options().is_none());
+
+        assert_eq!(config.plugins[1].name(), "pixelguard-plugin-slack");
+        assert!(config.plugins[1].options().is_some());
+
+        assert!(config.plugin_options.contains_key("pixelguard-plugin-s3"));
+        assert_eq!(
+            config.plugin_options["pixelguard-plugin-s3"]["bucket"],
+            "my-bucket"
+        );
+    }
+
+    #[test]
+    fn effective_viewports_returns_default_when_viewports_empty() {
+        let config = Config {
+            viewport: Viewport {
+                width: 1920,
+                height: 1080,
+            },
+            viewports: Vec::new(),
+            ..Default::default()
+        };
+
+        let viewports = config.effective_viewports().unwrap();
+        assert_eq!(viewports.len(), 1);
+        assert_eq!(viewports[0].name, "default");
+        assert_eq!(viewports[0].width, 1920);
+        assert_eq!(viewports[0].height, 1080);
+    }
+
+    #[test]
+    fn effective_viewports_returns_configured_viewports() {
+        let config = Config {
+            viewports: vec![
+                NamedViewport {
+                    name: "desktop".to_string(),
+                    width: 1920,
+                    height: 1080,
+                    ..Default::default()
+                },
+                NamedViewport {
+                    name: "mobile".to_string(),
+                    width: 375,
+                    height: 667,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let viewports = config.effective_viewports().unwrap();
+        assert_eq!(viewports.len(), 2);
+        assert_eq!(viewports[0].name, "desktop");
+        assert_eq!(viewports[1].name, "mobile");
+    }
+
+    #[test]
+    fn effective_browsers_defaults_to_chromium() {
+        let config = Config::default();
+
+        assert_eq!(config.effective_browsers(), vec![Browser::Chromium]);
+    }
+
+    #[test]
+    fn config_parses_browsers_from_json() {
+        let json = r#"{ "browsers": ["chromium", "firefox", "webkit"] }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.effective_browsers(),
+            vec![Browser::Chromium, Browser::Firefox, Browser::Webkit]
+        );
+        assert_eq!(config.browsers[2].to_string(), "webkit");
+    }
+
+    #[test]
+    fn matrix_rules_and_shot_lists_select_viewports_and_browsers() {
+        let json = r#"{
+            "viewports": [
+                { "name": "mobile", "width": 375, "height": 667 },
+                { "name": "tablet", "width": 768, "height": 1024 },
+                { "name": "desktop", "width": 1280, "height": 720 }
+            ],
+            "browsers": ["chromium", "firefox"],
+            "matrix": [
+                { "include": ["icon--*"], "exclude": ["icon--animated"], "viewports": ["desktop"] },
+                { "browsers": ["chromium"] }
+            ],
+            "shots": [
+                { "name": "icon--star", "path": "/star" },
+                { "name": "icon--animated", "path": "/animated" },
+                { "name": "icon--logo", "path": "/logo", "viewports": ["mobile"], "browsers": ["firefox"] }
+            ]
+        }"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        let names = |shot: &Shot| -> Vec<String> {
+            config
+                .viewports_for(shot)
+                .unwrap()
+                .into_iter()
+                .map(|viewport| viewport.name)
+                .collect()
+        };
+
+        assert_eq!(names(&config.shots[0]), vec!["desktop"]);
+        assert_eq!(names(&config.shots[1]).len(), 3);
+        assert_eq!(names(&config.shots[2]), vec!["mobile"]);
+        assert_eq!(
+            config.browsers_for(&config.shots[0]).unwrap(),
+            vec![Browser::Chromium]
+        );
+        assert_eq!(
+            config.browsers_for(&config.shots[2]).unwrap(),
+            vec![Browser::Firefox]
+        );
+
+        assert!(config
+            .selects("icon--star", "desktop", Some("chromium"))
+            .unwrap());
+        assert!(!config
+            .selects("icon--star", "mobile", Some("chromium"))
+            .unwrap());
+        assert!(!config
+            .selects("icon--star", "desktop", Some("firefox"))
+            .unwrap());
+        assert!(config
+            .selects("removed--story", "mobile", Some("webkit"))
+            .unwrap());
+    }
+
+    #[test]
+    fn shot_selection_rejects_unconfigured_viewport_or_browser() {
+        let shot = Shot {
+            name: "icon".to_string(),
+            viewports: vec!["mobile".to_string()],
+            browsers: vec![Browser::Webkit],
+            ..Default::default()
+        };
+        let config = Config::default();
+
+        let err = config.viewports_for(&shot).unwrap_err().to_string();
+        assert!(err.starts_with("❌ Shot 'icon' selects unknown viewport 'mobile'"));
+        assert!(err.contains("configured viewports: default\n"));
+        let err = config.browsers_for(&shot).unwrap_err().to_string();
+        assert!(err.starts_with("❌ Shot 'icon' selects browser 'webkit'"));
+        assert!(err.contains("configured browsers: chromium\n"));
+    }
+
+    #[test]
+    fn config_rejects_unknown_browser() {
+        let json = r#"{ "browsers": ["safari"] }"#;
+
+        assert!(serde_json::from_str::<Config>(json).is_err());
+    }
+
+    #[test]
+    fn effective_viewports_applies_device_preset() {
+        let json = r#"{
+            "viewports": [
+                { "name": "phone", "device": "iphone 13" },
+                { "name": "retina", "device": "Desktop HiDPI", "width": 1440, "hasTouch": true }
+            ]
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        let viewports = config.effective_viewports().unwrap();
+
+        assert_eq!(viewports[0].width, 390);
+        assert_eq!(viewports[0].height, 664);
+        assert_eq!(viewports[0].device_scale_factor, Some(3.0));
+        assert_eq!(viewports[0].is_mobile, Some(true));
+        assert!(viewports[0]
+            .user_agent
+            .as_deref()
+            .unwrap()
+            .contains("iPhone"));
+
+        assert_eq!(viewports[1].width, 1440);
+        assert_eq!(viewports[1].height, 720);
+        assert_eq!(viewports[1].has_touch, Some(true));
+        assert_eq!(viewports[1].user_agent, None);
+    }
+
+    #[test]
+    fn effective_viewports_rejects_unknown_device() {
+        let config = Config {
+            viewports: vec![NamedViewport {
+                name: "phone".to_string(),
+                device: Some("Nokia 3310".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let err = config.effective_viewports().unwrap_err().to_string();
+        assert!(err.contains("Unknown device 'Nokia 3310'"));
+        assert!(err.contains("iPhone 13"));
+    }
+
+    #[test]
+    fn effective_viewports_rejects_viewport_without_size() {
+        let config = Config {
+            viewports: vec![NamedViewport {
+                name: "empty".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert!(config.effective_viewports().is_err());
+    }
+
+    #[test]
+    fn effective_emulations_is_single_empty_combination_by_default() {
+        let emulations = Config::default().effective_emulations();
+
+        assert_eq!(emulations, vec![Emulation::default()]);
+        assert_eq!(emulations[0].suffix(), "");
+    }
+
+    #[test]
+    fn effective_emulations_expands_matrix_in_fixed_order() {
+        let json = r#"{
+            "colorScheme": ["light", "dark"],
+            "locale": ["en-US", "ar-SA"],
+            "timezoneId": "Etc/GMT+5",
+            "media": "print"
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        let emulations = config.effective_emulations();
+        let suffixes: Vec<_> = emulations.iter().map(Emulation::suffix).collect();
+
+        assert_eq!(
+            suffixes,
+            vec![
+                "+scheme-light+locale-en-US+tz-Etc-GMTplus5+media-print",
+                "+scheme-light+locale-ar-SA+tz-Etc-GMTplus5+media-print",
+                "+scheme-dark+locale-en-US+tz-Etc-GMTplus5+media-print",
+                "+scheme-dark+locale-ar-SA+tz-Etc-GMTplus5+media-print",
+            ]
+        );
+        assert_eq!(emulations[3].color_scheme, Some(ColorScheme::Dark));
+        assert_eq!(emulations[3].timezone_id.as_deref(), Some("Etc/GMT+5"));
+        assert_eq!(emulations[3].reduced_motion, None);
+    }
+
+    #[test]
+    fn config_parses_reduced_motion_values() {
+        let json = r#"{ "reducedMotion": ["reduce", "no-preference"] }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.reduced_motion,
+            vec![ReducedMotion::Reduce, ReducedMotion::NoPreference]
+        );
+        assert_eq!(
+            config.effective_emulations()[1].suffix(),
+            "+motion-no-preference"
+        );
+    }
+
+    #[test]
+    fn stabilize_true_enables_every_part_with_defaults() {
+        let json = r#"{ "stabilize": true, "shots": [{ "name": "a", "path": "/" }] }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        let resolved = config.stabilize_for(&config.shots[0]).unwrap();
+
+        assert_eq!(resolved.animations, Some(true));
+        assert_eq!(resolved.caret, Some(true));
+        assert_eq!(resolved.scrollbars, Some(true));
+        assert_eq!(resolved.clock, Some(true));
+        assert_eq!(resolved.random, Some(true));
+        assert_eq!(resolved.time.as_deref(), Some(DEFAULT_STABILIZE_TIME));
+        assert_eq!(resolved.seed, Some(DEFAULT_STABILIZE_SEED));
+    }
+
+    #[test]
+    fn stabilize_is_off_by_default() {
+        let config = Config::default();
+
+        assert_eq!(config.stabilize_for(&Shot::default()), None);
+    }
+
+    #[test]
+    fn shot_stabilize_overrides_config_parts() {
+        let json = r#"{
+            "stabilize": { "clock": false, "time": "2030-06-01T12:00:00Z" },
+            "shots": [
+                { "name": "a", "path": "/", "stabilize": { "random": false, "clock": true } },
+                { "name": "b", "path": "/", "stabilize": false },
+                { "name": "c", "path": "/" }
+            ]
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        let a = config.stabilize_for(&config.shots[0]).unwrap();
+        assert_eq!(a.clock, Some(true));
+        assert_eq!(a.random, Some(false));
+        assert_eq!(a.animations, Some(true));
+        assert_eq!(a.time.as_deref(), Some("2030-06-01T12:00:00Z"));
+
+        assert_eq!(config.stabilize_for(&config.shots[1]), None);
+
+        let c = config.stabilize_for(&config.shots[2]).unwrap();
+        assert_eq!(c.clock, Some(false));
+        assert_eq!(c.caret, Some(true));
+    }
+
+    #[test]
+    fn stabilize_rejects_unknown_parts() {
+        let json = r#"{ "stabilize": { "animation": false } }"#;
+
+        assert!(serde_json::from_str::<Config>(json).is_err());
+    }
+
+    #[test]
+    fn shot_ready_overrides_config_conditions() {
+        let json = r#"{
+            "ready": { "images": false, "signal": true },
+            "shots": [
+                { "name": "a", "path": "/" },
+                { "name": "b", "path": "/", "ready": { "images": true, "signal": "window.chartDrawn" } },
+                { "name": "c", "path": "/", "ready": false }
+            ]
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        let a = config.ready_for(&config.shots[0]).unwrap();
+        assert_eq!(a.fonts, Some(true));
+        assert_eq!(a.images, Some(false));
+        assert_eq!(a.signal_expression(), Some(DEFAULT_READY_SIGNAL));
+
+        let b = config.ready_for(&config.shots[1]).unwrap();
+        assert_eq!(b.images, Some(true));
+        assert_eq!(b.signal_expression(), Some("window.chartDrawn"));
+
+        assert_eq!(config.ready_for(&config.shots[2]), None);
+        assert_eq!(Config::default().ready_for(&config.shots[0]), None);
+    }
+
+    #[test]
+    fn ready_true_waits_for_fonts_and_images_only() {
+        let json = r#"{ "ready": true, "shots": [{ "name": "a", "path": "/" }] }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        let resolved = config.ready_for(&config.shots[0]).unwrap();
 
-        assert!(json.contains("\"viewport\""));
-        assert!(json.contains("\"threshold\""));
+        assert_eq!(resolved.fonts, Some(true));
+        assert_eq!(resolved.images, Some(true));
+        assert_eq!(resolved.signal, None);
     }
 
     #[test]
-    fn config_deserializes_from_json() {
+    fn timeouts_fall_back_to_config_then_defaults() {
         let json = r#"{
-            "source": "storybook",
-            "baseUrl": "http://localhost:6006",
-            "viewport": {
-                "width": 1920,
-                "height": 1080
-            }
+            "timeouts": { "navigation": 60000 },
+            "shots": [
+                { "name": "a", "path": "/" },
+                { "name": "b", "path": "/", "timeouts": { "wait": 2000 } }
+            ]
         }"#;
 
         let config: Config = serde_json::from_str(json).unwrap();
 
-        assert_eq!(config.source, "storybook");
-        assert_eq!(config.base_url, "http://localhost:6006");
-        assert_eq!(config.viewport.width, 1920);
-        assert_eq!(config.viewport.height, 1080);
+        assert_eq!(
+            config.timeouts_for(&config.shots[0]),
+            Timeouts {
+                navigation: Some(60000),
+                wait: Some(DEFAULT_WAIT_TIMEOUT)
+            }
+        );
+        assert_eq!(
+            config.timeouts_for(&config.shots[1]),
+            Timeouts {
+                navigation: Some(60000),
+                wait: Some(2000)
+            }
+        );
+        assert!(serde_json::from_str::<Config>(r#"{ "timeouts": { "load": 1 } }"#).is_err());
     }
 
     #[test]
-    fn config_uses_defaults_for_missing_fields() {
-        let json = r#"{"source": "storybook"}"#;
+    fn page_errors_combine_allow_patterns_of_config_and_shot() {
+        let json = r#"{
+            "pageErrors": { "fail": true, "allow": ["favicon\\.ico"] },
+            "shots": [
+                { "name": "a", "path": "/" },
+                { "name": "b", "path": "/", "pageErrors": { "fail": false, "allow": ["ResizeObserver"] } }
+            ]
+        }"#;
+
         let config: Config = serde_json::from_str(json).unwrap();
 
-        assert_eq!(config.source, "storybook");
-        assert_eq!(config.viewport.width, 1280);
-        assert_eq!(config.threshold, 0.01);
+        assert_eq!(
+            config.page_errors_for(&config.shots[0]),
+            PageErrorPolicy {
+                fail: Some(true),
+                allow: vec!["favicon\\.ico".to_string()]
+            }
+        );
+        assert_eq!(
+            config.page_errors_for(&config.shots[1]),
+            PageErrorPolicy {
+                fail: Some(false),
+                allow: vec!["favicon\\.ico".to_string(), "ResizeObserver".to_string()]
+            }
+        );
+        assert_eq!(
+            Config::default().page_errors_for(&config.shots[0]).fail,
+            Some(false)
+        );
+        assert!(serde_json::from_str::<Config>(r#"{ "pageErrors": { "ignore": [] } }"#).is_err());
     }
 
     #[test]
-    fn config_saves_and_loads() {
-        let dir = tempdir().unwrap();
-        let config = Config {
-            source: "storybook".to_string(),
-            base_url: "http://localhost:6006".to_string(),
-            ..Default::default()
-        };
-
-        config.save_to_dir(dir.path()).unwrap();
+    fn stable_capture_resolves_per_shot() {
+        let json = r#"{
+            "stableCapture": { "timeout": 2000 },
+            "shots": [
+                { "name": "chart", "path": "/chart" },
+                { "name": "static", "path": "/static", "stableCapture": false }
+            ]
+        }"#;
 
-        let loaded = Config::load_or_default(dir.path()).unwrap();
+        let config: Config = serde_json::from_str(json).unwrap();
 
-        assert_eq!(loaded.source, "storybook");
-        assert_eq!(loaded.base_url, "http://localhost:6006");
+        assert_eq!(
+            config.stable_capture_for(&config.shots[0]),
+            Some(StableCapture {
+                timeout: Some(2000),
+                interval: Some(DEFAULT_STABLE_CAPTURE_INTERVAL),
+            })
+        );
+        assert_eq!(config.stable_capture_for(&config.shots[1]), None);
     }
 
     #[test]
-    fn load_or_default_returns_default_when_missing() {
-        let dir = tempdir().unwrap();
-        let config = Config::load_or_default(dir.path()).unwrap();
+    fn stable_capture_accepts_booleans() {
+        let off: Config = serde_json::from_str(r#"{ "stableCapture": false }"#).unwrap();
+        assert_eq!(off.stable_capture, None);
 
-        assert_eq!(config.source, "");
-        assert_eq!(config.viewport.width, 1280);
+        let shot = Shot {
+            name: "chart".to_string(),
+            stable_capture: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            off.stable_capture_for(&shot).unwrap().timeout,
+            Some(DEFAULT_STABLE_CAPTURE_TIMEOUT)
+        );
     }
 
     #[test]
-    fn config_exists_returns_false_when_missing() {
-        let dir = tempdir().unwrap();
-        assert!(!Config::exists(dir.path()));
+    fn static_dir_defaults_for_storybook_static_source() {
+        let config: Config = serde_json::from_str(r#"{ "source": "storybook-static" }"#).unwrap();
+        assert!(config.is_storybook());
+        assert_eq!(config.static_dir(), Some(DEFAULT_STORYBOOK_STATIC_DIR));
+
+        let config: Config =
+            serde_json::from_str(r#"{ "source": "storybook", "staticDir": "dist/storybook" }"#)
+                .unwrap();
+        assert!(config.is_storybook());
+        assert_eq!(config.static_dir(), Some("dist/storybook"));
+
+        let config: Config = serde_json::from_str(r#"{ "source": "storybook" }"#).unwrap();
+        assert_eq!(config.static_dir(), None);
     }
 
     #[test]
-    fn config_exists_returns_true_when_present() {
-        let dir = tempdir().unwrap();
-        let config = Config::default();
-        config.save_to_dir(dir.path()).unwrap();
+    fn web_server_defaults_to_base_url_and_timeout() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "baseUrl": "http://localhost:6006",
+                "webServer": { "command": "npm run storybook -- --ci" }
+            }"#,
+        )
+        .unwrap();
+        let server = config.web_server.as_ref().unwrap();
+
+        assert_eq!(server.url(&config.base_url), "http://localhost:6006");
+        assert_eq!(
+            server.timeout(),
+            std::time::Duration::from_millis(DEFAULT_WEB_SERVER_TIMEOUT)
+        );
+        assert!(!server.reuse_existing);
 
-        assert!(Config::exists(dir.path()));
+        let custom: WebServer = serde_json::from_str(
+            r#"{ "command": "npm start", "url": "http://localhost:3000/health", "timeout": 120000, "reuseExisting": true }"#,
+        )
+        .unwrap();
+        assert_eq!(custom.url(&config.base_url), "http://localhost:3000/health");
+        assert_eq!(custom.timeout(), std::time::Duration::from_secs(120));
+        assert!(custom.reuse_existing);
+
+        assert!(serde_json::from_str::<WebServer>(r#"{ "command": "x", "port": 1 }"#).is_err());
     }
 
     #[test]
-    fn config_parses_simple_plugin_names() {
+    fn routes_parse_and_validate() {
         let json = r#"{
-            "plugins": ["pixelguard-plugin-s3", "pixelguard-plugin-slack"]
+            "routes": [
+                { "url": "**/api/users", "fixture": "fixtures/users.json" },
+                { "urlRegex": "analytics", "abort": true },
+                { "url": "**/api/**", "har": "hars/{shot}.har" }
+            ],
+            "shots": [{
+                "name": "empty",
+                "path": "/",
+                "routes": [{ "url": "**/api/users", "json": [], "status": 200 }]
+            }]
         }"#;
 
         let config: Config = serde_json::from_str(json).unwrap();
 
-        assert_eq!(config.plugins.len(), 2);
-        assert_eq!(config.plugins[0].name(), "pixelguard-plugin-s3");
-        assert_eq!(config.plugins[1].name(), "pixelguard-plugin-slack");
+        assert_eq!(config.routes.len(), 3);
+        assert!(config.routes[1].abort);
+        for rule in &config.routes {
+            rule.validate().unwrap();
+        }
+
+        let routes = config.routes_for(&config.shots[0]);
+        assert_eq!(routes.len(), 4);
+        assert_eq!(routes[0].json, Some(serde_json::json!([])));
+        assert_eq!(routes[1].fixture.as_deref(), Some("fixtures/users.json"));
     }
 
     #[test]
-    fn config_parses_plugin_with_options() {
-        let json = r#"{
-            "plugins": [
-                {
-                    "name": "pixelguard-plugin-slack",
-                    "options": {
-                        "webhookUrl": "https://hooks.slack.com/test"
-                    }
-                }
-            ]
-        }"#;
+    fn route_rule_validate_rejects_ambiguous_rules() {
+        let no_action = RouteRule {
+            url: Some("**/api".to_string()),
+            ..Default::default()
+        };
+        assert!(no_action.validate().is_err());
 
-        let config: Config = serde_json::from_str(json).unwrap();
+        let no_matcher = RouteRule {
+            abort: true,
+            ..Default::default()
+        };
+        assert!(no_matcher.validate().is_err());
 
-        assert_eq!(config.plugins.len(), 1);
-        assert_eq!(config.plugins[0].name(), "pixelguard-plugin-slack");
+        let two_actions = RouteRule {
+            url: Some("**/api".to_string()),
+            abort: true,
+            json: Some(serde_json::json!({})),
+            ..Default::default()
+        };
+        assert!(two_actions.validate().is_err());
 
-        let options = config.plugins[0].options().unwrap();
-        assert_eq!(options["webhookUrl"], "https://hooks.slack.com/test");
+        let status_on_abort = RouteRule {
+            url_regex: Some("api".to_string()),
+            abort: true,
+            status: Some(500),
+            ..Default::default()
+        };
+        assert!(status_on_abort.validate().is_err());
     }
 
     #[test]
-    fn config_parses_mixed_plugin_entries() {
+    fn auth_roles_resolve_per_shot() {
         let json = r##"{
-            "plugins": [
-                "pixelguard-plugin-s3",
-                {
-                    "name": "pixelguard-plugin-slack",
-                    "options": { "channel": "#testing" }
-                }
-            ],
-            "pluginOptions": {
-                "pixelguard-plugin-s3": {
-                    "bucket": "my-bucket",
-                    "region": "us-east-1"
-                }
-            }
+            "setup": {
+                "roles": {
+                    "admin": {
+                        "path": "/login",
+                        "actions": [
+                            { "type": "fill", "selector": "#user", "value": "admin" },
+                            { "type": "click", "selector": "button[type=submit]" }
+                        ],
+                        "waitFor": ".dashboard"
+                    },
+                    "viewer": { "script": "scripts/login-viewer.js" }
+                },
+                "defaultRole": "admin"
+            },
+            "shots": [
+                { "name": "dashboard", "path": "/" },
+                { "name": "report", "path": "/report", "auth": "viewer" },
+                { "name": "login", "path": "/login", "auth": false }
+            ]
         }"##;
 
         let config: Config = serde_json::from_str(json).unwrap();
+        config.setup.as_ref().unwrap().validate().unwrap();
 
-        assert_eq!(config.plugins.len(), 2);
-        assert_eq!(config.plugins[0].name(), "pixelguard-plugin-s3");
-        assert!(config.plugins[0].options().is_none());
-
-        assert_eq!(config.plugins[1].name(), "pixelguard-plugin-slack");
-        assert!(config.plugins[1].options().is_some());
-
-        assert!(config.plugin_options.contains_key("pixelguard-plugin-s3"));
+        let roles: Vec<_> = config
+            .shots
+            .iter()
+            .map(|shot| config.auth_role_for(shot).unwrap())
+            .collect();
         assert_eq!(
-            config.plugin_options["pixelguard-plugin-s3"]["bucket"],
-            "my-bucket"
+            roles,
+            vec![Some("admin".to_string()), Some("viewer".to_string()), None]
         );
     }
 
     #[test]
-    fn effective_viewports_returns_default_when_viewports_empty() {
+    fn auth_single_role_is_default() {
+        let setup = Setup {
+            roles: BTreeMap::from([(
+                "user".to_string(),
+                AuthRole {
+                    script: Some("login.js".to_string()),
+                    ..Default::default()
+                },
+            )]),
+            default_role: None,
+        };
+        assert_eq!(setup.default_role(), Some("user"));
+
         let config = Config {
-            viewport: Viewport {
-                width: 1920,
-                height: 1080,
-            },
-            viewports: Vec::new(),
+            setup: Some(setup),
             ..Default::default()
         };
-
-        let viewports = config.effective_viewports();
-        assert_eq!(viewports.len(), 1);
-        assert_eq!(viewports[0].name, "default");
-        assert_eq!(viewports[0].width, 1920);
-        assert_eq!(viewports[0].height, 1080);
+        let shot = Shot {
+            name: "home".to_string(),
+            auth: Some(ShotAuth::Role("admin".to_string())),
+            ..Default::default()
+        };
+        assert!(config.auth_role_for(&shot).is_err());
     }
 
     #[test]
-    fn effective_viewports_returns_configured_viewports() {
-        let config = Config {
-            viewports: vec![
-                NamedViewport {
-                    name: "desktop".to_string(),
-                    width: 1920,
-                    height: 1080,
-                },
-                NamedViewport {
-                    name: "mobile".to_string(),
-                    width: 375,
-                    height: 667,
+    fn setup_validate_rejects_bad_roles() {
+        let both = AuthRole {
+            actions: vec![ShotAction::Click {
+                selector: "button".to_string(),
+            }],
+            script: Some("login.js".to_string()),
+            ..Default::default()
+        };
+        let err = both.validate("admin").unwrap_err().to_string();
+        assert!(err.starts_with("❌ Auth role 'admin' has both `actions` and `script`."));
+        assert!(err.contains("💡 Solution"));
+        assert!(AuthRole::default().validate("admin").is_err());
+
+        let bad_name = Setup {
+            roles: BTreeMap::from([(
+                "../admin".to_string(),
+                AuthRole {
+                    script: Some("login.js".to_string()),
+                    ..Default::default()
                 },
-            ],
+            )]),
+            default_role: None,
+        };
+        assert!(bad_name.validate().is_err());
+    }
+
+    #[test]
+    fn browser_options_parse_and_validate() {
+        let config: Config =
+            serde_json::from_str(r#"{ "browser": { "cdpEndpoint": "http://browser:9222" } }"#)
+                .unwrap();
+        assert_eq!(config.browser.endpoint(), Some("http://browser:9222"));
+        assert!(config.browser.validate(&[]).is_ok());
+        assert!(config.browser.validate(&[Browser::Firefox]).is_err());
+
+        let ws = BrowserOptions {
+            ws_endpoint: Some("ws://browser:3000/".to_string()),
             ..Default::default()
         };
+        assert!(ws.validate(&[Browser::Webkit]).is_ok());
+        assert!(ws.validate(&[Browser::Chromium, Browser::Firefox]).is_err());
 
-        let viewports = config.effective_viewports();
-        assert_eq!(viewports.len(), 2);
-        assert_eq!(viewports[0].name, "desktop");
-        assert_eq!(viewports[1].name, "mobile");
+        let both = BrowserOptions {
+            cdp_endpoint: Some("http://browser:9222".to_string()),
+            ..ws.clone()
+        };
+        let err = both.validate(&[]).unwrap_err().to_string();
+        assert!(err.starts_with("❌ `browser` has both `wsEndpoint` and `cdpEndpoint`."));
+        assert!(err.contains("💡 Solution"));
+
+        let with_args = BrowserOptions {
+            launch_args: vec!["--font-render-hinting=none".to_string()],
+            ..ws
+        };
+        assert!(with_args.validate(&[]).is_err());
+
+        let bad_scheme = BrowserOptions {
+            ws_endpoint: Some("http://browser:3000".to_string()),
+            ..Default::default()
+        };
+        assert!(bad_scheme.validate(&[]).is_err());
+
+        let launch: BrowserOptions = serde_json::from_str(
+            r#"{ "launchArgs": ["--disable-gpu"], "channel": "chrome", "executablePath": "/usr/bin/chrome" }"#,
+        )
+        .unwrap();
+        assert!(launch.endpoint().is_none());
+        assert!(launch.validate(&[Browser::Chromium]).is_ok());
+        assert!(launch.validate(&[Browser::Webkit]).is_err());
+        assert!(BrowserOptions::default().is_empty());
+    }
+
+    #[test]
+    fn load_resolves_fixtures_dir_against_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_dir = dir.path().join("config");
+        std::fs::create_dir(&config_dir).unwrap();
+        let path = config_dir.join("pixelguard.config.json");
+        std::fs::write(
+            &path,
+            r#"{ "capture": { "backend": "fixtures", "dir": "screenshots" } }"#,
+        )
+        .unwrap();
+
+        let config = Config::load(&path).unwrap();
+
+        assert_eq!(
+            config.capture,
+            Some(CaptureBackendConfig::Fixtures {
+                dir: config_dir
+                    .join("screenshots")
+                    .to_string_lossy()
+                    .into_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn capture_backend_parses_from_json() {
+        let config: Config = serde_json::from_str(
+            r#"{ "capture": { "backend": "fixtures", "dir": "tests/screenshots" } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.capture,
+            Some(CaptureBackendConfig::Fixtures {
+                dir: "tests/screenshots".to_string()
+            })
+        );
+
+        let config: Config =
+            serde_json::from_str(r#"{ "capture": { "backend": "plugin" } }"#).unwrap();
+        assert_eq!(config.capture, Some(CaptureBackendConfig::Plugin));
+        assert!(Config::default().capture.is_none());
+        assert!(
+            serde_json::from_str::<Config>(r#"{ "capture": { "backend": "fixtures" } }"#).is_err()
+        );
+        assert!(
+            serde_json::from_str::<Config>(r#"{ "capture": { "backend": "chrome" } }"#).is_err()
+        );
     }
 
     #[test]
@@ -542,4 +3171,143 @@ mod tests {
         assert_eq!(config.viewports[1].name, "tablet");
         assert_eq!(config.viewports[2].name, "mobile");
     }
+
+    #[test]
+    fn config_parses_full_page_at_every_level() {
+        let json = r#"{
+            "fullPage": true,
+            "maxPageHeight": 10000,
+            "viewports": [
+                { "name": "desktop", "width": 1920, "height": 1080 },
+                { "name": "mobile", "width": 375, "height": 667, "fullPage": false }
+            ],
+            "shots": [
+                { "name": "home", "path": "/", "fullPage": false }
+            ]
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        assert!(config.full_page);
+        assert_eq!(config.max_page_height, Some(10000));
+        assert_eq!(config.viewports[0].full_page, None);
+        assert_eq!(config.viewports[1].full_page, Some(false));
+        assert_eq!(config.shots[0].full_page, Some(false));
+    }
+
+    #[test]
+    fn config_parses_mask_and_ignore_regions() {
+        let json = r#"{
+            "mask": [".timestamp"],
+            "ignoreRegions": [{ "x": 0, "y": 0, "width": 100, "height": 20 }],
+            "shots": [
+                {
+                    "name": "feed",
+                    "path": "/feed",
+                    "mask": [".avatar"],
+                    "ignoreRegions": [{ "x": 10, "y": 300, "width": 728, "height": 90 }]
+                }
+            ]
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.mask, vec![".timestamp"]);
+        assert_eq!(config.shots[0].mask, vec![".avatar"]);
+
+        let regions = config.ignore_regions_for("feed");
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[1].y, 300);
+
+        assert_eq!(config.ignore_regions_for("other").len(), 1);
+    }
+
+    #[test]
+    fn region_contains_checks_bounds() {
+        let region = Region {
+            x: 10,
+            y: 20,
+            width: 5,
+            height: 5,
+        };
+
+        assert!(region.contains(10, 20));
+        assert!(region.contains(14, 24));
+        assert!(!region.contains(15, 24));
+        assert!(!region.contains(9, 20));
+    }
+
+    #[test]
+    fn shot_parses_actions_from_json() {
+        let json = r##"{
+            "name": "menu--open",
+            "path": "/menu",
+            "actions": [
+                { "type": "click", "selector": "#toggle" },
+                { "type": "fill", "selector": "input", "value": "hello" },
+                { "type": "press", "key": "Enter" },
+                { "type": "scroll", "y": 400 },
+                { "type": "waitForSelector", "selector": ".menu" },
+                { "type": "evaluate", "script": "window.ready = true" }
+            ]
+        }"##;
+
+        let shot: Shot = serde_json::from_str(json).unwrap();
+
+        assert_eq!(shot.actions.len(), 6);
+        assert_eq!(
+            shot.actions[0],
+            ShotAction::Click {
+                selector: "#toggle".to_string()
+            }
+        );
+        assert_eq!(
+            shot.actions[2],
+            ShotAction::Press {
+                key: "Enter".to_string(),
+                selector: None
+            }
+        );
+        assert_eq!(
+            shot.actions[3],
+            ShotAction::Scroll {
+                selector: None,
+                x: 0,
+                y: 400
+            }
+        );
+        assert_eq!(
+            shot.actions[4],
+            ShotAction::WaitForSelector {
+                selector: ".menu".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn shot_without_actions_omits_field_when_serialized() {
+        let shot = Shot {
+            name: "home".to_string(),
+            path: "/".to_string(),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&shot).unwrap();
+        assert!(!json.contains("actions"));
+    }
+
+    #[test]
+    fn shot_parses_selector_and_padding() {
+        let json = r##"{
+            "name": "button--primary",
+            "path": "/iframe.html?id=button--primary",
+            "selector": "#storybook-root > *",
+            "padding": 8
+        }"##;
+
+        let shot: Shot = serde_json::from_str(json).unwrap();
+
+        assert_eq!(shot.selector.as_deref(), Some("#storybook-root > *"));
+        assert_eq!(shot.padding, Some(8));
+    }
 }
@@ -10,7 +10,7 @@
 use std::path::Path;
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::Deserialize;
 use tracing::{debug, info};
 
@@ -50,15 +50,54 @@ struct StorybookStories {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct StorybookEntry {
     id: String,
     #[serde(rename = "type")]
     entry_type: Option<String>,
+    import_path: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct StorybookStory {
     id: String,
+    import_path: Option<String>,
+}
+
+impl StorybookIndex {
+    /// Converts story entries (skipping docs pages) into shots.
+    fn into_shots(self) -> Vec<Shot> {
+        self.entries
+            .unwrap_or_default()
+            .into_values()
+            .filter(|entry| entry.entry_type.as_deref() != Some("docs"))
+            .map(|entry| story_shot(entry.id, entry.import_path))
+            .collect()
+    }
+}
+
+impl StorybookStories {
+    /// Converts stories into shots.
+    fn into_shots(self) -> Vec<Shot> {
+        self.stories
+            .unwrap_or_default()
+            .into_values()
+            .map(|story| story_shot(story.id, story.import_path))
+            .collect()
+    }
+}
+
+/// Builds the shot that captures a story in Storybook's iframe.
+fn story_shot(id: String, import_path: Option<String>) -> Shot {
+    Shot {
+        path: format!("/iframe.html?id={}&viewMode=story", id),
+        name: id,
+        wait_for: Some("#storybook-root".to_string()),
+        delay: Some(100),
+        import_path,
+        ..Default::default()
+    }
 }
 
 impl ProjectType {
@@ -182,21 +221,9 @@ pub async fn fetch_storybook_stories(base_url: &str) -> Option<Vec<Shot>> {
     if let Ok(response) = client.get(&index_url).send().await {
         if response.status().is_success() {
             if let Ok(index) = response.json::<StorybookIndex>().await {
-                if let Some(entries) = index.entries {
-                    let shots: Vec<Shot> = entries
-                        .into_iter()
-                        .filter(|(_, entry)| entry.entry_type.as_deref() != Some("docs"))
-                        .map(|(_, entry)| Shot {
-                            name: entry.id.clone(),
-                            path: format!("/iframe.html?id={}&viewMode=story", entry.id),
-                            wait_for: Some("#storybook-root".to_string()),
-                            delay: Some(100),
-                        })
-                        .collect();
-
-                    if !shots.is_empty() {
-                        return Some(shots);
-                    }
+                let shots = index.into_shots();
+                if !shots.is_empty() {
+                    return Some(shots);
                 }
             }
         }
@@ -207,20 +234,9 @@ pub async fn fetch_storybook_stories(base_url: &str) -> Option<Vec<Shot>> {
     if let Ok(response) = client.get(&stories_url).send().await {
         if response.status().is_success() {
             if let Ok(stories) = response.json::<StorybookStories>().await {
-                if let Some(stories_map) = stories.stories {
-                    let shots: Vec<Shot> = stories_map
-                        .into_values()
-                        .map(|story| Shot {
-                            name: story.id.clone(),
-                            path: format!("/iframe.html?id={}&viewMode=story", story.id),
-                            wait_for: Some("#storybook-root".to_string()),
-                            delay: Some(100),
-                        })
-                        .collect();
-
-                    if !shots.is_empty() {
-                        return Some(shots);
-                    }
+                let shots = stories.into_shots();
+                if !shots.is_empty() {
+                    return Some(shots);
                 }
             }
         }
@@ -229,6 +245,38 @@ pub async fn fetch_storybook_stories(base_url: &str) -> Option<Vec<Shot>> {
     None
 }
 
+/// Reads stories from a static Storybook build (`storybook build` output) on disk.
+///
+/// Like [`fetch_storybook_stories`], reads `index.json` and falls back to
+/// `stories.json`, but without needing a server.
+pub fn load_storybook_stories(static_dir: &Path) -> Result<Vec<Shot>> {
+    let index_path = static_dir.join("index.json");
+    if index_path.is_file() {
+        let content = std::fs::read_to_string(&index_path)
+            .with_context(|| format!("Failed to read {}", index_path.display()))?;
+        let index: StorybookIndex = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid Storybook index at {}", index_path.display()))?;
+        return Ok(index.into_shots());
+    }
+
+    let stories_path = static_dir.join("stories.json");
+    if stories_path.is_file() {
+        let content = std::fs::read_to_string(&stories_path)
+            .with_context(|| format!("Failed to read {}", stories_path.display()))?;
+        let stories: StorybookStories = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid Storybook index at {}", stories_path.display()))?;
+        return Ok(stories.into_shots());
+    }
+
+    anyhow::bail!(
+        "❌ No index.json found in {}\n\n\
+         💡 Solutions:\n  \
+         • Build Storybook first: npm run build-storybook\n  \
+         • Point 'staticDir' at the build output directory",
+        static_dir.display()
+    )
+}
+
 /// Detects a generic dev server by probing common ports.
 async fn detect_dev_server(port: Option<u16>) -> Option<ProjectType> {
     let ports: Vec<u16> = match port {
@@ -318,4 +366,54 @@ mod tests {
         let result = detect_project_type(dir.path(), Some(59999)).await.unwrap();
         assert!(matches!(result, ProjectType::Unknown));
     }
+
+    #[test]
+    fn load_storybook_stories_reads_index_from_disk() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("index.json"),
+            r#"{
+                "v": 5,
+                "entries": {
+                    "button--docs": { "id": "button--docs", "type": "docs" },
+                    "button--primary": {
+                        "id": "button--primary",
+                        "type": "story",
+                        "importPath": "./src/Button.stories.tsx"
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let shots = load_storybook_stories(dir.path()).unwrap();
+        assert_eq!(shots.len(), 1);
+        assert_eq!(shots[0].name, "button--primary");
+        assert_eq!(
+            shots[0].path,
+            "/iframe.html?id=button--primary&viewMode=story"
+        );
+        assert_eq!(shots[0].wait_for.as_deref(), Some("#storybook-root"));
+        assert_eq!(
+            shots[0].import_path.as_deref(),
+            Some("./src/Button.stories.tsx")
+        );
+    }
+
+    #[test]
+    fn load_storybook_stories_falls_back_to_stories_json() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("stories.json"),
+            r#"{ "stories": { "card--default": { "id": "card--default" } } }"#,
+        )
+        .unwrap();
+
+        let shots = load_storybook_stories(dir.path()).unwrap();
+        assert_eq!(shots[0].name, "card--default");
+
+        let empty = tempdir().unwrap();
+        let err = load_storybook_stories(empty.path()).unwrap_err();
+        assert!(err.to_string().contains("No index.json found"));
+    }
 }
@@ -4,13 +4,20 @@
 //! tolerance and generates visual diff images. Supports custom differ plugins
 //! for alternative algorithms like SSIM.
 
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use image::{ImageBuffer, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
-use crate::config::Config;
+use crate::a11y::{compare_snapshots, A11yChangedShot};
+use crate::capture::{
+    load_capture_stats, load_masked_regions, load_page_errors, FailedShot, PageErrors, Stability,
+};
+use crate::config::{Config, Emulation, Region, EMULATION_PREFIXES};
+use crate::dom::{self, DomChange};
 use crate::plugins::{
     executor, DifferInput, DifferOutput, LoadedPlugin, PluginCategory, PluginRegistry,
 };
@@ -26,19 +33,81 @@ pub struct DiffResult {
     pub added: Vec<String>,
     /// Shots that exist in baseline but not in current (removed)
     pub removed: Vec<String>,
+    /// Shots whose accessibility tree differs from baseline (with `a11y`); a shot
+    /// whose pixels also changed is in `changed` as well
+    pub a11y_changed: Vec<A11yChangedShot>,
+    /// Shots that failed to capture, with their `failureArtifacts`. `diff_images`
+    /// leaves this empty; the caller fills it in with [`DiffResult::record_failed`]
+    pub failed: Vec<FailedShot>,
+    /// `stableCapture` attempts of current screenshots, keyed by name
+    pub stability: BTreeMap<String, Stability>,
+    /// Console errors, uncaught exceptions and failed requests of current
+    /// screenshots, keyed by name
+    pub page_errors: BTreeMap<String, PageErrors>,
 }
 
 impl DiffResult {
-    /// Returns true if there are any changes (changed, added, or removed).
+    /// Returns true if there are any changes (changed, added, removed, or a11y changed).
     pub fn has_changes(&self) -> bool {
-        !self.changed.is_empty() || !self.added.is_empty() || !self.removed.is_empty()
+        !self.changed.is_empty()
+            || !self.added.is_empty()
+            || !self.removed.is_empty()
+            || !self.a11y_changed.is_empty()
+    }
+
+    /// Returns true if any shot failed to capture (e.g. `pageErrors.fail` rejected it).
+    pub fn has_failures(&self) -> bool {
+        !self.failed.is_empty()
+    }
+
+    /// Returns the shots whose accessibility tree changed while their pixels didn't.
+    pub fn a11y_only(&self) -> Vec<&A11yChangedShot> {
+        self.a11y_changed
+            .iter()
+            .filter(|shot| !self.changed.iter().any(|changed| changed.name == shot.name))
+            .collect()
+    }
+
+    /// Returns the accessibility changes of a screenshot, if its tree changed.
+    pub fn a11y_changes(&self, name: &str) -> Option<&A11yChangedShot> {
+        self.a11y_changed.iter().find(|shot| shot.name == name)
+    }
+
+    /// Records the shots that failed to capture, keeping the page errors that made
+    /// them fail even when they have no current or baseline screenshot.
+    ///
+    /// A failed shot is only reported as failed: without a current screenshot it
+    /// would otherwise show up as removed.
+    pub fn record_failed(&mut self, failed: &[FailedShot]) {
+        for shot in failed {
+            if !shot.page_errors.is_empty() {
+                self.page_errors
+                    .insert(shot.name.clone(), shot.page_errors.clone());
+            }
+        }
+        let is_failed = |name: &str| failed.iter().any(|shot| shot.name == name);
+        self.unchanged.retain(|name| !is_failed(name));
+        self.changed.retain(|shot| !is_failed(&shot.name));
+        self.added.retain(|name| !is_failed(name));
+        self.removed.retain(|name| !is_failed(name));
+        self.a11y_changed.retain(|shot| !is_failed(&shot.name));
+        self.failed = failed.to_vec();
+    }
+
+    /// Returns the names of screenshots that never stopped changing during capture.
+    pub fn unstable(&self) -> Vec<&str> {
+        self.stability
+            .iter()
+            .filter(|(_, stability)| !stability.stable)
+            .map(|(name, _)| name.as_str())
+            .collect()
     }
 }
 
 /// A shot with visual differences.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ChangedShot {
-    /// Name of the shot (includes viewport suffix if multi-viewport, e.g., "button@mobile")
+    /// Name of the shot (includes viewport and browser suffixes if set, e.g., "button@mobile~firefox")
     pub name: String,
     /// Path to baseline image
     pub baseline_path: PathBuf,
@@ -50,20 +119,147 @@ pub struct ChangedShot {
     pub diff_percentage: f64,
     /// Viewport name if multi-viewport (e.g., "mobile"), None for default viewport
     pub viewport: Option<String>,
+    /// Browser name if multi-browser (e.g., "firefox"), None when `browsers` is not set
+    pub browser: Option<String>,
+    /// Emulated colour scheme, reduced motion, locale, time zone and media
+    pub emulation: Emulation,
+    /// Baseline and current dimensions when the image size changed (e.g., a clipped
+    /// element was resized), None when both images have the same size
+    pub size_change: Option<SizeChange>,
+    /// Regions excluded from the comparison via `ignoreRegions`
+    pub ignored_regions: Vec<Region>,
+    /// Regions painted over during capture via `mask` selectors
+    pub masked_regions: Vec<Region>,
+    /// Elements that moved, resized or changed style (with `domSnapshot`)
+    pub dom_changes: Vec<DomChange>,
 }
 
-/// Parses a shot name into base name and viewport.
+/// Dimensions of a baseline and current image that differ in size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeChange {
+    /// Baseline image width in pixels
+    pub baseline_width: u32,
+    /// Baseline image height in pixels
+    pub baseline_height: u32,
+    /// Current image width in pixels
+    pub current_width: u32,
+    /// Current image height in pixels
+    pub current_height: u32,
+}
+
+impl SizeChange {
+    /// Returns the size change between two images, or None if they have the same size.
+    fn between(baseline_path: &Path, current_path: &Path) -> Result<Option<Self>> {
+        let (baseline_width, baseline_height) = image::image_dimensions(baseline_path)
+            .with_context(|| {
+                format!("Failed to read baseline image: {}", baseline_path.display())
+            })?;
+        let (current_width, current_height) = image::image_dimensions(current_path)
+            .with_context(|| format!("Failed to read current image: {}", current_path.display()))?;
+
+        if (baseline_width, baseline_height) == (current_width, current_height) {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            baseline_width,
+            baseline_height,
+            current_width,
+            current_height,
+        }))
+    }
+}
+
+impl std::fmt::Display for SizeChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{} \u{2192} {}x{}",
+            self.baseline_width, self.baseline_height, self.current_width, self.current_height
+        )
+    }
+}
+
+/// A screenshot name split into its capture matrix dimensions.
+#[derive(Debug, PartialEq)]
+struct ShotName<'a> {
+    /// Name of the shot without any suffix
+    base: &'a str,
+    /// Viewport name if multi-viewport
+    viewport: Option<&'a str>,
+    /// Browser name if multi-browser
+    browser: Option<&'a str>,
+    /// Emulation suffix (e.g., "+scheme-dark+locale-ar-SA"), empty if none
+    emulation: &'a str,
+}
+
+/// Parses a shot name into base name, viewport, browser, and emulation suffix.
 ///
-/// For multi-viewport shots named `shot@viewport`, returns `(shot, Some(viewport))`.
-/// For single-viewport shots named `shot`, returns `(shot, None)`.
-fn parse_shot_name(name: &str) -> (&str, Option<&str>) {
-    if let Some(at_pos) = name.rfind('@') {
-        let base = &name[..at_pos];
-        let viewport = &name[at_pos + 1..];
-        (base, Some(viewport))
-    } else {
-        (name, None)
+/// Names have the form `shot[@viewport][~browser][+prefix-value...]`, e.g.
+/// `button@mobile~webkit+scheme-dark`. Emulation tokens are recognised by their
+/// prefix (see [`EMULATION_PREFIXES`]), so a `+` inside a shot name is kept.
+fn parse_shot_name(name: &str) -> ShotName<'_> {
+    let mut end = name.len();
+    while let Some(plus_pos) = name[..end].rfind('+') {
+        let token = &name[plus_pos + 1..end];
+        if !EMULATION_PREFIXES
+            .iter()
+            .any(|(prefix, _)| token.starts_with(prefix))
+        {
+            break;
+        }
+        end = plus_pos;
     }
+    let (rest, emulation) = name.split_at(end);
+
+    let (rest, browser) = match rest.rfind('~') {
+        Some(tilde_pos) => (&rest[..tilde_pos], Some(&rest[tilde_pos + 1..])),
+        None => (rest, None),
+    };
+    let (base, viewport) = match rest.rfind('@') {
+        Some(at_pos) => (&rest[..at_pos], Some(&rest[at_pos + 1..])),
+        None => (rest, None),
+    };
+
+    ShotName {
+        base,
+        viewport,
+        browser,
+        emulation,
+    }
+}
+
+/// Returns the shot and viewport names of a screenshot name, with `"default"` as
+/// the viewport of single-viewport screenshots.
+pub(crate) fn shot_and_viewport(name: &str) -> (&str, &str) {
+    let parsed = parse_shot_name(name);
+    (parsed.base, parsed.viewport.unwrap_or("default"))
+}
+
+/// Returns the capture matrix dimensions encoded in a shot name, as
+/// `(config key, value)` pairs, e.g. `[("viewport", "mobile"), ("colorScheme", "dark")]`.
+///
+/// Emulation values are the file-name tokens, so a time zone such as
+/// "Europe/Berlin" is returned as "Europe-Berlin".
+pub fn shot_dimensions(name: &str) -> Vec<(&'static str, &str)> {
+    let parsed = parse_shot_name(name);
+    let mut dimensions = Vec::new();
+    if let Some(viewport) = parsed.viewport {
+        dimensions.push(("viewport", viewport));
+    }
+    if let Some(browser) = parsed.browser {
+        dimensions.push(("browser", browser));
+    }
+    for token in parsed.emulation.split('+').filter(|t| !t.is_empty()) {
+        if let Some((prefix, key)) = EMULATION_PREFIXES
+            .iter()
+            .find(|(prefix, _)| token.starts_with(prefix))
+        {
+            dimensions.push((*key, &token[prefix.len()..]));
+        }
+    }
+    dimensions
 }
 
 /// Compares current screenshots against baseline.
@@ -106,6 +302,10 @@ pub fn diff_images<P: AsRef<Path>>(
         changed: Vec::new(),
         added: Vec::new(),
         removed: Vec::new(),
+        stability: BTreeMap::new(),
+        page_errors: BTreeMap::new(),
+        a11y_changed: Vec::new(),
+        failed: Vec::new(),
     };
 
     // Get all current screenshots
@@ -136,6 +336,30 @@ pub fn diff_images<P: AsRef<Path>>(
         }
     }
 
+    // A shard only answers for its own combinations; the others are compared,
+    // and reported as removed, by the shard they belong to
+    if let Some(shard) = &config.shard {
+        let in_shard = |name: &String| {
+            let (shot, viewport) = shot_and_viewport(name);
+            shard.contains(shot, viewport)
+        };
+        current_shots.retain(in_shard);
+        baseline_shots.retain(in_shard);
+    }
+
+    // Combinations a shot's `viewports`, `browsers` or a `matrix` rule leave out
+    // are not captured, so their baselines are kept rather than reported as removed
+    let mut skipped = std::collections::HashSet::new();
+    for name in current_shots.union(&baseline_shots) {
+        let parsed = parse_shot_name(name);
+        let viewport = parsed.viewport.unwrap_or("default");
+        if !config.selects(parsed.base, viewport, parsed.browser)? {
+            skipped.insert(name.clone());
+        }
+    }
+    current_shots.retain(|name| !skipped.contains(name));
+    baseline_shots.retain(|name| !skipped.contains(name));
+
     // Find added shots (in current but not baseline)
     result
         .added
@@ -149,14 +373,49 @@ pub fn diff_images<P: AsRef<Path>>(
     // Get differ plugin if available
     let differ_plugin = plugin_registry.and_then(|r| r.get(PluginCategory::Differ));
 
+    // Regions painted over during capture, recorded per screenshot
+    let masked_regions = load_masked_regions(&current_dir);
+
+    // Attempts of screenshots taken with stableCapture
+    result.stability = load_capture_stats(&current_dir)
+        .into_iter()
+        .filter(|(name, _)| current_shots.contains(name))
+        .collect();
+
+    // Errors the pages reported during capture, including shots that failed
+    // because of them and so have no current screenshot; new shots that failed
+    // are added back by `record_failed`
+    result.page_errors = load_page_errors(&current_dir)
+        .into_iter()
+        .filter(|(name, _)| current_shots.contains(name) || result.removed.contains(name))
+        .collect();
+
+    // Emulation combinations keyed by their file name suffix
+    let emulations: HashMap<String, Emulation> = config
+        .effective_emulations()
+        .into_iter()
+        .map(|emulation| (emulation.suffix(), emulation))
+        .collect();
+
     // Compare shots that exist in both
     for name in current_shots.intersection(&baseline_shots) {
         let baseline_path = baseline_dir.join(format!("{}.png", name));
         let current_path = current_dir.join(format!("{}.png", name));
         let diff_path = diff_dir.join(format!("{}.png", name));
 
+        // Baselines carried forward by --changed-since are unchanged by definition
+        if config.carried_forward.contains(shot_and_viewport(name).0) {
+            result.unchanged.push(name.clone());
+            continue;
+        }
+
         debug!("🔍 Comparing: {}", name);
 
+        let size_change = SizeChange::between(&baseline_path, &current_path)?;
+        let parsed = parse_shot_name(name);
+        let ignored = config.ignore_regions_for(parsed.base);
+        let masked = masked_regions.get(name).cloned().unwrap_or_default();
+
         let diff_percentage = if let Some(plugin) = differ_plugin {
             // Use plugin for comparison
             compare_with_plugin(
@@ -165,27 +424,70 @@ pub fn diff_images<P: AsRef<Path>>(
                 &current_path,
                 &diff_path,
                 config.threshold,
+                &ignored,
                 working_dir,
             )?
         } else {
             // Use built-in comparison
-            compare_images(&baseline_path, &current_path, &diff_path, config.threshold)?
+            compare_images(
+                &baseline_path,
+                &current_path,
+                &diff_path,
+                config.threshold,
+                &ignored,
+                &masked,
+            )?
+        };
+
+        // With `a11y`, the accessibility tree is compared as well
+        let a11y_changed = match compare_snapshots(&baseline_dir, &current_dir, name)? {
+            Some(changes) if !changes.is_empty() => {
+                info!(
+                    "♿ {}: {} accessibility tree change(s)",
+                    name,
+                    changes.len()
+                );
+                result.a11y_changed.push(A11yChangedShot {
+                    name: name.clone(),
+                    changes,
+                });
+                true
+            }
+            _ => false,
         };
 
-        if diff_percentage > config.threshold {
-            info!("⚠️  {}: {:.2}% different", name, diff_percentage);
-            let (_, viewport) = parse_shot_name(name);
+        if diff_percentage > config.threshold || size_change.is_some() {
+            match &size_change {
+                Some(size) => info!(
+                    "⚠️  {}: resized {} ({:.2}% different)",
+                    name, size, diff_percentage
+                ),
+                None => info!("⚠️  {}: {:.2}% different", name, diff_percentage),
+            }
+            // With `domSnapshot`, list the elements behind the change
+            let dom_changes = dom::compare_snapshots(&baseline_dir, &current_dir, name)?;
             result.changed.push(ChangedShot {
                 name: name.clone(),
                 baseline_path,
                 current_path,
                 diff_path,
                 diff_percentage,
-                viewport: viewport.map(String::from),
+                viewport: parsed.viewport.map(String::from),
+                browser: parsed.browser.map(String::from),
+                emulation: emulations
+                    .get(parsed.emulation)
+                    .cloned()
+                    .unwrap_or_default(),
+                size_change,
+                ignored_regions: ignored,
+                masked_regions: masked,
+                dom_changes,
             });
         } else {
-            debug!("✅ {}: unchanged", name);
-            result.unchanged.push(name.clone());
+            if !a11y_changed {
+                debug!("✅ {}: unchanged", name);
+                result.unchanged.push(name.clone());
+            }
             // Remove diff file if it exists and shot is unchanged
             let _ = std::fs::remove_file(&diff_path);
         }
@@ -196,13 +498,15 @@ pub fn diff_images<P: AsRef<Path>>(
     result.added.sort();
     result.removed.sort();
     result.changed.sort_by(|a, b| a.name.cmp(&b.name));
+    result.a11y_changed.sort_by(|a, b| a.name.cmp(&b.name));
 
     info!(
-        "📊 Diff complete: {} unchanged, {} changed, {} added, {} removed",
+        "📊 Diff complete: {} unchanged, {} changed, {} added, {} removed, {} a11y changed",
         result.unchanged.len(),
         result.changed.len(),
         result.added.len(),
-        result.removed.len()
+        result.removed.len(),
+        result.a11y_changed.len()
     );
 
     Ok(result)
@@ -217,6 +521,7 @@ fn compare_with_plugin(
     current_path: &Path,
     diff_path: &Path,
     threshold: f64,
+    ignore_regions: &[Region],
     working_dir: &Path,
 ) -> Result<f64> {
     let input = DifferInput {
@@ -224,6 +529,7 @@ fn compare_with_plugin(
         current_path: current_path.to_string_lossy().to_string(),
         diff_path: diff_path.to_string_lossy().to_string(),
         threshold,
+        ignore_regions: ignore_regions.to_vec(),
         options: serde_json::Value::Null,
     };
 
@@ -234,12 +540,23 @@ fn compare_with_plugin(
 
 /// Compares two images and generates a diff image.
 ///
-/// Returns the percentage of pixels that differ.
+/// Images of different sizes (e.g., full-page screenshots whose height changed)
+/// are compared over the union of both canvases, aligned at the top-left corner.
+/// Pixels that only exist in one of the images count as different and are shown
+/// red-tinted in the diff image, so the added or removed area stays readable.
+///
+/// Pixels inside `ignored` regions are left out of the comparison entirely.
+/// Ignored and `masked` regions are outlined in the diff image so reviewers can
+/// see what was excluded.
+///
+/// Returns the percentage of compared pixels that differ.
 fn compare_images(
     baseline_path: &Path,
     current_path: &Path,
     diff_path: &Path,
     threshold: f64,
+    ignored: &[Region],
+    masked: &[Region],
 ) -> Result<f64> {
     let baseline = image::open(baseline_path)
         .with_context(|| format!("Failed to load baseline image: {}", baseline_path.display()))?
@@ -249,33 +566,45 @@ fn compare_images(
         .with_context(|| format!("Failed to load current image: {}", current_path.display()))?
         .to_rgba8();
 
-    // Handle size mismatch
-    if baseline.dimensions() != current.dimensions() {
-        // Create a diff image showing the size difference
-        let (bw, bh) = baseline.dimensions();
-        let (cw, ch) = current.dimensions();
-        let max_w = bw.max(cw);
-        let max_h = bh.max(ch);
-
-        let mut diff_img: RgbaImage = ImageBuffer::new(max_w, max_h);
-
-        // Fill with red to show size mismatch
-        for pixel in diff_img.pixels_mut() {
-            *pixel = Rgba([255, 0, 0, 255]);
-        }
-
-        diff_img.save(diff_path)?;
-        return Ok(100.0);
-    }
-
-    let (width, height) = baseline.dimensions();
-    let total_pixels = (width * height) as f64;
+    let (bw, bh) = baseline.dimensions();
+    let (cw, ch) = current.dimensions();
+    let width = bw.max(cw);
+    let height = bh.max(ch);
+    let mut compared_pixels = 0u64;
     let mut diff_count = 0u64;
 
     let mut diff_img: RgbaImage = ImageBuffer::new(width, height);
 
     for y in 0..height {
         for x in 0..width {
+            if ignored.iter().any(|region| region.contains(x, y)) {
+                let pixel = if x < cw && y < ch {
+                    *current.get_pixel(x, y)
+                } else {
+                    Rgba([0, 0, 0, 0])
+                };
+                diff_img.put_pixel(x, y, dim(&pixel));
+                continue;
+            }
+            compared_pixels += 1;
+
+            let in_baseline = x < bw && y < bh;
+            let in_current = x < cw && y < ch;
+
+            // Pixels outside one of the images are part of the size change
+            if !in_baseline || !in_current {
+                diff_count += 1;
+                let pixel = if in_current {
+                    *current.get_pixel(x, y)
+                } else if in_baseline {
+                    *baseline.get_pixel(x, y)
+                } else {
+                    Rgba([0, 0, 0, 255])
+                };
+                diff_img.put_pixel(x, y, red_overlay(&pixel));
+                continue;
+            }
+
             let baseline_pixel = baseline.get_pixel(x, y);
             let current_pixel = current.get_pixel(x, y);
 
@@ -283,33 +612,26 @@ fn compare_images(
                 diff_count += 1;
 
                 // Create diff pixel: red overlay on dimmed current
-                diff_img.put_pixel(
-                    x,
-                    y,
-                    Rgba([
-                        255,
-                        ((current_pixel[1] as f32) * 0.3) as u8,
-                        ((current_pixel[2] as f32) * 0.3) as u8,
-                        255,
-                    ]),
-                );
+                diff_img.put_pixel(x, y, red_overlay(current_pixel));
             } else {
                 // Dimmed version of current for context
-                diff_img.put_pixel(
-                    x,
-                    y,
-                    Rgba([
-                        ((current_pixel[0] as f32) * 0.5) as u8,
-                        ((current_pixel[1] as f32) * 0.5) as u8,
-                        ((current_pixel[2] as f32) * 0.5) as u8,
-                        current_pixel[3],
-                    ]),
-                );
+                diff_img.put_pixel(x, y, dim(current_pixel));
             }
         }
     }
 
-    let diff_percentage = (diff_count as f64 / total_pixels) * 100.0;
+    for region in ignored {
+        draw_outline(&mut diff_img, region, IGNORED_OUTLINE);
+    }
+    for region in masked {
+        draw_outline(&mut diff_img, region, MASKED_OUTLINE);
+    }
+
+    let diff_percentage = if compared_pixels > 0 {
+        (diff_count as f64 / compared_pixels as f64) * 100.0
+    } else {
+        0.0
+    };
 
     // Only save diff image if there are differences
     if diff_count > 0 {
@@ -321,6 +643,51 @@ fn compare_images(
     Ok(diff_percentage)
 }
 
+/// Outline colour for `ignoreRegions` in diff images.
+const IGNORED_OUTLINE: Rgba<u8> = Rgba([59, 130, 246, 255]);
+
+/// Outline colour for masked elements in diff images.
+const MASKED_OUTLINE: Rgba<u8> = Rgba([245, 158, 11, 255]);
+
+/// Returns the diff image pixel for an unchanged pixel: the source dimmed for context.
+fn dim(pixel: &Rgba<u8>) -> Rgba<u8> {
+    Rgba([
+        ((pixel[0] as f32) * 0.5) as u8,
+        ((pixel[1] as f32) * 0.5) as u8,
+        ((pixel[2] as f32) * 0.5) as u8,
+        pixel[3],
+    ])
+}
+
+/// Draws a 2px outline of a region onto an image, clipped to the image bounds.
+fn draw_outline(img: &mut RgbaImage, region: &Region, color: Rgba<u8>) {
+    let (width, height) = img.dimensions();
+    let x_end = region.x.saturating_add(region.width).min(width);
+    let y_end = region.y.saturating_add(region.height).min(height);
+
+    for y in region.y..y_end {
+        for x in region.x..x_end {
+            let on_edge = x < region.x + 2
+                || y < region.y + 2
+                || x + 2 >= region.x.saturating_add(region.width)
+                || y + 2 >= region.y.saturating_add(region.height);
+            if on_edge {
+                img.put_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+/// Returns the diff image pixel for a differing pixel: red overlay on the dimmed source.
+fn red_overlay(pixel: &Rgba<u8>) -> Rgba<u8> {
+    Rgba([
+        255,
+        ((pixel[1] as f32) * 0.3) as u8,
+        ((pixel[2] as f32) * 0.3) as u8,
+        255,
+    ])
+}
+
 /// Checks if two pixels differ beyond the tolerance threshold.
 ///
 /// Uses color distance calculation with anti-aliasing tolerance.
@@ -373,6 +740,64 @@ pub fn has_baseline<P: AsRef<Path>>(config: &Config, working_dir: P) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::a11y::snapshot_path;
+    use crate::config::ColorScheme;
+
+    #[test]
+    fn parse_shot_name_splits_viewport_and_browser() {
+        let parsed = parse_shot_name("button");
+        assert_eq!(
+            (parsed.base, parsed.viewport, parsed.browser),
+            ("button", None, None)
+        );
+
+        let parsed = parse_shot_name("button@mobile");
+        assert_eq!(
+            (parsed.base, parsed.viewport, parsed.browser),
+            ("button", Some("mobile"), None)
+        );
+
+        let parsed = parse_shot_name("button~firefox");
+        assert_eq!(
+            (parsed.base, parsed.viewport, parsed.browser),
+            ("button", None, Some("firefox"))
+        );
+
+        let parsed = parse_shot_name("button@mobile~webkit");
+        assert_eq!(
+            (parsed.base, parsed.viewport, parsed.browser),
+            ("button", Some("mobile"), Some("webkit"))
+        );
+    }
+
+    #[test]
+    fn parse_shot_name_splits_emulation_suffix() {
+        assert_eq!(
+            parse_shot_name("a+b@mobile~webkit+scheme-dark+locale-ar-SA"),
+            ShotName {
+                base: "a+b",
+                viewport: Some("mobile"),
+                browser: Some("webkit"),
+                emulation: "+scheme-dark+locale-ar-SA",
+            }
+        );
+        assert_eq!(parse_shot_name("a+b").emulation, "");
+    }
+
+    #[test]
+    fn shot_dimensions_lists_every_dimension() {
+        assert_eq!(
+            shot_dimensions("card@tablet~firefox+motion-reduce+tz-Europe-Berlin+media-print"),
+            vec![
+                ("viewport", "tablet"),
+                ("browser", "firefox"),
+                ("reducedMotion", "reduce"),
+                ("timezoneId", "Europe-Berlin"),
+                ("media", "print"),
+            ]
+        );
+        assert!(shot_dimensions("card").is_empty());
+    }
 
     #[test]
     fn identical_pixels_do_not_differ() {
@@ -403,6 +828,10 @@ mod tests {
             changed: Vec::new(),
             added: Vec::new(),
             removed: Vec::new(),
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
         };
 
         assert!(result.unchanged.is_empty());
@@ -418,6 +847,10 @@ mod tests {
             changed: Vec::new(),
             added: Vec::new(),
             removed: Vec::new(),
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
         };
         assert!(!result.has_changes());
     }
@@ -432,10 +865,14 @@ mod tests {
                 current_path: PathBuf::new(),
                 diff_path: PathBuf::new(),
                 diff_percentage: 1.0,
-                viewport: None,
+                ..Default::default()
             }],
             added: Vec::new(),
             removed: Vec::new(),
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
         };
         assert!(result.has_changes());
     }
@@ -447,6 +884,10 @@ mod tests {
             changed: Vec::new(),
             added: vec!["new".to_string()],
             removed: Vec::new(),
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
         };
         assert!(result.has_changes());
     }
@@ -458,6 +899,10 @@ mod tests {
             changed: Vec::new(),
             added: Vec::new(),
             removed: vec!["old".to_string()],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
         };
         assert!(result.has_changes());
     }
@@ -490,4 +935,420 @@ mod tests {
 
         assert!(has_baseline(&config, dir.path()));
     }
+
+    #[test]
+    fn compare_images_reports_partial_diff_for_resized_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_path = dir.path().join("baseline.png");
+        let current_path = dir.path().join("current.png");
+        let diff_path = dir.path().join("diff.png");
+
+        let white = Rgba([255u8, 255, 255, 255]);
+        ImageBuffer::from_pixel(10, 10, white)
+            .save(&baseline_path)
+            .unwrap();
+        ImageBuffer::from_pixel(10, 12, white)
+            .save(&current_path)
+            .unwrap();
+
+        let diff =
+            compare_images(&baseline_path, &current_path, &diff_path, 0.01, &[], &[]).unwrap();
+
+        // Only the two extra rows differ: 20 of 120 pixels
+        assert!((diff - 100.0 * 20.0 / 120.0).abs() < 1e-9);
+        assert_eq!(image::image_dimensions(&diff_path).unwrap(), (10, 12));
+    }
+
+    #[test]
+    fn compare_images_only_counts_extra_height_of_taller_full_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_path = dir.path().join("baseline.png");
+        let current_path = dir.path().join("current.png");
+        let diff_path = dir.path().join("diff.png");
+
+        let white = Rgba([255u8, 255, 255, 255]);
+        ImageBuffer::from_pixel(10, 10, white)
+            .save(&baseline_path)
+            .unwrap();
+        ImageBuffer::from_pixel(10, 20, white)
+            .save(&current_path)
+            .unwrap();
+
+        let diff =
+            compare_images(&baseline_path, &current_path, &diff_path, 0.01, &[], &[]).unwrap();
+        assert!((diff - 50.0).abs() < 1e-9);
+
+        // Shared area is dimmed, the extra area keeps the current content tinted red
+        let diff_img = image::open(&diff_path).unwrap().to_rgba8();
+        assert_eq!(*diff_img.get_pixel(5, 5), Rgba([127, 127, 127, 255]));
+        assert_eq!(*diff_img.get_pixel(5, 15), Rgba([255, 76, 76, 255]));
+    }
+
+    #[test]
+    fn compare_images_skips_ignored_regions_and_outlines_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_path = dir.path().join("baseline.png");
+        let current_path = dir.path().join("current.png");
+        let diff_path = dir.path().join("diff.png");
+
+        let white = Rgba([255u8, 255, 255, 255]);
+        let black = Rgba([0u8, 0, 0, 255]);
+        ImageBuffer::from_pixel(20, 20, white)
+            .save(&baseline_path)
+            .unwrap();
+
+        // A changing "timestamp" in the top-left corner and one changed pixel elsewhere
+        let mut current = ImageBuffer::from_pixel(20, 20, white);
+        for y in 0..5 {
+            for x in 0..10 {
+                current.put_pixel(x, y, black);
+            }
+        }
+        current.put_pixel(15, 15, black);
+        current.save(&current_path).unwrap();
+
+        let ignored = [Region {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 5,
+        }];
+        let masked = [Region {
+            x: 2,
+            y: 10,
+            width: 8,
+            height: 8,
+        }];
+        let diff = compare_images(
+            &baseline_path,
+            &current_path,
+            &diff_path,
+            0.01,
+            &ignored,
+            &masked,
+        )
+        .unwrap();
+
+        // Only the single pixel outside the ignored region counts, out of 350 compared
+        assert!((diff - 100.0 / 350.0).abs() < 1e-9);
+
+        let diff_img = image::open(&diff_path).unwrap().to_rgba8();
+        assert_eq!(*diff_img.get_pixel(0, 0), IGNORED_OUTLINE);
+        assert_eq!(*diff_img.get_pixel(2, 10), MASKED_OUTLINE);
+        assert_eq!(*diff_img.get_pixel(5, 13), Rgba([127, 127, 127, 255]));
+    }
+
+    #[test]
+    fn size_change_detects_resized_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_path = dir.path().join("baseline.png");
+        let current_path = dir.path().join("current.png");
+        let same_path = dir.path().join("same.png");
+
+        let img: RgbaImage = ImageBuffer::new(120, 40);
+        img.save(&baseline_path).unwrap();
+        img.save(&same_path).unwrap();
+        let resized: RgbaImage = ImageBuffer::new(130, 40);
+        resized.save(&current_path).unwrap();
+
+        let change = SizeChange::between(&baseline_path, &current_path)
+            .unwrap()
+            .unwrap();
+        assert_eq!(change.baseline_width, 120);
+        assert_eq!(change.current_width, 130);
+        assert_eq!(change.to_string(), "120x40 \u{2192} 130x40");
+
+        assert!(SizeChange::between(&baseline_path, &same_path)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn diff_images_reports_resized_element_as_size_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_dir = dir.path().join(".pixelguard/baseline");
+        let current_dir = dir.path().join(".pixelguard/current");
+        std::fs::create_dir_all(&baseline_dir).unwrap();
+        std::fs::create_dir_all(&current_dir).unwrap();
+
+        let white = Rgba([255u8, 255, 255, 255]);
+        ImageBuffer::from_pixel(100, 40, white)
+            .save(baseline_dir.join("button.png"))
+            .unwrap();
+        ImageBuffer::from_pixel(110, 40, white)
+            .save(current_dir.join("button.png"))
+            .unwrap();
+
+        let config = Config::default();
+        let result = diff_images(&config, dir.path(), None).unwrap();
+
+        assert_eq!(result.changed.len(), 1);
+        let changed = &result.changed[0];
+        assert!(changed.diff_percentage < 100.0);
+        assert_eq!(
+            changed.size_change,
+            Some(SizeChange {
+                baseline_width: 100,
+                baseline_height: 40,
+                current_width: 110,
+                current_height: 40,
+            })
+        );
+    }
+
+    #[test]
+    fn diff_images_resolves_emulation_from_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_dir = dir.path().join(".pixelguard/baseline");
+        let current_dir = dir.path().join(".pixelguard/current");
+        std::fs::create_dir_all(&baseline_dir).unwrap();
+        std::fs::create_dir_all(&current_dir).unwrap();
+
+        let name = "clock+scheme-dark+tz-Asia-Tokyo.png";
+        ImageBuffer::from_pixel(10, 10, Rgba([255u8, 255, 255, 255]))
+            .save(baseline_dir.join(name))
+            .unwrap();
+        ImageBuffer::from_pixel(10, 10, Rgba([0u8, 0, 0, 255]))
+            .save(current_dir.join(name))
+            .unwrap();
+
+        let config = Config {
+            color_scheme: vec![ColorScheme::Light, ColorScheme::Dark],
+            timezone_id: vec!["Asia/Tokyo".to_string()],
+            ..Default::default()
+        };
+        let result = diff_images(&config, dir.path(), None).unwrap();
+
+        assert_eq!(result.changed.len(), 1);
+        let emulation = &result.changed[0].emulation;
+        assert_eq!(emulation.color_scheme, Some(ColorScheme::Dark));
+        assert_eq!(emulation.timezone_id.as_deref(), Some("Asia/Tokyo"));
+    }
+
+    #[test]
+    fn diff_images_keeps_baselines_of_unselected_viewports() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_dir = dir.path().join(".pixelguard/baseline");
+        let current_dir = dir.path().join(".pixelguard/current");
+        std::fs::create_dir_all(&baseline_dir).unwrap();
+        std::fs::create_dir_all(&current_dir).unwrap();
+
+        let image: RgbaImage = ImageBuffer::from_pixel(4, 4, Rgba([255u8, 255, 255, 255]));
+        for name in ["icon@mobile", "icon@desktop", "page@mobile", "page@desktop"] {
+            image
+                .save(baseline_dir.join(format!("{}.png", name)))
+                .unwrap();
+        }
+        for name in ["icon@desktop", "page@desktop"] {
+            image
+                .save(current_dir.join(format!("{}.png", name)))
+                .unwrap();
+        }
+
+        let viewport = |name: &str| crate::config::NamedViewport {
+            name: name.to_string(),
+            width: 4,
+            height: 4,
+            ..Default::default()
+        };
+        let shot = |name: &str, viewports: &[&str]| crate::config::Shot {
+            name: name.to_string(),
+            viewports: viewports.iter().map(|v| v.to_string()).collect(),
+            ..Default::default()
+        };
+        let config = Config {
+            viewports: vec![viewport("mobile"), viewport("desktop")],
+            shots: vec![shot("icon", &["desktop"]), shot("page", &[])],
+            ..Default::default()
+        };
+        let result = diff_images(&config, dir.path(), None).unwrap();
+
+        assert_eq!(result.unchanged.len(), 2);
+        assert_eq!(result.removed, vec!["page@mobile".to_string()]);
+    }
+
+    #[test]
+    fn diff_images_reports_accessibility_tree_changes_separately() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_dir = dir.path().join(".pixelguard/baseline");
+        let current_dir = dir.path().join(".pixelguard/current");
+        std::fs::create_dir_all(&baseline_dir).unwrap();
+        std::fs::create_dir_all(&current_dir).unwrap();
+
+        let image: RgbaImage = ImageBuffer::from_pixel(4, 4, Rgba([255u8, 255, 255, 255]));
+        let tree = |label: &str| {
+            serde_json::json!({ "role": "WebArea", "children": [{ "role": "textbox", "name": label }] })
+                .to_string()
+        };
+        for (name, baseline, current) in [
+            ("form", "Email", ""),
+            ("search", "Search", "Search"),
+            ("legacy", "Name", "Name"),
+        ] {
+            image
+                .save(baseline_dir.join(format!("{}.png", name)))
+                .unwrap();
+            image
+                .save(current_dir.join(format!("{}.png", name)))
+                .unwrap();
+            std::fs::write(snapshot_path(&baseline_dir, name), tree(baseline)).unwrap();
+            if name != "legacy" {
+                std::fs::write(snapshot_path(&current_dir, name), tree(current)).unwrap();
+            }
+        }
+
+        let result = diff_images(&Config::default(), dir.path(), None).unwrap();
+
+        assert!(result.changed.is_empty());
+        assert_eq!(result.unchanged, vec!["legacy", "search"]);
+        assert_eq!(result.a11y_changed.len(), 1);
+        assert_eq!(result.a11y_changed[0].name, "form");
+        assert_eq!(result.a11y_changed[0].changes.len(), 2);
+        assert_eq!(result.a11y_only().len(), 1);
+        assert!(result.has_changes());
+    }
+
+    #[test]
+    fn diff_images_lists_dom_changes_of_changed_shots() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_dir = dir.path().join(".pixelguard/baseline");
+        let current_dir = dir.path().join(".pixelguard/current");
+        std::fs::create_dir_all(&baseline_dir).unwrap();
+        std::fs::create_dir_all(&current_dir).unwrap();
+
+        let white: RgbaImage = ImageBuffer::from_pixel(4, 4, Rgba([255u8, 255, 255, 255]));
+        let black: RgbaImage = ImageBuffer::from_pixel(4, 4, Rgba([0u8, 0, 0, 255]));
+        let snapshot = |color: &str| {
+            serde_json::json!({ "elements": [{
+                "path": "body",
+                "box": { "x": 0, "y": 0, "width": 4, "height": 4 },
+                "styles": { "background-color": color }
+            }] })
+            .to_string()
+        };
+        for name in ["card", "badge"] {
+            white
+                .save(baseline_dir.join(format!("{}.png", name)))
+                .unwrap();
+            std::fs::write(
+                crate::dom::snapshot_path(&baseline_dir, name),
+                snapshot("rgb(255, 255, 255)"),
+            )
+            .unwrap();
+            std::fs::write(
+                crate::dom::snapshot_path(&current_dir, name),
+                snapshot("rgb(0, 0, 0)"),
+            )
+            .unwrap();
+        }
+        black.save(current_dir.join("card.png")).unwrap();
+        white.save(current_dir.join("badge.png")).unwrap();
+
+        let result = diff_images(&Config::default(), dir.path(), None).unwrap();
+
+        // Only changed screenshots are explained
+        assert_eq!(result.unchanged, vec!["badge"]);
+        assert_eq!(result.changed.len(), 1);
+        let changes = &result.changed[0].dom_changes;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            changes[0].details(),
+            vec!["background-color: rgb(255, 255, 255) → rgb(0, 0, 0)"]
+        );
+    }
+
+    #[test]
+    fn record_failed_keeps_page_errors_of_new_failed_shots() {
+        let dir = tempfile::tempdir().unwrap();
+        let current_dir = dir.path().join(".pixelguard/current");
+        std::fs::create_dir_all(dir.path().join(".pixelguard/baseline")).unwrap();
+        std::fs::create_dir_all(&current_dir).unwrap();
+
+        // A new shot failed because of its page errors: no screenshot anywhere
+        let failed = FailedShot {
+            name: "signup".to_string(),
+            error: "Page reported 1 error(s)".to_string(),
+            duration_ms: None,
+            page_errors: PageErrors {
+                exceptions: vec!["TypeError: x is undefined".to_string()],
+                ..Default::default()
+            },
+            artifacts: Vec::new(),
+        };
+        let capture = crate::capture::CaptureResult {
+            failed: vec![failed.clone()],
+            ..Default::default()
+        };
+        crate::capture::save_page_errors(&current_dir, &capture).unwrap();
+
+        let mut result = diff_images(&Config::default(), dir.path(), None).unwrap();
+        result.record_failed(&capture.failed);
+
+        assert_eq!(result.failed, vec![failed.clone()]);
+        assert_eq!(result.page_errors.get("signup"), Some(&failed.page_errors));
+        assert!(result.has_failures());
+    }
+
+    #[test]
+    fn record_failed_reports_failed_shot_with_baseline_as_failed_not_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_dir = dir.path().join(".pixelguard/baseline");
+        std::fs::create_dir_all(&baseline_dir).unwrap();
+        std::fs::create_dir_all(dir.path().join(".pixelguard/current")).unwrap();
+        ImageBuffer::from_pixel(10, 10, Rgba([255u8, 255, 255, 255]))
+            .save(baseline_dir.join("signup.png"))
+            .unwrap();
+
+        let mut result = diff_images(&Config::default(), dir.path(), None).unwrap();
+        assert_eq!(result.removed, vec!["signup".to_string()]);
+
+        result.record_failed(&[FailedShot {
+            name: "signup".to_string(),
+            error: "1 page error(s): TypeError".to_string(),
+            duration_ms: None,
+            page_errors: PageErrors::default(),
+            artifacts: Vec::new(),
+        }]);
+
+        assert!(result.removed.is_empty());
+        assert!(result.has_failures());
+    }
+
+    #[test]
+    fn diff_images_only_reports_the_shards_own_screenshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline_dir = dir.path().join(".pixelguard/baseline");
+        let current_dir = dir.path().join(".pixelguard/current");
+        std::fs::create_dir_all(&baseline_dir).unwrap();
+        std::fs::create_dir_all(&current_dir).unwrap();
+
+        let shard = crate::shard::Shard { index: 1, total: 2 };
+        let names: Vec<String> = (0..20).map(|i| format!("story-{}", i)).collect();
+        let white = Rgba([255u8, 255, 255, 255]);
+        for name in &names {
+            let image: RgbaImage = ImageBuffer::from_pixel(4, 4, white);
+            image
+                .save(baseline_dir.join(format!("{}.png", name)))
+                .unwrap();
+            if shard.contains(name, "default") {
+                image
+                    .save(current_dir.join(format!("{}.png", name)))
+                    .unwrap();
+            }
+        }
+
+        let config = Config {
+            shard: Some(shard),
+            ..Default::default()
+        };
+        let result = diff_images(&config, dir.path(), None).unwrap();
+
+        let expected: Vec<&String> = names
+            .iter()
+            .filter(|name| shard.contains(name, "default"))
+            .collect();
+        assert!(!expected.is_empty() && expected.len() < names.len());
+        assert_eq!(result.unchanged.len(), expected.len());
+        assert!(result.removed.is_empty());
+    }
 }
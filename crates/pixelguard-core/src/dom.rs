@@ -0,0 +1,396 @@
+//! DOM and computed-style snapshots that explain visual changes.
+//!
+//! With `domSnapshot` enabled, the capture script serializes the shot's root
+//! element (the `selector` element, or `<body>`) and its descendants, each with
+//! its bounding box and a set of key computed styles, to `{name}.dom.json` next
+//! to the screenshot. When a screenshot changes, [`compare_snapshots`] matches
+//! elements by their path and lists those that moved, resized or changed style.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// File name suffix of DOM snapshots.
+pub const DOM_SNAPSHOT_SUFFIX: &str = ".dom.json";
+
+/// Most element changes listed for one screenshot.
+pub const MAX_DOM_CHANGES: usize = 100;
+
+/// Position and size differences below this many pixels are ignored.
+const BOX_TOLERANCE: f64 = 0.5;
+
+/// Returns the path of a screenshot's DOM snapshot in an image directory.
+pub fn snapshot_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}{}", name, DOM_SNAPSHOT_SUFFIX))
+}
+
+/// A serialized DOM, as written by the capture script.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DomSnapshot {
+    /// Elements in document order, starting with the root
+    pub elements: Vec<DomElement>,
+}
+
+/// An element with its layout and computed styles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DomElement {
+    /// Path from the root, e.g. `body > main > div.card[2] > button#save`
+    pub path: String,
+    /// Bounding box in page coordinates
+    #[serde(rename = "box")]
+    pub bounds: Bounds,
+    /// Key computed styles by property name
+    #[serde(default)]
+    pub styles: BTreeMap<String, String>,
+}
+
+/// An element's bounding box in CSS pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bounds {
+    /// Left edge
+    pub x: f64,
+    /// Top edge
+    pub y: f64,
+    /// Width
+    pub width: f64,
+    /// Height
+    pub height: f64,
+}
+
+impl std::fmt::Display for Bounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}×{} at {},{}", self.width, self.height, self.x, self.y)
+    }
+}
+
+/// An element that moved, resized, changed style, or exists on one side only.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomChange {
+    /// Path of the element
+    pub path: String,
+    /// Whether the element changed, was added or was removed
+    pub kind: DomChangeKind,
+    /// Baseline bounding box (if it changed, or the element was removed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline_box: Option<Bounds>,
+    /// Current bounding box (if it changed, or the element was added)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_box: Option<Bounds>,
+    /// Computed styles with different values
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<PropertyChange>,
+}
+
+impl DomChange {
+    /// Describes the change as one line per box or property change, e.g.
+    /// `padding-top: 16px → 24px`.
+    pub fn details(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        match (self.kind, self.baseline_box, self.current_box) {
+            (DomChangeKind::Added, _, Some(current)) => lines.push(format!("added: {}", current)),
+            (DomChangeKind::Removed, Some(baseline), _) => {
+                lines.push(format!("removed: {}", baseline))
+            }
+            (_, Some(baseline), Some(current)) => {
+                lines.push(format!("box: {} → {}", baseline, current))
+            }
+            _ => {}
+        }
+        lines.extend(self.properties.iter().map(|change| {
+            format!(
+                "{}: {} → {}",
+                change.property, change.baseline, change.current
+            )
+        }));
+        lines
+    }
+}
+
+/// How an element changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DomChangeKind {
+    /// In both snapshots, with a different box or styles
+    Changed,
+    /// Only in the current snapshot
+    Added,
+    /// Only in the baseline snapshot
+    Removed,
+}
+
+/// A computed style whose value changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropertyChange {
+    /// CSS property name, e.g. `padding-top`
+    pub property: String,
+    /// Value in the baseline
+    pub baseline: String,
+    /// Value in the current screenshot
+    pub current: String,
+}
+
+/// Compares the DOM snapshots of a screenshot in two image directories.
+///
+/// Returns no changes if either snapshot is missing.
+pub fn compare_snapshots(
+    baseline_dir: &Path,
+    current_dir: &Path,
+    name: &str,
+) -> Result<Vec<DomChange>> {
+    let (Some(baseline), Some(current)) = (
+        load_snapshot(&snapshot_path(baseline_dir, name))?,
+        load_snapshot(&snapshot_path(current_dir, name))?,
+    ) else {
+        return Ok(Vec::new());
+    };
+    Ok(diff_snapshots(&baseline, &current))
+}
+
+/// Reads a snapshot file, or None if it doesn't exist.
+fn load_snapshot(path: &Path) -> Result<Option<DomSnapshot>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let snapshot = serde_json::from_str(&content).with_context(|| {
+        format!(
+            "❌ Invalid DOM snapshot: {}\n\n\
+             💡 Solution: Run 'pixelguard test' again to recapture it.",
+            path.display()
+        )
+    })?;
+    Ok(Some(snapshot))
+}
+
+/// Lists the elements that differ between two snapshots, up to [`MAX_DOM_CHANGES`].
+///
+/// An element counts as moved only if its position relative to its parent
+/// changed, so a shifted container is reported once rather than with every
+/// descendant. Likewise only the outermost added or removed element is listed.
+pub fn diff_snapshots(baseline: &DomSnapshot, current: &DomSnapshot) -> Vec<DomChange> {
+    let before: HashMap<&str, &DomElement> = baseline
+        .elements
+        .iter()
+        .map(|element| (element.path.as_str(), element))
+        .collect();
+    let after: HashMap<&str, &DomElement> = current
+        .elements
+        .iter()
+        .map(|element| (element.path.as_str(), element))
+        .collect();
+
+    let mut changes = Vec::new();
+    let mut added = HashSet::new();
+    for element in &current.elements {
+        let Some(old) = before.get(element.path.as_str()) else {
+            added.insert(element.path.as_str());
+            if !parent_path(&element.path).is_some_and(|parent| added.contains(parent)) {
+                changes.push(DomChange {
+                    path: element.path.clone(),
+                    kind: DomChangeKind::Added,
+                    baseline_box: None,
+                    current_box: Some(element.bounds),
+                    properties: Vec::new(),
+                });
+            }
+            continue;
+        };
+
+        let moved = !same_bounds(
+            relative_bounds(old, &before),
+            relative_bounds(element, &after),
+        );
+        let properties: Vec<PropertyChange> = element
+            .styles
+            .iter()
+            .filter_map(|(property, value)| {
+                let old_value = old.styles.get(property)?;
+                (old_value != value).then(|| PropertyChange {
+                    property: property.clone(),
+                    baseline: old_value.clone(),
+                    current: value.clone(),
+                })
+            })
+            .collect();
+        if moved || !properties.is_empty() {
+            changes.push(DomChange {
+                path: element.path.clone(),
+                kind: DomChangeKind::Changed,
+                baseline_box: moved.then_some(old.bounds),
+                current_box: moved.then_some(element.bounds),
+                properties,
+            });
+        }
+    }
+
+    let mut removed = HashSet::new();
+    for element in &baseline.elements {
+        if after.contains_key(element.path.as_str()) {
+            continue;
+        }
+        removed.insert(element.path.as_str());
+        if !parent_path(&element.path).is_some_and(|parent| removed.contains(parent)) {
+            changes.push(DomChange {
+                path: element.path.clone(),
+                kind: DomChangeKind::Removed,
+                baseline_box: Some(element.bounds),
+                current_box: None,
+                properties: Vec::new(),
+            });
+        }
+    }
+
+    changes.truncate(MAX_DOM_CHANGES);
+    changes
+}
+
+/// Returns the path of an element's parent, or None for the root.
+fn parent_path(path: &str) -> Option<&str> {
+    path.rsplit_once(" > ").map(|(parent, _)| parent)
+}
+
+/// Returns an element's box with its position relative to its parent's.
+fn relative_bounds(element: &DomElement, elements: &HashMap<&str, &DomElement>) -> Bounds {
+    let parent = parent_path(&element.path).and_then(|parent| elements.get(parent));
+    match parent {
+        Some(parent) => Bounds {
+            x: element.bounds.x - parent.bounds.x,
+            y: element.bounds.y - parent.bounds.y,
+            ..element.bounds
+        },
+        None => element.bounds,
+    }
+}
+
+/// Returns true if two boxes differ by less than [`BOX_TOLERANCE`] on every side.
+fn same_bounds(a: Bounds, b: Bounds) -> bool {
+    (a.x - b.x).abs() < BOX_TOLERANCE
+        && (a.y - b.y).abs() < BOX_TOLERANCE
+        && (a.width - b.width).abs() < BOX_TOLERANCE
+        && (a.height - b.height).abs() < BOX_TOLERANCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(path: &str, bounds: (f64, f64, f64, f64), styles: &[(&str, &str)]) -> DomElement {
+        DomElement {
+            path: path.to_string(),
+            bounds: Bounds {
+                x: bounds.0,
+                y: bounds.1,
+                width: bounds.2,
+                height: bounds.3,
+            },
+            styles: styles
+                .iter()
+                .map(|(property, value)| (property.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    fn card(padding: &str, offset: f64) -> DomSnapshot {
+        DomSnapshot {
+            elements: vec![
+                element("body", (0.0, 0.0, 800.0, 600.0), &[]),
+                element(
+                    "body > div.card",
+                    (0.0, offset, 300.0, 200.0),
+                    &[("padding-top", padding)],
+                ),
+                element(
+                    "body > div.card > h2",
+                    (16.0, offset + 16.0, 268.0, 24.0),
+                    &[],
+                ),
+                element(
+                    "body > div.card > button",
+                    (16.0, offset + 48.0, 80.0, 32.0),
+                    &[],
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_changes() {
+        assert!(diff_snapshots(&card("16px", 0.0), &card("16px", 0.0)).is_empty());
+    }
+
+    #[test]
+    fn moved_container_is_reported_without_its_children() {
+        let changes = diff_snapshots(&card("16px", 0.0), &card("16px", 20.0));
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "body > div.card");
+        assert_eq!(changes[0].kind, DomChangeKind::Changed);
+        assert_eq!(changes[0].baseline_box.unwrap().y, 0.0);
+        assert_eq!(changes[0].current_box.unwrap().y, 20.0);
+        assert!(changes[0].properties.is_empty());
+        assert_eq!(
+            changes[0].details(),
+            vec!["box: 300×200 at 0,0 → 300×200 at 0,20"]
+        );
+    }
+
+    #[test]
+    fn changed_styles_list_old_and_new_values() {
+        let changes = diff_snapshots(&card("16px", 0.0), &card("24px", 0.0));
+
+        assert_eq!(
+            changes,
+            vec![DomChange {
+                path: "body > div.card".to_string(),
+                kind: DomChangeKind::Changed,
+                baseline_box: None,
+                current_box: None,
+                properties: vec![PropertyChange {
+                    property: "padding-top".to_string(),
+                    baseline: "16px".to_string(),
+                    current: "24px".to_string(),
+                }],
+            }]
+        );
+    }
+
+    #[test]
+    fn only_outermost_added_and_removed_elements_are_listed() {
+        let mut current = card("16px", 0.0);
+        current
+            .elements
+            .retain(|e| !e.path.starts_with("body > div.card"));
+        current
+            .elements
+            .push(element("body > aside", (0.0, 0.0, 100.0, 600.0), &[]));
+        current
+            .elements
+            .push(element("body > aside > nav", (0.0, 0.0, 100.0, 300.0), &[]));
+
+        let changes = diff_snapshots(&card("16px", 0.0), &current);
+
+        let listed: Vec<(&str, DomChangeKind)> = changes
+            .iter()
+            .map(|change| (change.path.as_str(), change.kind))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("body > aside", DomChangeKind::Added),
+                ("body > div.card", DomChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn sub_pixel_jitter_is_ignored() {
+        let mut current = card("16px", 0.0);
+        current.elements[3].bounds.width = 80.3;
+
+        assert!(diff_snapshots(&card("16px", 0.0), &current).is_empty());
+    }
+}
@@ -4,7 +4,7 @@
 //!
 //! - **Configuration**: Load and manage `pixelguard.config.json`
 //! - **Detection**: Auto-detect project types (Storybook, Next.js, Vite)
-//! - **Capture**: Take screenshots using Playwright
+//! - **Capture**: Take screenshots using Playwright, a plugin or pre-rendered fixtures
 //! - **Diff**: Compare images pixel-by-pixel
 //! - **Report**: Generate static HTML reports
 //! - **Plugins**: Extend functionality with npm-based plugins
@@ -29,17 +29,31 @@
 //! }
 //! ```
 
+pub mod a11y;
+pub mod affected;
+pub mod backend;
 pub mod capture;
 pub mod config;
 pub mod detect;
 pub mod diff;
+pub mod dom;
 pub mod plugins;
 pub mod report;
+pub mod shard;
 pub mod storage;
+pub mod web_server;
 
+pub use backend::{select_backend, CaptureBackend};
 pub use capture::capture_screenshots;
-pub use config::{Config, NamedViewport, Shot, Viewport};
-pub use detect::{detect_project_type, fetch_storybook_stories, ProjectType};
+pub use config::{
+    AuthRole, Browser, BrowserOptions, CaptureBackendConfig, ColorScheme, Config, DevicePreset,
+    Emulation, FailureArtifacts, Media, NamedViewport, ReducedMotion, Region, RouteRule, Setup,
+    Shot, ShotAction, ShotAuth, Stabilize, StableCapture, Viewport, WebServer, DEVICE_PRESETS,
+};
+pub use detect::{
+    detect_project_type, fetch_storybook_stories, load_storybook_stories, ProjectType,
+};
 pub use diff::{diff_images, DiffResult};
 pub use report::generate_report;
+pub use shard::{merge_results, Shard};
 pub use storage::Storage;
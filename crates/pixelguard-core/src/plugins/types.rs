@@ -7,6 +7,13 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
+use crate::a11y::A11yChangedShot;
+use crate::capture::{PageErrors, Stability};
+use crate::config::{
+    Browser, Emulation, FailureArtifacts, NamedViewport, PageErrorPolicy, Ready, Region, RouteRule,
+    Setup, ShotAction, Stabilize, StableCapture, Timeouts,
+};
+
 /// Plugin categories that can extend Pixelguard functionality.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -188,9 +195,34 @@ pub struct CaptureInput {
     /// Viewport dimensions
     pub viewport: CaptureViewport,
 
+    /// Named viewports to capture at, with device presets resolved into
+    /// `deviceScaleFactor`, `isMobile`, `hasTouch` and `userAgent`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub viewports: Vec<NamedViewport>,
+
+    /// Browser engines to capture in (`chromium` when `browsers` is not set)
+    #[serde(default)]
+    pub browsers: Vec<Browser>,
+
     /// Output directory for screenshots
     pub output_dir: String,
 
+    /// Config-level `fullPage`, used when neither the shot nor the viewport sets it
+    #[serde(default)]
+    pub full_page: bool,
+
+    /// Maximum height in pixels for full-page screenshots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_page_height: Option<u32>,
+
+    /// Login flows for the auth roles referenced by `shots[].auth`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub setup: Option<Setup>,
+
+    /// Debugging files to save for shots that fail to capture
+    #[serde(default)]
+    pub failure_artifacts: FailureArtifacts,
+
     /// Plugin options
     #[serde(default)]
     pub options: serde_json::Value,
@@ -213,6 +245,86 @@ pub struct CaptureShot {
     /// Optional delay in ms
     #[serde(skip_serializing_if = "Option::is_none")]
     pub delay: Option<u64>,
+
+    /// Interaction steps to run before capturing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<ShotAction>,
+
+    /// Optional CSS selector to clip the screenshot to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+
+    /// Padding in pixels around the selector clip
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding: Option<u32>,
+
+    /// The shot's own `fullPage`, overriding the viewport and config-level settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_page: Option<bool>,
+
+    /// CSS selectors of elements to paint over (config and shot masks combined)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mask: Vec<String>,
+
+    /// Resolved deterministic rendering settings (None when nothing is stabilized)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stabilize: Option<Stabilize>,
+
+    /// Network route rules in priority order (shot rules, then config rules);
+    /// file paths are relative to the working directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<RouteRule>,
+
+    /// Auth role to capture as (resolved from `setup`; None when not logged in)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<String>,
+
+    /// Resolved `stableCapture` settings (None when the shot is captured once)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stable_capture: Option<StableCapture>,
+
+    /// Resolved readiness conditions (None when nothing is waited for)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ready: Option<Ready>,
+
+    /// Resolved navigation, wait and capture timeouts
+    #[serde(default)]
+    pub timeouts: Timeouts,
+
+    /// Resolved page error policy
+    #[serde(default)]
+    pub page_errors: PageErrorPolicy,
+
+    /// Save and compare the accessibility tree (`{name}.a11y.yaml`)
+    #[serde(default)]
+    pub a11y: bool,
+
+    /// Save and compare the DOM and computed styles (`{name}.dom.json`)
+    #[serde(default)]
+    pub dom_snapshot: bool,
+
+    /// Every viewport, browser and emulation combination to capture the shot in,
+    /// with the screenshot name the result is reported under
+    #[serde(default)]
+    pub screenshots: Vec<CaptureScreenshot>,
+}
+
+/// One screenshot of a shot: a viewport, browser and emulation combination.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureScreenshot {
+    /// Screenshot name, e.g. `button@mobile~firefox+scheme-dark`
+    pub name: String,
+
+    /// Name of the viewport in `viewports` (`default` when none are configured)
+    pub viewport: String,
+
+    /// Browser engine to capture in
+    pub browser: Browser,
+
+    /// Emulated media features and locale (empty when nothing is emulated)
+    #[serde(default)]
+    pub emulation: Emulation,
 }
 
 /// Viewport for capture input.
@@ -244,6 +356,26 @@ pub struct CapturedShot {
 
     /// Path to the screenshot file
     pub path: String,
+
+    /// Bounding box of the clipped element (if the shot used a selector)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub element: Option<Region>,
+
+    /// Areas painted over by mask selectors (in screenshot pixels)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub masked: Vec<Region>,
+
+    /// Attempts and time taken, when the shot used `stableCapture`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stability: Option<Stability>,
+
+    /// Console errors, uncaught exceptions and failed requests of the page
+    #[serde(
+        default,
+        rename = "pageErrors",
+        skip_serializing_if = "PageErrors::is_empty"
+    )]
+    pub page_errors: PageErrors,
 }
 
 /// A failed shot capture.
@@ -272,6 +404,10 @@ pub struct DifferInput {
     /// Diff threshold (0.0 to 1.0)
     pub threshold: f64,
 
+    /// Regions to exclude from the comparison
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_regions: Vec<Region>,
+
     /// Plugin options
     #[serde(default)]
     pub options: serde_json::Value,
@@ -321,6 +457,10 @@ pub struct ReporterDiffResult {
 
     /// Removed shots (baseline exists, no current)
     pub removed: Vec<String>,
+
+    /// Shots whose accessibility tree changed (with `a11y`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub a11y_changed: Vec<A11yChangedShot>,
 }
 
 /// A changed shot for reporter input.
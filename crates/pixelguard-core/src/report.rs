@@ -4,23 +4,31 @@
 //! with side-by-side comparison of baseline, current, and diff images.
 //! Additionally generates a machine-readable JSON export (results.json) for CI integration.
 
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use chrono::Utc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use crate::config::Config;
-use crate::diff::DiffResult;
+use crate::a11y::{A11yChangeKind, A11yChangedShot};
+use crate::capture::{FailedShot, PageErrors, Stability};
+use crate::config::{Config, Emulation, Region};
+use crate::diff::{shot_dimensions, ChangedShot, DiffResult, SizeChange};
+use crate::dom::{DomChange, DomChangeKind};
+use crate::shard::Shard;
 
 /// JSON export format for results.json
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ResultsJson {
     /// Schema version
-    pub version: &'static str,
+    pub version: String,
     /// ISO 8601 timestamp when the report was generated
     pub timestamp: String,
+    /// The shard this run covered (if run with `--shard`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shard: Option<Shard>,
     /// Summary statistics
     pub summary: ResultsSummary,
     /// Detailed results grouped by status
@@ -28,7 +36,7 @@ pub struct ResultsJson {
 }
 
 /// Summary statistics for the JSON export
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ResultsSummary {
     /// Total number of shots compared
     pub total: usize,
@@ -40,12 +48,21 @@ pub struct ResultsSummary {
     pub added: usize,
     /// Number of removed shots
     pub removed: usize,
-    /// Whether all tests passed (no changes)
+    /// Number of shots whose accessibility tree changed
+    #[serde(default, rename = "a11yChanged")]
+    pub a11y_changed: usize,
+    /// Number of screenshots that never stopped changing during capture
+    #[serde(default)]
+    pub unstable: usize,
+    /// Number of screenshots that failed to capture
+    #[serde(default)]
+    pub failed: usize,
+    /// Whether all tests passed (no changes and no failed shots)
     pub passed: bool,
 }
 
 /// Detailed results by category
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ResultsDetail {
     /// Changed shots with diff information
     pub changed: Vec<ChangedShotJson>,
@@ -55,10 +72,29 @@ pub struct ResultsDetail {
     pub removed: Vec<String>,
     /// Names of unchanged shots
     pub unchanged: Vec<String>,
+    /// Shots whose accessibility tree changed, with the nodes added and removed
+    #[serde(default, rename = "a11yChanged")]
+    pub a11y_changed: Vec<A11yChangedShot>,
+    /// Screenshots that failed to capture, with their error and debugging files
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<FailedShot>,
+    /// Names of screenshots that never stopped changing during capture
+    #[serde(default)]
+    pub unstable: Vec<String>,
+    /// `stableCapture` attempts and time taken, keyed by screenshot name
+    #[serde(default)]
+    pub stability: BTreeMap<String, Stability>,
+    /// Console errors, uncaught exceptions and failed requests, keyed by screenshot name
+    #[serde(
+        default,
+        rename = "pageErrors",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub page_errors: BTreeMap<String, PageErrors>,
 }
 
 /// Changed shot information for JSON export
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChangedShotJson {
     /// Name of the shot
@@ -66,8 +102,26 @@ pub struct ChangedShotJson {
     /// Percentage of pixels that differ
     pub diff_percentage: f64,
     /// Viewport name (if multi-viewport)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub viewport: Option<String>,
+    /// Browser name (if multi-browser)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browser: Option<String>,
+    /// Emulated colour scheme, reduced motion, locale, time zone and media (if set)
+    #[serde(flatten)]
+    pub emulation: Emulation,
+    /// Baseline and current dimensions (if the image size changed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_change: Option<SizeChange>,
+    /// Regions excluded from the comparison
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignored_regions: Vec<Region>,
+    /// Regions painted over during capture
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub masked_regions: Vec<Region>,
+    /// Elements that moved, resized or changed style (with `domSnapshot`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dom_changes: Vec<DomChange>,
     /// Path to baseline image (relative to output dir)
     pub baseline_path: String,
     /// Path to current image (relative to output dir)
@@ -76,6 +130,55 @@ pub struct ChangedShotJson {
     pub diff_path: String,
 }
 
+impl ResultsJson {
+    /// Loads the `results.json` written to an output directory.
+    pub fn load(output_dir: &Path) -> Result<Self> {
+        let path = output_dir.join("results.json");
+        let content = std::fs::read_to_string(&path).with_context(|| {
+            format!(
+                "❌ No results found at {}\n\n\
+                 💡 Solution: Run 'pixelguard test' first (with --shard for each CI job).",
+                path.display()
+            )
+        })?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("❌ Invalid results file: {}", path.display()))
+    }
+
+    /// Converts the export back into a diff result, with image paths under `output_dir`.
+    pub fn into_diff_result(self, output_dir: &Path) -> DiffResult {
+        let image_path = |relative: &str| -> PathBuf { output_dir.join(relative) };
+        DiffResult {
+            unchanged: self.results.unchanged,
+            changed: self
+                .results
+                .changed
+                .into_iter()
+                .map(|shot| ChangedShot {
+                    baseline_path: image_path(&shot.baseline_path),
+                    current_path: image_path(&shot.current_path),
+                    diff_path: image_path(&shot.diff_path),
+                    name: shot.name,
+                    diff_percentage: shot.diff_percentage,
+                    viewport: shot.viewport,
+                    browser: shot.browser,
+                    emulation: shot.emulation,
+                    size_change: shot.size_change,
+                    ignored_regions: shot.ignored_regions,
+                    masked_regions: shot.masked_regions,
+                    dom_changes: shot.dom_changes,
+                })
+                .collect(),
+            added: self.results.added,
+            removed: self.results.removed,
+            a11y_changed: self.results.a11y_changed,
+            failed: self.results.failed,
+            stability: self.results.stability,
+            page_errors: self.results.page_errors,
+        }
+    }
+}
+
 /// Generates an HTML report and JSON export from diff results.
 ///
 /// The report includes:
@@ -116,7 +219,8 @@ pub fn generate_report<P: AsRef<Path>>(
         .with_context(|| format!("Failed to write report to {}", report_path.display()))?;
 
     // Generate JSON export
-    let json = generate_results_json(result);
+    let mut json = generate_results_json(result);
+    json.shard = config.shard;
     let json_str =
         serde_json::to_string_pretty(&json).context("Failed to serialize results to JSON")?;
     std::fs::write(&json_path, json_str)
@@ -132,19 +236,26 @@ pub fn generate_report<P: AsRef<Path>>(
 /// This creates a machine-readable format suitable for CI integration,
 /// custom tooling, or programmatic analysis.
 fn generate_results_json(result: &DiffResult) -> ResultsJson {
-    let total =
-        result.unchanged.len() + result.changed.len() + result.added.len() + result.removed.len();
-    let passed = result.changed.is_empty() && result.added.is_empty() && result.removed.is_empty();
+    let total = result.unchanged.len()
+        + result.changed.len()
+        + result.added.len()
+        + result.removed.len()
+        + result.a11y_only().len();
+    let passed = !result.has_changes() && !result.has_failures();
 
     ResultsJson {
-        version: "1.0",
+        version: "1.0".to_string(),
         timestamp: Utc::now().to_rfc3339(),
+        shard: None,
         summary: ResultsSummary {
             total,
             unchanged: result.unchanged.len(),
             changed: result.changed.len(),
             added: result.added.len(),
             removed: result.removed.len(),
+            a11y_changed: result.a11y_changed.len(),
+            unstable: result.unstable().len(),
+            failed: result.failed.len(),
             passed,
         },
         results: ResultsDetail {
@@ -155,6 +266,12 @@ fn generate_results_json(result: &DiffResult) -> ResultsJson {
                     name: shot.name.clone(),
                     diff_percentage: shot.diff_percentage,
                     viewport: shot.viewport.clone(),
+                    browser: shot.browser.clone(),
+                    emulation: shot.emulation.clone(),
+                    size_change: shot.size_change,
+                    ignored_regions: shot.ignored_regions.clone(),
+                    masked_regions: shot.masked_regions.clone(),
+                    dom_changes: shot.dom_changes.clone(),
                     baseline_path: format!("baseline/{}.png", shot.name),
                     current_path: format!("current/{}.png", shot.name),
                     diff_path: format!("diff/{}.png", shot.name),
@@ -163,6 +280,11 @@ fn generate_results_json(result: &DiffResult) -> ResultsJson {
             added: result.added.clone(),
             removed: result.removed.clone(),
             unchanged: result.unchanged.clone(),
+            a11y_changed: result.a11y_changed.clone(),
+            failed: result.failed.clone(),
+            unstable: result.unstable().into_iter().map(String::from).collect(),
+            stability: result.stability.clone(),
+            page_errors: result.page_errors.clone(),
         },
     }
 }
@@ -205,8 +327,11 @@ mod icons {
 }
 
 fn generate_html(result: &DiffResult) -> String {
-    let total =
-        result.unchanged.len() + result.changed.len() + result.added.len() + result.removed.len();
+    let total = result.unchanged.len()
+        + result.changed.len()
+        + result.added.len()
+        + result.removed.len()
+        + result.a11y_only().len();
 
     let changed_html = if result.changed.is_empty() {
         String::new()
@@ -222,14 +347,48 @@ fn generate_html(result: &DiffResult) -> String {
                     .map(|v| format!(r#"<span class="badge badge--viewport">{}</span>"#, html_escape(v)))
                     .unwrap_or_default();
 
+                // Generate browser badge if multi-browser
+                let browser_badge = shot
+                    .browser
+                    .as_ref()
+                    .map(|b| format!(r#"<span class="badge badge--browser">{}</span>"#, html_escape(b)))
+                    .unwrap_or_default();
+
+                // Generate badges for emulated colour scheme, locale, etc.
+                let emulation_badges = emulation_badges(&shot.emulation);
+
+                // Generate size badge if the image was resized
+                let size_badge = shot
+                    .size_change
+                    .map(|size| format!(r#"<span class="badge badge--size" title="Baseline size → current size">{}</span>"#, size))
+                    .unwrap_or_default();
+
+                // Generate excluded regions badge and overlay data
+                let regions_badge = regions_badge(shot);
+                let regions_attr = regions_attr(shot);
+                let unstable_badge = unstable_badge(result, &shot.name);
+                let page_errors_badge = page_errors_badge(result, &shot.name);
+                let a11y_badge = if result.a11y_changes(&shot.name).is_some() {
+                    r#"<span class="badge badge--a11y" title="The accessibility tree changed too; see Accessibility Changed">A11y changed</span>"#
+                } else {
+                    ""
+                };
+
                 format!(
                     r#"
-            <div class="shot-card" data-name="{name}" data-status="changed" data-diff="{diff}">
+            <div class="shot-card" data-name="{name}" data-status="changed" data-diff="{diff}"{dimension_attrs}>
                 <div class="shot-header">
                     <div class="shot-title">
                         <span class="shot-icon">{image_icon}</span>
                         <span class="shot-name">{name}</span>
                         {viewport_badge}
+                        {browser_badge}
+                        {emulation_badges}
+                        {size_badge}
+                        {regions_badge}
+                        {unstable_badge}
+                        {page_errors_badge}
+                        {a11y_badge}
                     </div>
                     <div class="shot-header-right">
                         <div class="shot-actions">
@@ -249,21 +408,21 @@ fn generate_html(result: &DiffResult) -> String {
                         <div class="comparison">
                             <div class="image-panel" data-zoomable data-src="baseline/{name}.png" data-label="Baseline">
                                 <div class="image-label">Baseline</div>
-                                <div class="image-frame">
+                                <div class="image-frame"{regions_attr}>
                                     <img src="baseline/{name}.png" alt="Baseline" loading="lazy">
                                     <div class="zoom-hint">{zoom_icon}</div>
                                 </div>
                             </div>
                             <div class="image-panel" data-zoomable data-src="current/{name}.png" data-label="Current">
                                 <div class="image-label">Current</div>
-                                <div class="image-frame">
+                                <div class="image-frame"{regions_attr}>
                                     <img src="current/{name}.png" alt="Current" loading="lazy">
                                     <div class="zoom-hint">{zoom_icon}</div>
                                 </div>
                             </div>
                             <div class="image-panel" data-zoomable data-src="diff/{name}.png" data-label="Diff">
                                 <div class="image-label">Diff</div>
-                                <div class="image-frame image-frame--diff">
+                                <div class="image-frame image-frame--diff"{regions_attr}>
                                     <img src="diff/{name}.png" alt="Diff" loading="lazy">
                                     <div class="zoom-hint">{zoom_icon}</div>
                                 </div>
@@ -296,11 +455,22 @@ fn generate_html(result: &DiffResult) -> String {
                         </div>
                     </div>
                 </div>
+                {dom_changes}
             </div>
             "#,
                     name = html_escape(&shot.name),
                     diff = shot.diff_percentage,
                     viewport_badge = viewport_badge,
+                    browser_badge = browser_badge,
+                    emulation_badges = emulation_badges,
+                    dimension_attrs = dimension_attrs(&shot.name),
+                    size_badge = size_badge,
+                    regions_badge = regions_badge,
+                    unstable_badge = unstable_badge,
+                    page_errors_badge = page_errors_badge,
+                    a11y_badge = a11y_badge,
+                    dom_changes = dom_changes_html(shot),
+                    regions_attr = regions_attr,
                     image_icon = icons::IMAGE,
                     zoom_icon = icons::ZOOM_IN,
                     approve_icon = icons::APPROVE,
@@ -339,11 +509,13 @@ fn generate_html(result: &DiffResult) -> String {
             .map(|name| {
                 format!(
                     r#"
-            <div class="shot-card" data-name="{name}" data-status="added" data-diff="0">
+            <div class="shot-card" data-name="{name}" data-status="added" data-diff="0"{dimension_attrs}>
                 <div class="shot-header">
                     <div class="shot-title">
                         <span class="shot-icon">{image_icon}</span>
                         <span class="shot-name">{name}</span>
+                        {unstable_badge}
+                        {page_errors_badge}
                     </div>
                     <span class="badge badge--new">New</span>
                 </div>
@@ -359,6 +531,9 @@ fn generate_html(result: &DiffResult) -> String {
             </div>
             "#,
                     name = html_escape(name),
+                    dimension_attrs = dimension_attrs(name),
+                    unstable_badge = unstable_badge(result, name),
+                    page_errors_badge = page_errors_badge(result, name),
                     image_icon = icons::IMAGE,
                     zoom_icon = icons::ZOOM_IN,
                 )
@@ -395,7 +570,7 @@ fn generate_html(result: &DiffResult) -> String {
             .map(|name| {
                 format!(
                     r#"
-            <div class="shot-card" data-name="{name}" data-status="removed" data-diff="0">
+            <div class="shot-card" data-name="{name}" data-status="removed" data-diff="0"{dimension_attrs}>
                 <div class="shot-header">
                     <div class="shot-title">
                         <span class="shot-icon">{image_icon}</span>
@@ -415,6 +590,7 @@ fn generate_html(result: &DiffResult) -> String {
             </div>
             "#,
                     name = html_escape(name),
+                    dimension_attrs = dimension_attrs(name),
                     image_icon = icons::IMAGE,
                     zoom_icon = icons::ZOOM_IN,
                 )
@@ -442,6 +618,188 @@ fn generate_html(result: &DiffResult) -> String {
         )
     };
 
+    let a11y_html = if result.a11y_changed.is_empty() {
+        String::new()
+    } else {
+        let items: String = result
+            .a11y_changed
+            .iter()
+            .map(|shot| {
+                let lines: String = shot
+                    .changes
+                    .iter()
+                    .map(|change| {
+                        let class = match change.kind {
+                            A11yChangeKind::Added => "a11y-line--added",
+                            A11yChangeKind::Removed => "a11y-line--removed",
+                        };
+                        format!(
+                            r#"<span class="a11y-line {}">{}</span>"#,
+                            class,
+                            html_escape(&change.to_string())
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                format!(
+                    r#"
+            <div class="shot-card" data-name="{name}" data-status="a11y-changed" data-diff="0"{dimension_attrs}>
+                <div class="shot-header">
+                    <div class="shot-title">
+                        <span class="shot-icon">{image_icon}</span>
+                        <span class="shot-name">{name}</span>
+                    </div>
+                    <div class="shot-header-right">
+                        <div class="shot-actions">
+                            <button class="action-btn action-btn--approve" data-shot="{name}" data-action="approve" title="Approve">{approve_icon}</button>
+                            <button class="action-btn action-btn--reject" data-shot="{name}" data-action="reject" title="Reject">{reject_icon}</button>
+                        </div>
+                        <span class="badge badge--a11y">{count} node change{plural}</span>
+                    </div>
+                </div>
+                <pre class="a11y-diff">{lines}</pre>
+            </div>
+            "#,
+                    name = html_escape(&shot.name),
+                    dimension_attrs = dimension_attrs(&shot.name),
+                    count = shot.changes.len(),
+                    plural = if shot.changes.len() == 1 { "" } else { "s" },
+                    lines = lines,
+                    image_icon = icons::IMAGE,
+                    approve_icon = icons::APPROVE,
+                    reject_icon = icons::REJECT,
+                )
+            })
+            .collect();
+
+        format!(
+            r#"
+        <section class="section">
+            <div class="section-header">
+                <div class="section-title">
+                    <span class="section-icon section-icon--diff">{icon}</span>
+                    <h2>Accessibility Changed</h2>
+                    <span class="section-count">{count}</span>
+                </div>
+            </div>
+            <div class="section-content">
+                {items}
+            </div>
+        </section>
+        "#,
+            count = result.a11y_changed.len(),
+            items = items,
+            icon = icons::X_CIRCLE,
+        )
+    };
+
+    let page_errors_html = if result.page_errors.is_empty() {
+        String::new()
+    } else {
+        let items: String = result
+            .page_errors
+            .iter()
+            .map(|(name, errors)| {
+                let messages: String = errors
+                    .iter()
+                    .map(|(kind, message)| {
+                        format!(
+                            r#"<li><span class="page-error-kind">{}</span> {}</li>"#,
+                            kind,
+                            html_escape(message)
+                        )
+                    })
+                    .collect();
+                format!(
+                    r#"<div class="page-errors-item" data-name="{name}">
+                        <span class="shot-name">{name}</span>
+                        <ul class="page-errors-list">{messages}</ul>
+                    </div>"#,
+                    name = html_escape(name),
+                    messages = messages,
+                )
+            })
+            .collect();
+
+        format!(
+            r#"
+        <section class="section">
+            <div class="section-header">
+                <div class="section-title">
+                    <span class="section-icon section-icon--new">{icon}</span>
+                    <h2>Page Errors</h2>
+                    <span class="section-count">{count}</span>
+                </div>
+            </div>
+            <div class="section-content">
+                {items}
+            </div>
+        </section>
+        "#,
+            count = result.page_errors.len(),
+            items = items,
+            icon = icons::X_CIRCLE,
+        )
+    };
+
+    let failed_html = if result.failed.is_empty() {
+        String::new()
+    } else {
+        let items: String = result
+            .failed
+            .iter()
+            .map(|shot| {
+                let links: String = shot
+                    .artifacts
+                    .iter()
+                    .map(|artifact| {
+                        let file = artifact.rsplit('/').next().unwrap_or(artifact);
+                        format!(
+                            r#"<a href="{}" target="_blank">{}</a>"#,
+                            html_escape(artifact),
+                            html_escape(file)
+                        )
+                    })
+                    .collect();
+                let links = if links.is_empty() {
+                    String::new()
+                } else {
+                    format!(r#"<div class="failed-artifacts">{}</div>"#, links)
+                };
+                format!(
+                    r#"<div class="failed-item" data-name="{name}">
+                        <span class="shot-name">{name}</span>
+                        <pre class="failed-error">{error}</pre>
+                        {links}
+                    </div>"#,
+                    name = html_escape(&shot.name),
+                    error = html_escape(&shot.error),
+                    links = links,
+                )
+            })
+            .collect();
+
+        format!(
+            r#"
+        <section class="section">
+            <div class="section-header">
+                <div class="section-title">
+                    <span class="section-icon section-icon--removed">{icon}</span>
+                    <h2>Failed to Capture</h2>
+                    <span class="section-count">{count}</span>
+                </div>
+            </div>
+            <div class="section-content">
+                {items}
+            </div>
+        </section>
+        "#,
+            count = result.failed.len(),
+            items = items,
+            icon = icons::X_CIRCLE,
+        )
+    };
+
     let unchanged_html = if result.unchanged.is_empty() {
         String::new()
     } else {
@@ -485,11 +843,12 @@ fn generate_html(result: &DiffResult) -> String {
         )
     };
 
-    let has_issues =
-        !result.changed.is_empty() || !result.added.is_empty() || !result.removed.is_empty();
+    let has_issues = result.has_changes() || result.has_failures();
     let status_class = if has_issues { "fail" } else { "pass" };
-    let status_text = if has_issues {
+    let status_text = if result.has_changes() {
         "Visual changes detected"
+    } else if has_issues {
+        "Shots failed to capture"
     } else {
         "All tests passed"
     };
@@ -561,6 +920,33 @@ fn generate_html(result: &DiffResult) -> String {
         .badge--new{{background:var(--color-warning-muted);color:var(--color-warning)}}
         .badge--removed{{background:var(--color-info-muted);color:var(--color-info)}}
         .badge--viewport{{background:var(--color-bg-hover);color:var(--color-text-muted);font-size:10px;padding:3px 8px}}
+        .badge--emulation{{background:var(--color-bg-hover);color:var(--color-text-muted);font-size:10px;padding:3px 8px}}
+        .badge--browser{{background:var(--color-bg-hover);color:var(--color-text-muted);font-size:10px;padding:3px 8px;text-transform:capitalize}}
+        .badge--regions{{background:var(--color-info-muted);color:var(--color-info);font-size:10px;padding:3px 8px;text-transform:none}}
+        .region-outline{{position:absolute;pointer-events:none;border:2px dashed;border-radius:2px}}
+        .region-outline--ignored{{border-color:var(--color-info)}}
+        .region-outline--masked{{border-color:var(--color-warning)}}
+        .badge--unstable{{background:var(--color-warning-muted);color:var(--color-warning);font-size:10px;padding:3px 8px;text-transform:none}}
+        .badge--page-errors{{background:var(--color-error-muted);color:var(--color-error);font-size:10px;padding:3px 8px;text-transform:none}}
+        .failed-item{{padding:12px 20px;border-bottom:1px solid var(--color-border-subtle)}}
+        .failed-error{{margin:8px 0 0;font-family:var(--font-mono);font-size:12px;color:var(--color-error);white-space:pre-wrap;word-break:break-word}}
+        .failed-artifacts{{display:flex;gap:12px;margin-top:8px;font-size:12px}}
+        .failed-artifacts a{{color:var(--color-accent)}}
+        .page-errors-item{{padding:12px 20px;border-bottom:1px solid var(--color-border-subtle)}}
+        .page-errors-list{{margin:8px 0 0;padding-left:20px;font-family:var(--font-mono);font-size:12px;color:var(--color-text-muted);word-break:break-word}}
+        .page-error-kind{{font-weight:600;color:var(--color-error)}}
+        .badge--a11y{{background:var(--color-error-muted);color:var(--color-error);font-size:10px;padding:3px 8px;text-transform:none}}
+        .a11y-diff{{margin:0;padding:16px 20px;overflow-x:auto;font-family:var(--font-mono);font-size:12px;line-height:1.6;background:var(--color-bg)}}
+        .a11y-line--added{{color:var(--color-success)}}
+        .a11y-line--removed{{color:var(--color-error)}}
+        .dom-changes{{border-top:1px solid var(--color-border-subtle);background:var(--color-bg)}}
+        .dom-changes summary{{padding:12px 20px;font-size:13px;font-weight:500;color:var(--color-text-muted);cursor:pointer}}
+        .dom-changes-table{{width:100%;border-collapse:collapse;font-family:var(--font-mono);font-size:12px;line-height:1.6}}
+        .dom-changes-table td{{padding:6px 20px;border-top:1px solid var(--color-border-subtle);vertical-align:top}}
+        .dom-changes-table td:first-child{{width:40%;word-break:break-all}}
+        .dom-change--added code{{color:var(--color-success)}}
+        .dom-change--removed code{{color:var(--color-error)}}
+        .badge--size{{background:var(--color-warning-muted);color:var(--color-warning);font-size:10px;padding:3px 8px;text-transform:none}}
         .comparison-tabs{{display:flex;gap:4px;padding:12px 20px;border-bottom:1px solid var(--color-border-subtle);background:var(--color-bg)}}
         .tab-btn{{padding:8px 16px;border:none;border-radius:var(--radius-sm);background:transparent;color:var(--color-text-muted);font-size:13px;font-weight:500;cursor:pointer;transition:all 0.15s}}
         .tab-btn:hover{{color:var(--color-text);background:var(--color-bg-hover)}}
@@ -677,7 +1063,9 @@ fn generate_html(result: &DiffResult) -> String {
                 <button class="filter-btn" data-filter="changed">Changed</button>
                 <button class="filter-btn" data-filter="added">Added</button>
                 <button class="filter-btn" data-filter="removed">Removed</button>
+                <button class="filter-btn" data-filter="a11y-changed">Accessibility</button>
             </div>
+            {dimension_filters}
             <select class="sort-select" id="sort-select">
                 <option value="name">Sort by Name</option>
                 <option value="diff-desc">Sort by Diff % (High to Low)</option>
@@ -685,7 +1073,7 @@ fn generate_html(result: &DiffResult) -> String {
             </select>
             <button class="export-btn" id="export-decisions" title="Export decisions">{download_icon} Export</button>
         </div>
-        <main id="shots-container">{changed_html}{added_html}{removed_html}{unchanged_html}</main>
+        <main id="shots-container">{changed_html}{added_html}{removed_html}{a11y_html}{failed_html}{page_errors_html}{unchanged_html}</main>
         <footer class="footer">
             <span>Generated by Pixelguard</span>
             <a href="https://github.com/emiliodominguez/pixelguard" target="_blank">View on GitHub {external_link}</a>
@@ -703,9 +1091,10 @@ fn generate_html(result: &DiffResult) -> String {
     (function(){{const root=document.documentElement,btns=document.querySelectorAll('.theme-btn');function sys(){{return window.matchMedia('(prefers-color-scheme:dark)').matches?'dark':'light'}}function set(t){{root.setAttribute('data-theme',t==='system'?sys():t);btns.forEach(b=>b.classList.toggle('active',b.dataset.theme===t));localStorage.setItem('pg-theme',t)}}set(localStorage.getItem('pg-theme')||'system');btns.forEach(b=>b.addEventListener('click',()=>set(b.dataset.theme)));window.matchMedia('(prefers-color-scheme:dark)').addEventListener('change',()=>{{if(localStorage.getItem('pg-theme')==='system')set('system')}})}}());
     document.querySelectorAll('.shot-card').forEach(c=>{{const tabs=c.querySelectorAll('.tab-btn'),views={{'side-by-side':c.querySelector('.view-side-by-side'),'slider':c.querySelector('.view-slider'),'diff':c.querySelector('.view-diff')}};tabs.forEach(t=>t.addEventListener('click',()=>{{tabs.forEach(x=>x.classList.remove('active'));t.classList.add('active');Object.values(views).forEach(v=>v&&v.classList.remove('active'));const v=views[t.dataset.view];if(v){{v.classList.add('active');if(t.dataset.view==='slider')window.dispatchEvent(new Event('slider-shown'))}}}}))}});
     document.querySelectorAll('.slider-container').forEach(c=>{{const cur=c.querySelector('.slider-current'),h=c.querySelector('.slider-handle'),curImg=cur.querySelector('img');let drag=false;function setImgWidth(){{if(curImg&&c.offsetWidth>0)curImg.style.width=c.offsetWidth+'px'}}function upd(x){{const r=c.getBoundingClientRect(),p=Math.max(0,Math.min(100,((x-r.left)/r.width)*100));cur.style.width=p+'%';h.style.left=p+'%'}}c.addEventListener('mousedown',e=>{{drag=true;upd(e.clientX)}});document.addEventListener('mousemove',e=>{{if(drag)upd(e.clientX)}});document.addEventListener('mouseup',()=>drag=false);c.addEventListener('touchstart',e=>{{drag=true;upd(e.touches[0].clientX)}},{{passive:true}});c.addEventListener('touchmove',e=>{{if(drag){{upd(e.touches[0].clientX);e.preventDefault()}}}},{{passive:false}});c.addEventListener('touchend',()=>drag=false);setImgWidth();if(curImg)curImg.addEventListener('load',setImgWidth);window.addEventListener('resize',setImgWidth);window.addEventListener('slider-shown',()=>setTimeout(setImgWidth,10))}});
+    (function(){{function draw(f){{const img=f.querySelector('img');f.querySelectorAll('.region-outline').forEach(o=>o.remove());if(!img||!img.naturalWidth||!img.offsetWidth)return;const sx=img.offsetWidth/img.naturalWidth,sy=img.offsetHeight/img.naturalHeight;JSON.parse(f.dataset.regions).forEach(r=>{{const o=document.createElement('div');o.className='region-outline region-outline--'+r.kind;o.title=r.kind==='ignored'?'Ignored region':'Masked element';o.style.left=(img.offsetLeft+r.x*sx)+'px';o.style.top=(img.offsetTop+r.y*sy)+'px';o.style.width=(r.width*sx)+'px';o.style.height=(r.height*sy)+'px';f.appendChild(o)}})}}function drawAll(){{document.querySelectorAll('[data-regions]').forEach(draw)}}document.querySelectorAll('[data-regions]').forEach(f=>{{const img=f.querySelector('img');if(img)img.addEventListener('load',()=>draw(f))}});window.addEventListener('resize',drawAll);document.querySelectorAll('.tab-btn').forEach(t=>t.addEventListener('click',()=>setTimeout(drawAll,10)));drawAll()}})();
     (function(){{const m=document.getElementById('modal'),img=document.getElementById('modal-image'),lbl=document.getElementById('modal-label'),cls=document.getElementById('modal-close');function open(s,l){{img.src=s;lbl.textContent=l;m.classList.add('active');document.body.style.overflow='hidden'}}function close(){{m.classList.remove('active');document.body.style.overflow=''}}document.querySelectorAll('[data-zoomable]').forEach(el=>el.addEventListener('click',()=>open(el.dataset.src,el.dataset.label)));cls.addEventListener('click',close);m.addEventListener('click',e=>{{if(e.target===m)close()}});document.addEventListener('keydown',e=>{{if(e.key==='Escape')close()}})}})();
-    (function(){{const searchInput=document.getElementById('search-input'),sortSelect=document.getElementById('sort-select'),filterBtns=document.querySelectorAll('.filter-btn'),container=document.getElementById('shots-container');let currentFilter='all',currentSearch='';function filterShots(){{const cards=document.querySelectorAll('.shot-card');let visibleCount=0;cards.forEach(card=>{{const name=card.dataset.name.toLowerCase(),status=card.dataset.status,matchesSearch=!currentSearch||name.includes(currentSearch.toLowerCase()),matchesFilter=currentFilter==='all'||status===currentFilter;card.style.display=matchesSearch&&matchesFilter?'':'none';if(matchesSearch&&matchesFilter)visibleCount++}});document.querySelectorAll('.section').forEach(sec=>{{const visible=sec.querySelectorAll('.shot-card:not([style*="display: none"])');sec.style.display=visible.length?'':'none'}});const noResults=document.getElementById('no-results');if(noResults)noResults.style.display=visibleCount===0?'block':'none'}}function sortShots(){{const sections=document.querySelectorAll('.section-content');sections.forEach(section=>{{const cards=[...section.querySelectorAll('.shot-card')];const sortVal=sortSelect.value;cards.sort((a,b)=>{{if(sortVal==='diff-desc')return parseFloat(b.dataset.diff)-parseFloat(a.dataset.diff);if(sortVal==='diff-asc')return parseFloat(a.dataset.diff)-parseFloat(b.dataset.diff);return a.dataset.name.localeCompare(b.dataset.name)}});cards.forEach(card=>section.appendChild(card))}})}}searchInput.addEventListener('input',e=>{{currentSearch=e.target.value;filterShots()}});filterBtns.forEach(btn=>btn.addEventListener('click',()=>{{filterBtns.forEach(b=>b.classList.remove('active'));btn.classList.add('active');currentFilter=btn.dataset.filter;filterShots()}}));sortSelect.addEventListener('change',sortShots)}})();
-    (function(){{let decisions={{}};let serverMode=false;const exportBtn=document.getElementById('export-decisions');async function init(){{try{{const resp=await fetch('/api/decisions');if(resp.ok){{const data=await resp.json();decisions=data.decisions||{{}};serverMode=true;exportBtn.innerHTML='\u2713 Auto-saving';exportBtn.title='Decisions are automatically saved to disk';exportBtn.style.cursor='default';exportBtn.style.borderColor='var(--color-success)';exportBtn.style.color='var(--color-success)';console.log('[pixelguard] Server mode enabled - decisions will auto-save')}}else{{throw new Error('API returned '+resp.status)}}}}catch(e){{decisions=JSON.parse(localStorage.getItem('pg-decisions')||'{{}}');serverMode=false;console.log('[pixelguard] Offline mode - use Export button. Error:',e.message)}}updateUI()}}function updateUI(){{document.querySelectorAll('.shot-card[data-status="changed"]').forEach(card=>{{const name=card.dataset.name,decision=decisions[name];card.classList.remove('decision-approved','decision-rejected');card.querySelectorAll('.action-btn').forEach(b=>b.classList.remove('active'));if(decision){{card.classList.add('decision-'+decision.action+'d');card.querySelector('.action-btn--'+decision.action)?.classList.add('active')}}}})}};async function saveDecisions(){{const data={{version:'1.0',exportedAt:new Date().toISOString(),decisions:decisions}};if(serverMode){{const resp=await fetch('/api/decisions',{{method:'POST',headers:{{'Content-Type':'application/json'}},body:JSON.stringify(data)}});return resp.ok}}else{{localStorage.setItem('pg-decisions',JSON.stringify(decisions));return true}}}}async function makeDecision(name,action){{if(decisions[name]&&decisions[name].action===action){{delete decisions[name]}}else{{decisions[name]={{action:action,timestamp:new Date().toISOString(),source:'browser'}}}}if(serverMode)await saveDecisions();else localStorage.setItem('pg-decisions',JSON.stringify(decisions));updateUI()}}document.querySelectorAll('.action-btn').forEach(btn=>{{btn.addEventListener('click',e=>{{e.stopPropagation();makeDecision(btn.dataset.shot,btn.dataset.action)}})}});exportBtn.addEventListener('click',async()=>{{if(!serverMode){{const data={{version:'1.0',exportedAt:new Date().toISOString(),decisions:decisions}};const blob=new Blob([JSON.stringify(data,null,2)],{{type:'application/json'}});const url=URL.createObjectURL(blob);const a=document.createElement('a');a.href=url;a.download='pixelguard-decisions.json';a.click();URL.revokeObjectURL(url)}}}});init()}})();
+    (function(){{const searchInput=document.getElementById('search-input'),sortSelect=document.getElementById('sort-select'),filterBtns=document.querySelectorAll('.filter-btn'),dimSelects=document.querySelectorAll('.dimension-filter'),container=document.getElementById('shots-container');let currentFilter='all',currentSearch='';function filterShots(){{const cards=document.querySelectorAll('.shot-card');let visibleCount=0;cards.forEach(card=>{{const name=card.dataset.name.toLowerCase(),status=card.dataset.status,matchesSearch=!currentSearch||name.includes(currentSearch.toLowerCase()),matchesFilter=(currentFilter==='all'||status===currentFilter)&&[...dimSelects].every(s=>!s.value||card.dataset[s.dataset.dimension]===s.value);card.style.display=matchesSearch&&matchesFilter?'':'none';if(matchesSearch&&matchesFilter)visibleCount++}});document.querySelectorAll('.section').forEach(sec=>{{const visible=sec.querySelectorAll('.shot-card:not([style*="display: none"])');sec.style.display=visible.length?'':'none'}});const noResults=document.getElementById('no-results');if(noResults)noResults.style.display=visibleCount===0?'block':'none'}}function sortShots(){{const sections=document.querySelectorAll('.section-content');sections.forEach(section=>{{const cards=[...section.querySelectorAll('.shot-card')];const sortVal=sortSelect.value;cards.sort((a,b)=>{{if(sortVal==='diff-desc')return parseFloat(b.dataset.diff)-parseFloat(a.dataset.diff);if(sortVal==='diff-asc')return parseFloat(a.dataset.diff)-parseFloat(b.dataset.diff);return a.dataset.name.localeCompare(b.dataset.name)}});cards.forEach(card=>section.appendChild(card))}})}}searchInput.addEventListener('input',e=>{{currentSearch=e.target.value;filterShots()}});filterBtns.forEach(btn=>btn.addEventListener('click',()=>{{filterBtns.forEach(b=>b.classList.remove('active'));btn.classList.add('active');currentFilter=btn.dataset.filter;filterShots()}}));dimSelects.forEach(s=>s.addEventListener('change',filterShots));sortSelect.addEventListener('change',sortShots)}})();
+    (function(){{let decisions={{}};let serverMode=false;const exportBtn=document.getElementById('export-decisions');async function init(){{try{{const resp=await fetch('/api/decisions');if(resp.ok){{const data=await resp.json();decisions=data.decisions||{{}};serverMode=true;exportBtn.innerHTML='\u2713 Auto-saving';exportBtn.title='Decisions are automatically saved to disk';exportBtn.style.cursor='default';exportBtn.style.borderColor='var(--color-success)';exportBtn.style.color='var(--color-success)';console.log('[pixelguard] Server mode enabled - decisions will auto-save')}}else{{throw new Error('API returned '+resp.status)}}}}catch(e){{decisions=JSON.parse(localStorage.getItem('pg-decisions')||'{{}}');serverMode=false;console.log('[pixelguard] Offline mode - use Export button. Error:',e.message)}}updateUI()}}function updateUI(){{document.querySelectorAll('.shot-card[data-status="changed"],.shot-card[data-status="a11y-changed"]').forEach(card=>{{const name=card.dataset.name,decision=decisions[name];card.classList.remove('decision-approved','decision-rejected');card.querySelectorAll('.action-btn').forEach(b=>b.classList.remove('active'));if(decision){{card.classList.add('decision-'+decision.action+'d');card.querySelector('.action-btn--'+decision.action)?.classList.add('active')}}}})}};async function saveDecisions(){{const data={{version:'1.0',exportedAt:new Date().toISOString(),decisions:decisions}};if(serverMode){{const resp=await fetch('/api/decisions',{{method:'POST',headers:{{'Content-Type':'application/json'}},body:JSON.stringify(data)}});return resp.ok}}else{{localStorage.setItem('pg-decisions',JSON.stringify(decisions));return true}}}}async function makeDecision(name,action){{if(decisions[name]&&decisions[name].action===action){{delete decisions[name]}}else{{decisions[name]={{action:action,timestamp:new Date().toISOString(),source:'browser'}}}}if(serverMode)await saveDecisions();else localStorage.setItem('pg-decisions',JSON.stringify(decisions));updateUI()}}document.querySelectorAll('.action-btn').forEach(btn=>{{btn.addEventListener('click',e=>{{e.stopPropagation();makeDecision(btn.dataset.shot,btn.dataset.action)}})}});exportBtn.addEventListener('click',async()=>{{if(!serverMode){{const data={{version:'1.0',exportedAt:new Date().toISOString(),decisions:decisions}};const blob=new Blob([JSON.stringify(data,null,2)],{{type:'application/json'}});const url=URL.createObjectURL(blob);const a=document.createElement('a');a.href=url;a.download='pixelguard-decisions.json';a.click();URL.revokeObjectURL(url)}}}});init()}})();
     </script>
 </body>
 </html>
@@ -721,6 +1110,9 @@ fn generate_html(result: &DiffResult) -> String {
         changed_html = changed_html,
         added_html = added_html,
         removed_html = removed_html,
+        a11y_html = a11y_html,
+        failed_html = failed_html,
+        page_errors_html = page_errors_html,
         unchanged_html = unchanged_html,
         logo = icons::LOGO,
         check_icon = icons::CHECK_CIRCLE,
@@ -734,6 +1126,194 @@ fn generate_html(result: &DiffResult) -> String {
         x_icon_large = icons::X,
         search_icon = icons::SEARCH,
         download_icon = icons::DOWNLOAD,
+        dimension_filters = dimension_filters(result),
+    )
+}
+
+/// Returns a badge for each emulated dimension of a changed shot.
+fn emulation_badges(emulation: &Emulation) -> String {
+    let values = [
+        ("colorScheme", emulation.color_scheme.map(|c| c.as_str())),
+        (
+            "reducedMotion",
+            emulation.reduced_motion.map(|m| m.as_str()),
+        ),
+        ("locale", emulation.locale.as_deref()),
+        ("timezoneId", emulation.timezone_id.as_deref()),
+        ("media", emulation.media.map(|m| m.as_str())),
+    ];
+    values
+        .iter()
+        .filter_map(|(key, value)| {
+            value.map(|v| {
+                format!(
+                    r#"<span class="badge badge--emulation" title="{}">{}</span>"#,
+                    key,
+                    html_escape(v)
+                )
+            })
+        })
+        .collect()
+}
+
+/// Returns `data-*` attributes for the matrix dimensions encoded in a shot name,
+/// used by the report's dimension filters.
+fn dimension_attrs(name: &str) -> String {
+    shot_dimensions(name)
+        .into_iter()
+        .map(|(key, value)| format!(r#" data-{}="{}""#, kebab_case(key), html_escape(value)))
+        .collect()
+}
+
+/// Returns a filter dropdown for each dimension that has values in the report.
+fn dimension_filters(result: &DiffResult) -> String {
+    let mut dimensions: Vec<(&str, Vec<&str>)> = Vec::new();
+    let names = result
+        .changed
+        .iter()
+        .map(|shot| shot.name.as_str())
+        .chain(result.added.iter().map(String::as_str))
+        .chain(result.removed.iter().map(String::as_str));
+    for name in names {
+        for (key, value) in shot_dimensions(name) {
+            match dimensions.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, values)) => {
+                    if !values.contains(&value) {
+                        values.push(value);
+                    }
+                }
+                None => dimensions.push((key, vec![value])),
+            }
+        }
+    }
+
+    dimensions
+        .into_iter()
+        .map(|(key, mut values)| {
+            values.sort_unstable();
+            let options: String = values
+                .iter()
+                .map(|v| format!(r#"<option value="{0}">{0}</option>"#, html_escape(v)))
+                .collect();
+            format!(
+                r#"<select class="sort-select dimension-filter" data-dimension="{key}" title="Filter by {key}"><option value="">All {key}</option>{options}</select>"#
+            )
+        })
+        .collect()
+}
+
+/// Converts a camelCase config key to the kebab-case used in `data-*` attributes.
+fn kebab_case(key: &str) -> String {
+    let mut out = String::with_capacity(key.len() + 2);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('-');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Returns a badge summarizing the ignored and masked regions of a changed shot.
+fn regions_badge(shot: &ChangedShot) -> String {
+    let mut parts = Vec::new();
+    if !shot.ignored_regions.is_empty() {
+        parts.push(format!("{} ignored", shot.ignored_regions.len()));
+    }
+    if !shot.masked_regions.is_empty() {
+        parts.push(format!("{} masked", shot.masked_regions.len()));
+    }
+    if parts.is_empty() {
+        return String::new();
+    }
+    format!(
+        r#"<span class="badge badge--regions" title="Regions excluded from comparison are outlined">{}</span>"#,
+        parts.join(" · ")
+    )
+}
+
+/// Returns a badge for a screenshot that never stopped changing during capture.
+fn unstable_badge(result: &DiffResult, name: &str) -> String {
+    match result.stability.get(name) {
+        Some(stability) if !stability.stable => format!(
+            r#"<span class="badge badge--unstable" title="Still changing after {} screenshots in {}ms">Unstable</span>"#,
+            stability.attempts, stability.duration_ms
+        ),
+        _ => String::new(),
+    }
+}
+
+/// Returns a badge for a screenshot whose page logged errors during capture.
+fn page_errors_badge(result: &DiffResult, name: &str) -> String {
+    match result.page_errors.get(name) {
+        Some(errors) if !errors.is_empty() => format!(
+            r#"<span class="badge badge--page-errors" title="Console errors, exceptions and failed requests are listed under Page Errors">{} page error{}</span>"#,
+            errors.len(),
+            if errors.len() == 1 { "" } else { "s" }
+        ),
+        _ => String::new(),
+    }
+}
+
+/// Returns a collapsible table of the elements that moved, resized or changed style.
+fn dom_changes_html(shot: &ChangedShot) -> String {
+    if shot.dom_changes.is_empty() {
+        return String::new();
+    }
+    let rows: String = shot
+        .dom_changes
+        .iter()
+        .map(|change| {
+            let details: Vec<String> = change.details().iter().map(|d| html_escape(d)).collect();
+            format!(
+                r#"<tr class="dom-change dom-change--{kind}"><td><code>{path}</code></td><td>{details}</td></tr>"#,
+                kind = match change.kind {
+                    DomChangeKind::Changed => "changed",
+                    DomChangeKind::Added => "added",
+                    DomChangeKind::Removed => "removed",
+                },
+                path = html_escape(&change.path),
+                details = details.join("<br>"),
+            )
+        })
+        .collect();
+    let count = shot.dom_changes.len();
+    format!(
+        r#"<details class="dom-changes">
+                    <summary>{count} element{plural} changed</summary>
+                    <table class="dom-changes-table">{rows}</table>
+                </details>"#,
+        count = count,
+        plural = if count == 1 { "" } else { "s" },
+        rows = rows,
+    )
+}
+
+/// Returns a `data-regions` attribute listing the regions to outline over the images.
+fn regions_attr(shot: &ChangedShot) -> String {
+    let regions: Vec<serde_json::Value> = shot
+        .ignored_regions
+        .iter()
+        .map(|r| (r, "ignored"))
+        .chain(shot.masked_regions.iter().map(|r| (r, "masked")))
+        .map(|(r, kind)| {
+            serde_json::json!({
+                "kind": kind,
+                "x": r.x,
+                "y": r.y,
+                "width": r.width,
+                "height": r.height,
+            })
+        })
+        .collect();
+    if regions.is_empty() {
+        return String::new();
+    }
+    format!(
+        r#" data-regions="{}""#,
+        html_escape(&serde_json::Value::from(regions).to_string())
     )
 }
 
@@ -749,7 +1329,6 @@ fn html_escape(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::diff::ChangedShot;
 
     #[test]
     fn html_escape_handles_special_chars() {
@@ -769,9 +1348,14 @@ mod tests {
                 diff_path: "diff/card--default.png".into(),
                 diff_percentage: 5.5,
                 viewport: None,
+                ..Default::default()
             }],
             added: vec!["new-component".to_string()],
             removed: vec!["old-component".to_string()],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
         };
 
         let html = generate_html(&result);
@@ -796,9 +1380,14 @@ mod tests {
                 diff_path: "diff/card--default@mobile.png".into(),
                 diff_percentage: 5.5,
                 viewport: Some("mobile".to_string()),
+                ..Default::default()
             }],
             added: vec![],
             removed: vec![],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
         };
 
         let html = generate_html(&result);
@@ -807,6 +1396,359 @@ mod tests {
         assert!(html.contains("mobile"));
     }
 
+    #[test]
+    fn generate_html_shows_browser_badge() {
+        let result = DiffResult {
+            unchanged: vec![],
+            changed: vec![ChangedShot {
+                name: "card--default@mobile~webkit".to_string(),
+                diff_percentage: 5.5,
+                viewport: Some("mobile".to_string()),
+                browser: Some("webkit".to_string()),
+                ..Default::default()
+            }],
+            added: vec![],
+            removed: vec![],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
+        };
+
+        let html = generate_html(&result);
+
+        assert!(html.contains(r#"<span class="badge badge--browser">webkit</span>"#));
+        assert!(html.contains(r#"data-browser="webkit""#));
+
+        let json = serde_json::to_value(generate_results_json(&result)).unwrap();
+        assert_eq!(json["results"]["changed"][0]["browser"], "webkit");
+        assert_eq!(json["results"]["changed"][0]["viewport"], "mobile");
+    }
+
+    #[test]
+    fn generate_html_filters_by_emulation_dimensions() {
+        let result = DiffResult {
+            unchanged: vec![],
+            changed: vec![ChangedShot {
+                name: "card+scheme-dark+tz-Europe-Berlin".to_string(),
+                diff_percentage: 1.5,
+                emulation: Emulation {
+                    color_scheme: Some(crate::config::ColorScheme::Dark),
+                    timezone_id: Some("Europe/Berlin".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            added: vec!["card+scheme-light+tz-Europe-Berlin".to_string()],
+            removed: vec![],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
+        };
+
+        let html = generate_html(&result);
+
+        assert!(html.contains(
+            r#"<span class="badge badge--emulation" title="timezoneId">Europe/Berlin</span>"#
+        ));
+        assert!(html.contains(r#"data-color-scheme="dark" data-timezone-id="Europe-Berlin""#));
+        assert!(html.contains(r#"data-dimension="colorScheme""#));
+        assert!(html
+            .contains(r#"<option value="dark">dark</option><option value="light">light</option>"#));
+
+        let json = serde_json::to_value(generate_results_json(&result)).unwrap();
+        assert_eq!(json["results"]["changed"][0]["colorScheme"], "dark");
+        assert_eq!(json["results"]["changed"][0]["timezoneId"], "Europe/Berlin");
+        assert!(json["results"]["changed"][0].get("locale").is_none());
+    }
+
+    #[test]
+    fn generate_html_shows_size_badge() {
+        let result = DiffResult {
+            unchanged: vec![],
+            changed: vec![ChangedShot {
+                name: "button--primary".to_string(),
+                diff_percentage: 9.1,
+                size_change: Some(SizeChange {
+                    baseline_width: 100,
+                    baseline_height: 40,
+                    current_width: 110,
+                    current_height: 40,
+                }),
+                ..Default::default()
+            }],
+            added: vec![],
+            removed: vec![],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
+        };
+
+        let html = generate_html(&result);
+
+        assert!(html.contains("badge--size"));
+        assert!(html.contains("100x40 \u{2192} 110x40"));
+
+        let json = serde_json::to_value(generate_results_json(&result)).unwrap();
+        assert_eq!(
+            json["results"]["changed"][0]["sizeChange"]["currentWidth"],
+            110
+        );
+    }
+
+    #[test]
+    fn generate_html_outlines_excluded_regions() {
+        let region = Region {
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 20,
+        };
+        let result = DiffResult {
+            unchanged: vec![],
+            changed: vec![ChangedShot {
+                name: "feed".to_string(),
+                diff_percentage: 2.0,
+                ignored_regions: vec![region],
+                masked_regions: vec![region, region],
+                ..Default::default()
+            }],
+            added: vec![],
+            removed: vec![],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
+        };
+
+        let html = generate_html(&result);
+
+        assert!(html.contains("1 ignored · 2 masked"));
+        assert!(html.contains(
+            r#"data-regions="[{&quot;height&quot;:20,&quot;kind&quot;:&quot;ignored&quot;"#
+        ));
+
+        let json = serde_json::to_value(generate_results_json(&result)).unwrap();
+        assert_eq!(
+            json["results"]["changed"][0]["maskedRegions"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn unstable_shots_are_flagged_in_html_and_json() {
+        let result = DiffResult {
+            unchanged: vec!["static".to_string()],
+            changed: vec![],
+            added: vec!["chart".to_string()],
+            removed: vec![],
+            stability: BTreeMap::from([
+                (
+                    "chart".to_string(),
+                    Stability {
+                        attempts: 40,
+                        duration_ms: 5012,
+                        stable: false,
+                    },
+                ),
+                (
+                    "static".to_string(),
+                    Stability {
+                        attempts: 2,
+                        duration_ms: 104,
+                        stable: true,
+                    },
+                ),
+            ]),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
+        };
+
+        let html = generate_html(&result);
+        assert!(html.contains(
+            r#"<span class="badge badge--unstable" title="Still changing after 40 screenshots in 5012ms">Unstable</span>"#
+        ));
+
+        let json = serde_json::to_value(generate_results_json(&result)).unwrap();
+        assert_eq!(json["summary"]["unstable"], 1);
+        assert_eq!(json["results"]["unstable"], serde_json::json!(["chart"]));
+        assert_eq!(
+            json["results"]["stability"]["static"],
+            serde_json::json!({ "attempts": 2, "durationMs": 104, "stable": true })
+        );
+    }
+
+    #[test]
+    fn page_errors_are_listed_in_html_and_json() {
+        let result = DiffResult {
+            unchanged: vec![],
+            changed: vec![],
+            added: vec!["form".to_string()],
+            removed: vec![],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::from([(
+                "form".to_string(),
+                PageErrors {
+                    console: vec!["Warning: <div> cannot appear inside <p>".to_string()],
+                    exceptions: vec![],
+                    requests: vec!["GET http://localhost/api/user: 500".to_string()],
+                },
+            )]),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
+        };
+
+        let html = generate_html(&result);
+        assert!(html.contains(">2 page errors</span>"));
+        assert!(html.contains("<h2>Page Errors</h2>"));
+        assert!(html.contains(
+            r#"<span class="page-error-kind">console</span> Warning: &lt;div&gt; cannot appear inside &lt;p&gt;"#
+        ));
+        assert!(html.contains(
+            r#"<span class="page-error-kind">request</span> GET http://localhost/api/user: 500"#
+        ));
+
+        let json = serde_json::to_value(generate_results_json(&result)).unwrap();
+        assert_eq!(
+            json["results"]["pageErrors"]["form"]["requests"],
+            serde_json::json!(["GET http://localhost/api/user: 500"])
+        );
+        assert!(json["results"]["pageErrors"]["form"]
+            .get("exceptions")
+            .is_none());
+    }
+
+    #[test]
+    fn failed_shots_link_to_their_artifacts() {
+        let result = DiffResult {
+            unchanged: vec!["home".to_string()],
+            changed: vec![],
+            added: vec![],
+            removed: vec![],
+            a11y_changed: Vec::new(),
+            failed: vec![FailedShot {
+                name: "checkout@mobile".to_string(),
+                error: "Timeout 10000ms exceeded".to_string(),
+                duration_ms: Some(10_250),
+                page_errors: PageErrors::default(),
+                artifacts: vec![
+                    "failures/checkout@mobile/page.html".to_string(),
+                    "failures/checkout@mobile/trace.zip".to_string(),
+                ],
+            }],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+        };
+
+        let html = generate_html(&result);
+        assert!(html.contains("<h2>Failed to Capture</h2>"));
+        assert!(html.contains(r#"<pre class="failed-error">Timeout 10000ms exceeded</pre>"#));
+        assert!(html.contains(
+            r#"<a href="failures/checkout@mobile/page.html" target="_blank">page.html</a>"#
+        ));
+        assert!(html.contains(
+            r#"<a href="failures/checkout@mobile/trace.zip" target="_blank">trace.zip</a>"#
+        ));
+
+        let json = serde_json::to_value(generate_results_json(&result)).unwrap();
+        assert_eq!(json["summary"]["failed"], 1);
+        assert_eq!(json["results"]["failed"][0]["name"], "checkout@mobile");
+        assert_eq!(
+            json["results"]["failed"][0]["artifacts"][1],
+            "failures/checkout@mobile/trace.zip"
+        );
+    }
+
+    #[test]
+    fn a11y_changes_are_reviewable_in_html_and_json() {
+        let result = DiffResult {
+            unchanged: vec![],
+            changed: vec![],
+            added: vec![],
+            removed: vec![],
+            a11y_changed: vec![A11yChangedShot {
+                name: "form".to_string(),
+                changes: vec![crate::a11y::A11yChange {
+                    kind: A11yChangeKind::Removed,
+                    depth: 1,
+                    node: r#"textbox "Email""#.to_string(),
+                }],
+            }],
+            failed: Vec::new(),
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+        };
+
+        let html = generate_html(&result);
+        assert!(html.contains("<h2>Accessibility Changed</h2>"));
+        assert!(html.contains(r#"data-status="a11y-changed""#));
+        assert!(html.contains(r#"data-shot="form" data-action="approve""#));
+        assert!(html.contains(
+            r#"<span class="a11y-line a11y-line--removed">-   textbox &quot;Email&quot;</span>"#
+        ));
+        assert!(html.contains("Visual changes detected"));
+
+        let json = serde_json::to_value(generate_results_json(&result)).unwrap();
+        assert_eq!(json["summary"]["a11yChanged"], 1);
+        assert_eq!(json["summary"]["total"], 1);
+        assert_eq!(json["summary"]["passed"], false);
+        assert_eq!(
+            json["results"]["a11yChanged"][0]["changes"][0],
+            serde_json::json!({ "kind": "removed", "depth": 1, "node": "textbox \"Email\"" })
+        );
+    }
+
+    #[test]
+    fn dom_changes_are_listed_on_the_card_and_in_json() {
+        let result = DiffResult {
+            unchanged: vec![],
+            changed: vec![ChangedShot {
+                name: "card".to_string(),
+                diff_percentage: 3.0,
+                dom_changes: vec![DomChange {
+                    path: "body > div.card".to_string(),
+                    kind: DomChangeKind::Changed,
+                    baseline_box: None,
+                    current_box: None,
+                    properties: vec![crate::dom::PropertyChange {
+                        property: "padding-top".to_string(),
+                        baseline: "16px".to_string(),
+                        current: "24px".to_string(),
+                    }],
+                }],
+                ..Default::default()
+            }],
+            added: vec![],
+            removed: vec![],
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+        };
+
+        let html = generate_html(&result);
+        assert!(html.contains("<summary>1 element changed</summary>"));
+        assert!(html.contains(
+            r#"<tr class="dom-change dom-change--changed"><td><code>body &gt; div.card</code></td><td>padding-top: 16px → 24px</td></tr>"#
+        ));
+
+        let json = serde_json::to_value(generate_results_json(&result)).unwrap();
+        assert_eq!(
+            json["results"]["changed"][0]["domChanges"][0],
+            serde_json::json!({
+                "path": "body > div.card",
+                "kind": "changed",
+                "properties": [{ "property": "padding-top", "baseline": "16px", "current": "24px" }]
+            })
+        );
+    }
+
     #[test]
     fn generate_html_handles_empty_results() {
         let result = DiffResult {
@@ -814,6 +1756,10 @@ mod tests {
             changed: vec![],
             added: vec![],
             removed: vec![],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
         };
 
         let html = generate_html(&result);
@@ -837,6 +1783,10 @@ mod tests {
             changed: vec![],
             added: vec![],
             removed: vec![],
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
         };
 
         let report_path = generate_report(&config, &result, dir.path()).unwrap();
@@ -0,0 +1,438 @@
+//! Splitting a test run across CI jobs.
+//!
+//! A [`Shard`] (`--shard 2/4`) owns a fixed subset of the shot × viewport
+//! combinations. Each combination is assigned by a stable hash of its name, so
+//! every job agrees on the split without coordinating, and a baseline whose shot
+//! was deleted still belongs to exactly one shard. [`merge_results`] combines the
+//! shards' output directories into one.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::a11y;
+use crate::capture::{
+    load_capture_stats, load_masked_regions, load_page_errors, CAPTURE_STATS_FILE,
+    MASKED_REGIONS_FILE, PAGE_ERRORS_FILE,
+};
+use crate::diff::DiffResult;
+use crate::dom;
+use crate::report::ResultsJson;
+
+/// One part of a sharded test run, e.g. `2/4`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shard {
+    /// 1-based index of this shard
+    pub index: usize,
+    /// Number of shards the run is split into
+    pub total: usize,
+}
+
+impl Shard {
+    /// Returns true if the screenshots of `shot` at `viewport` belong to this shard.
+    ///
+    /// Every browser and emulation of a shot × viewport combination stays together.
+    /// The single-viewport case uses the viewport name `"default"`.
+    pub fn contains(&self, shot: &str, viewport: &str) -> bool {
+        let key = format!("{}@{}", shot, viewport);
+        (fnv1a(key.as_bytes()) % self.total as u64) as usize + 1 == self.index
+    }
+}
+
+impl FromStr for Shard {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            anyhow::anyhow!(
+                "invalid shard '{}': expected <index>/<total> with 1 <= index <= total, e.g. 2/4",
+                s
+            )
+        };
+        let (index, total) = s.split_once('/').ok_or_else(invalid)?;
+        let index: usize = index.trim().parse().map_err(|_| invalid())?;
+        let total: usize = total.trim().parse().map_err(|_| invalid())?;
+        if index == 0 || index > total {
+            return Err(invalid());
+        }
+        Ok(Self { index, total })
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.total)
+    }
+}
+
+/// 64-bit FNV-1a, used because its output is fixed across platforms and Rust versions.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Results of one input directory to [`merge_results`].
+#[derive(Debug)]
+pub struct MergedShard {
+    /// The shard's output directory
+    pub dir: PathBuf,
+    /// The shard it ran as, None for an unsharded run
+    pub shard: Option<Shard>,
+    /// Number of screenshots it compared
+    pub total: usize,
+}
+
+/// Combines the output directories of sharded runs into `output_dir`.
+///
+/// Reads each directory's `results.json`, copies its current and diff images
+/// (plus masked regions and capture stats) into `output_dir`, and returns the
+/// combined diff result for report generation. Fails if a shard is missing or
+/// given twice, or if two inputs report the same screenshot.
+pub fn merge_results(
+    output_dir: &Path,
+    shard_dirs: &[PathBuf],
+) -> Result<(DiffResult, Vec<MergedShard>)> {
+    let mut inputs = Vec::with_capacity(shard_dirs.len());
+    for dir in shard_dirs {
+        inputs.push((dir, ResultsJson::load(dir)?));
+    }
+    check_shards(&inputs)?;
+
+    let current_dir = output_dir.join("current");
+    let diff_dir = output_dir.join("diff");
+    std::fs::create_dir_all(&current_dir)?;
+    std::fs::create_dir_all(&diff_dir)?;
+
+    let mut merged = DiffResult {
+        unchanged: Vec::new(),
+        changed: Vec::new(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        stability: BTreeMap::new(),
+        page_errors: BTreeMap::new(),
+        a11y_changed: Vec::new(),
+        failed: Vec::new(),
+    };
+    let mut masked_regions = load_masked_regions(&current_dir);
+    let mut capture_stats = load_capture_stats(&current_dir);
+    let mut page_errors = load_page_errors(&current_dir);
+    let mut seen: HashMap<String, &Path> = HashMap::new();
+    let mut shards = Vec::with_capacity(inputs.len());
+
+    for (dir, results) in inputs {
+        let shard = results.shard;
+        let total = results.summary.total;
+        let diff = results.into_diff_result(output_dir);
+
+        // Screenshots with a current image, then removed ones without
+        let captured: HashSet<&str> = diff
+            .unchanged
+            .iter()
+            .chain(&diff.added)
+            .chain(diff.changed.iter().map(|c| &c.name))
+            .chain(diff.a11y_changed.iter().map(|c| &c.name))
+            .map(String::as_str)
+            .collect();
+        for name in captured
+            .iter()
+            .copied()
+            .chain(diff.removed.iter().map(String::as_str))
+        {
+            if let Some(other) = seen.insert(name.to_string(), dir) {
+                anyhow::bail!(
+                    "❌ '{}' is reported by both {} and {}.\n\n\
+                     💡 Solution: Merge the output of one sharded run, with each shard once.",
+                    name,
+                    other.display(),
+                    dir.display()
+                );
+            }
+        }
+
+        if !same_path(dir, output_dir) {
+            let shard_current = dir.join("current");
+            for name in &captured {
+                copy_image(&shard_current, &current_dir, name)?;
+                for snapshot_path in [a11y::snapshot_path, dom::snapshot_path] {
+                    let snapshot = snapshot_path(&shard_current, name);
+                    if snapshot.exists() {
+                        std::fs::copy(&snapshot, snapshot_path(&current_dir, name))
+                            .with_context(|| format!("Failed to copy {}", snapshot.display()))?;
+                    }
+                }
+            }
+            for changed in &diff.changed {
+                copy_image(&dir.join("diff"), &diff_dir, &changed.name)?;
+            }
+            // Failure artifacts are listed relative to the output directory
+            for artifact in diff.failed.iter().flat_map(|shot| &shot.artifacts) {
+                let source = dir.join(artifact);
+                let target = output_dir.join(artifact);
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::copy(&source, &target)
+                    .with_context(|| format!("Failed to copy {}", source.display()))?;
+            }
+
+            masked_regions.extend(
+                load_masked_regions(&shard_current)
+                    .into_iter()
+                    .filter(|(name, _)| captured.contains(name.as_str())),
+            );
+            capture_stats.extend(
+                load_capture_stats(&shard_current)
+                    .into_iter()
+                    .filter(|(name, _)| captured.contains(name.as_str())),
+            );
+            page_errors.extend(
+                load_page_errors(&shard_current)
+                    .into_iter()
+                    .filter(|(name, _)| captured.contains(name.as_str())),
+            );
+        }
+
+        shards.push(MergedShard {
+            dir: dir.clone(),
+            shard,
+            total,
+        });
+        merged.unchanged.extend(diff.unchanged);
+        merged.changed.extend(diff.changed);
+        merged.added.extend(diff.added);
+        merged.removed.extend(diff.removed);
+        merged.a11y_changed.extend(diff.a11y_changed);
+        merged.failed.extend(diff.failed);
+        merged.stability.extend(diff.stability);
+        merged.page_errors.extend(diff.page_errors);
+    }
+
+    write_map(&current_dir.join(MASKED_REGIONS_FILE), &masked_regions)?;
+    write_map(&current_dir.join(CAPTURE_STATS_FILE), &capture_stats)?;
+    write_map(&current_dir.join(PAGE_ERRORS_FILE), &page_errors)?;
+
+    merged.unchanged.sort();
+    merged.added.sort();
+    merged.removed.sort();
+    merged.changed.sort_by(|a, b| a.name.cmp(&b.name));
+    merged.a11y_changed.sort_by(|a, b| a.name.cmp(&b.name));
+    merged.failed.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok((merged, shards))
+}
+
+/// Checks that sharded inputs are the complete set of one run's shards.
+fn check_shards(inputs: &[(&PathBuf, ResultsJson)]) -> Result<()> {
+    let shards: Vec<(&PathBuf, Shard)> = inputs
+        .iter()
+        .filter_map(|(dir, results)| Some((*dir, results.shard?)))
+        .collect();
+    let Some((_, first)) = shards.first() else {
+        return Ok(());
+    };
+
+    if shards.len() != inputs.len() {
+        anyhow::bail!(
+            "❌ Can't merge sharded and unsharded results.\n\n\
+             💡 Solution: Pass only the output directories of 'pixelguard test --shard' runs."
+        );
+    }
+
+    let mut by_index: BTreeMap<usize, &PathBuf> = BTreeMap::new();
+    for (dir, shard) in &shards {
+        if shard.total != first.total {
+            anyhow::bail!(
+                "❌ Results come from runs split into different numbers of shards ({} and {}).\n\n\
+                 💡 Solution: Use the same --shard total for every job of a run.",
+                first,
+                shard
+            );
+        }
+        if let Some(other) = by_index.insert(shard.index, dir) {
+            anyhow::bail!(
+                "❌ Shard {} is given twice: {} and {}",
+                shard,
+                other.display(),
+                dir.display()
+            );
+        }
+    }
+
+    let missing: Vec<String> = (1..=first.total)
+        .filter(|index| !by_index.contains_key(index))
+        .map(|index| format!("{}/{}", index, first.total))
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "❌ Missing results for shard(s) {}.\n\n\
+             💡 Solution: Wait for every shard to finish and pass all of their output directories.",
+            missing.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Copies `{name}.png` from one image directory to another.
+fn copy_image(from: &Path, to: &Path, name: &str) -> Result<()> {
+    let source = from.join(format!("{}.png", name));
+    std::fs::copy(&source, to.join(format!("{}.png", name)))
+        .with_context(|| format!("Failed to copy {}", source.display()))?;
+    Ok(())
+}
+
+/// Writes a name-keyed JSON map, removing the file when the map is empty.
+fn write_map<T: Serialize>(path: &Path, map: &BTreeMap<String, T>) -> Result<()> {
+    if map.is_empty() {
+        let _ = std::fs::remove_file(path);
+        return Ok(());
+    }
+    std::fs::write(path, serde_json::to_string_pretty(map)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Returns true if both paths point at the same directory.
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::diff::ChangedShot;
+    use crate::report::generate_report;
+
+    #[test]
+    fn parses_and_displays_shards() {
+        let shard: Shard = "2/4".parse().unwrap();
+        assert_eq!(shard, Shard { index: 2, total: 4 });
+        assert_eq!(shard.to_string(), "2/4");
+
+        for invalid in ["0/4", "5/4", "2", "a/4", "1/0"] {
+            assert!(invalid.parse::<Shard>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn every_combination_belongs_to_exactly_one_shard() {
+        let shards: Vec<Shard> = (1..=4).map(|index| Shard { index, total: 4 }).collect();
+        let mut counts = [0; 4];
+        for story in 0..400 {
+            for viewport in ["mobile", "tablet", "desktop"] {
+                let owners: Vec<usize> = shards
+                    .iter()
+                    .filter(|shard| shard.contains(&format!("story-{}", story), viewport))
+                    .map(|shard| shard.index)
+                    .collect();
+                assert_eq!(owners.len(), 1);
+                counts[owners[0] - 1] += 1;
+            }
+        }
+        // 1200 combinations split roughly evenly
+        assert!(
+            counts.iter().all(|&count| (200..=400).contains(&count)),
+            "{:?}",
+            counts
+        );
+    }
+
+    /// Writes a shard's output directory with one unchanged and one changed screenshot.
+    fn write_shard(working_dir: &Path, shard: Shard, unchanged: &str, changed: &str) -> PathBuf {
+        let output_dir = working_dir.join(".pixelguard");
+        for (sub, name) in [
+            ("current", unchanged),
+            ("current", changed),
+            ("diff", changed),
+        ] {
+            std::fs::create_dir_all(output_dir.join(sub)).unwrap();
+            std::fs::write(output_dir.join(sub).join(format!("{}.png", name)), sub).unwrap();
+        }
+
+        let config = Config {
+            shard: Some(shard),
+            ..Default::default()
+        };
+        let result = DiffResult {
+            unchanged: vec![unchanged.to_string()],
+            changed: vec![ChangedShot {
+                name: changed.to_string(),
+                diff_percentage: 2.5,
+                ..Default::default()
+            }],
+            added: Vec::new(),
+            removed: Vec::new(),
+            stability: BTreeMap::new(),
+            page_errors: BTreeMap::new(),
+            a11y_changed: Vec::new(),
+            failed: Vec::new(),
+        };
+        generate_report(&config, &result, working_dir).unwrap();
+        output_dir
+    }
+
+    #[test]
+    fn merges_shard_outputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write_shard(
+            &dir.path().join("a"),
+            Shard { index: 1, total: 2 },
+            "a1",
+            "a2",
+        );
+        let second = write_shard(
+            &dir.path().join("b"),
+            Shard { index: 2, total: 2 },
+            "b1",
+            "b2",
+        );
+        let output_dir = dir.path().join("merged");
+
+        let (result, shards) = merge_results(&output_dir, &[first, second]).unwrap();
+
+        assert_eq!(result.unchanged, vec!["a1", "b1"]);
+        let changed: Vec<&str> = result.changed.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(changed, vec!["a2", "b2"]);
+        assert_eq!(result.changed[1].diff_percentage, 2.5);
+        assert_eq!(result.changed[1].diff_path, output_dir.join("diff/b2.png"));
+        assert_eq!(shards.len(), 2);
+        assert!(output_dir.join("current/b1.png").exists());
+        assert!(output_dir.join("diff/a2.png").exists());
+    }
+
+    #[test]
+    fn merge_requires_every_shard_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write_shard(
+            &dir.path().join("a"),
+            Shard { index: 1, total: 3 },
+            "a1",
+            "a2",
+        );
+        let second = write_shard(
+            &dir.path().join("b"),
+            Shard { index: 2, total: 3 },
+            "b1",
+            "b2",
+        );
+        let output_dir = dir.path().join("merged");
+
+        let err = merge_results(&output_dir, &[first.clone(), second])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Missing results for shard(s) 3/3"), "{}", err);
+
+        let err = merge_results(&output_dir, &[first.clone(), first])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("given twice"), "{}", err);
+    }
+}
@@ -0,0 +1,459 @@
+//! Dev server lifecycle for the `webServer` config.
+//!
+//! [`start_web_server`] spawns the configured command, polls its URL until it
+//! responds, and returns a [`WebServerHandle`]. Stopping or dropping the handle
+//! tears down the whole process tree, so servers started through `npm run` don't
+//! outlive Pixelguard.
+//!
+//! [`check_url`] and [`check_browser_endpoint`] are also used on their own by
+//! `pixelguard validate`.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tracing::{debug, info, warn};
+
+use crate::config::WebServer;
+
+/// How often the server URL is polled while waiting for it to start.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long the server gets to shut down after SIGTERM before it is killed.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Number of trailing output lines kept for error messages.
+const OUTPUT_TAIL_LINES: usize = 20;
+
+/// How long to wait for the rest of an exited server's output. A background
+/// process that inherited its pipes can keep them open indefinitely.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Outcome of requesting a URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reachability {
+    /// The URL answered with a success status
+    Ready,
+    /// The URL answered, but with a non-success status code
+    Status(u16),
+    /// The request failed (connection refused, timeout, DNS, ...)
+    Unreachable(String),
+}
+
+impl Reachability {
+    /// Returns true if the URL answered with a success status.
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Reachability::Ready)
+    }
+}
+
+/// Requests `url` once and reports whether it is serving.
+pub async fn check_url(url: &str) -> Reachability {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => return Reachability::Unreachable(e.to_string()),
+    };
+
+    match client.get(url).send().await {
+        Ok(response) if response.status().is_success() => Reachability::Ready,
+        Ok(response) => Reachability::Status(response.status().as_u16()),
+        Err(e) => Reachability::Unreachable(e.to_string()),
+    }
+}
+
+/// Checks that a `browser.wsEndpoint` or `browser.cdpEndpoint` is accepting connections.
+///
+/// An http(s) CDP endpoint must answer `/json/version`; for a ws(s) endpoint a
+/// TCP connection to its host and port is enough, as the WebSocket handshake
+/// is left to Playwright.
+pub async fn check_browser_endpoint(endpoint: &str) -> Reachability {
+    let url = match reqwest::Url::parse(endpoint) {
+        Ok(url) => url,
+        Err(e) => return Reachability::Unreachable(format!("invalid URL: {}", e)),
+    };
+
+    if matches!(url.scheme(), "http" | "https") {
+        return check_url(&format!("{}/json/version", endpoint.trim_end_matches('/'))).await;
+    }
+
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return Reachability::Unreachable(format!("no host or port in '{}'", endpoint));
+    };
+    let connect = tokio::net::TcpStream::connect((host, port));
+    match tokio::time::timeout(Duration::from_secs(5), connect).await {
+        Ok(Ok(_)) => Reachability::Ready,
+        Ok(Err(e)) => Reachability::Unreachable(e.to_string()),
+        Err(_) => Reachability::Unreachable("timed out after 5s".to_string()),
+    }
+}
+
+/// A running (or reused) dev server.
+///
+/// Dropping the handle kills the server's process tree; call [`WebServerHandle::stop`]
+/// to give it a chance to shut down cleanly first.
+#[derive(Debug)]
+pub struct WebServerHandle {
+    child: Option<Child>,
+    url: String,
+}
+
+impl WebServerHandle {
+    /// The URL the server answered on.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns true if Pixelguard started this server (rather than reusing one).
+    pub fn is_owned(&self) -> bool {
+        self.child.is_some()
+    }
+
+    /// Process ID of the started server, for use with [`kill_process_tree`].
+    pub fn pid(&self) -> Option<u32> {
+        self.child.as_ref().and_then(Child::id)
+    }
+
+    /// Stops the server: SIGTERM to the process tree, then SIGKILL if it is still
+    /// running after a grace period. Does nothing for a reused server.
+    pub async fn stop(mut self) {
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+
+        debug!("🛑 Stopping web server at {}", self.url);
+        terminate_tree(&child, false);
+        if tokio::time::timeout(SHUTDOWN_GRACE, child.wait())
+            .await
+            .is_err()
+        {
+            warn!(
+                "⚠️  Web server did not exit after {:?}; killing it",
+                SHUTDOWN_GRACE
+            );
+            terminate_tree(&child, true);
+            let _ = child.wait().await;
+        }
+    }
+}
+
+impl Drop for WebServerHandle {
+    fn drop(&mut self) {
+        if let Some(child) = &self.child {
+            terminate_tree(child, true);
+        }
+    }
+}
+
+/// Starts the configured dev server and waits until its URL responds.
+///
+/// If something is already serving the URL, it is reused when `reuseExisting` is
+/// set and reported as an error otherwise, since the tests would silently run
+/// against the wrong server.
+pub async fn start_web_server(
+    server: &WebServer,
+    base_url: &str,
+    working_dir: &Path,
+) -> Result<WebServerHandle> {
+    let url = server.url(base_url).to_string();
+    if url.is_empty() {
+        anyhow::bail!(
+            "❌ webServer needs a URL to wait for.\n\n\
+             💡 Solution: Set 'baseUrl' or 'webServer.url' in pixelguard.config.json"
+        );
+    }
+
+    if check_url(&url).await.is_ready() {
+        if server.reuse_existing {
+            info!("♻️  Reusing the server already running at {}", url);
+            return Ok(WebServerHandle { child: None, url });
+        }
+        anyhow::bail!(
+            "❌ {} is already in use, so the webServer command was not started.\n\n\
+             💡 Solutions:\n  \
+             • Stop the server that is already running\n  \
+             • Or set \"reuseExisting\": true in webServer to test against it",
+            url
+        );
+    }
+
+    info!("🚀 Starting web server: {}", server.command);
+    let mut child = shell_command(&server.command)
+        .current_dir(working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to start webServer command: {}", server.command))?;
+
+    // Keep the server's output out of Pixelguard's (CI mode prints JSON to stdout),
+    // but hold on to the end of it for error messages
+    let output = Arc::new(Mutex::new(VecDeque::new()));
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(tokio::spawn(collect_output(stdout, Arc::clone(&output))));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(tokio::spawn(collect_output(stderr, Arc::clone(&output))));
+    }
+    let output_tail = || {
+        let lines = output.lock().map(|l| l.clone()).unwrap_or_default();
+        Vec::from(lines).join("\n")
+    };
+
+    let mut handle = WebServerHandle {
+        child: Some(child),
+        url: url.clone(),
+    };
+    let timeout = server.timeout();
+    let started = Instant::now();
+
+    loop {
+        let exited = handle
+            .child
+            .as_mut()
+            .and_then(|child| child.try_wait().ok().flatten());
+        if let Some(status) = exited {
+            handle.child = None;
+            // The readers may not have seen the last lines yet
+            let deadline = tokio::time::Instant::now() + OUTPUT_DRAIN_TIMEOUT;
+            for reader in readers.drain(..) {
+                let _ = tokio::time::timeout_at(deadline, reader).await;
+            }
+            anyhow::bail!(
+                "❌ The webServer command exited with {} before {} responded.\n\n\
+                 🔍 Command: {}\n\
+                 🔍 Output:\n{}\n\n\
+                 💡 Solution: Run the command yourself to check that it starts a server.",
+                status,
+                url,
+                server.command,
+                output_tail()
+            );
+        }
+
+        if check_url(&url).await.is_ready() {
+            info!(
+                "✅ Web server ready at {} after {:.1}s",
+                url,
+                started.elapsed().as_secs_f64()
+            );
+            return Ok(handle);
+        }
+
+        if started.elapsed() >= timeout {
+            handle.stop().await;
+            anyhow::bail!(
+                "❌ Timed out after {}ms waiting for {} to respond.\n\n\
+                 🔍 Command: {}\n\
+                 🔍 Output:\n{}\n\n\
+                 💡 Solutions:\n  \
+                 • Check that webServer.url (or baseUrl) matches the port the server uses\n  \
+                 • Raise webServer.timeout for slow builds",
+                timeout.as_millis(),
+                url,
+                server.command,
+                output_tail()
+            );
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Builds a command that runs `command` through the platform shell, in its own
+/// process group on Unix so the whole tree can be signalled at once.
+fn shell_command(command: &str) -> Command {
+    #[cfg(unix)]
+    {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command).process_group(0);
+        cmd
+    }
+    #[cfg(not(unix))]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    }
+}
+
+/// Signals the child's whole process tree.
+fn terminate_tree(child: &Child, force: bool) {
+    if let Some(pid) = child.id() {
+        signal_tree(pid, force);
+    }
+}
+
+/// Kills the process tree started by a [`WebServerHandle`], given its [`pid`].
+///
+/// For signal handlers that can't own the handle.
+///
+/// [`pid`]: WebServerHandle::pid
+pub fn kill_process_tree(pid: u32) {
+    signal_tree(pid, true);
+}
+
+/// Sends SIGTERM (or SIGKILL when `force` is set) to the process group led by `pid`.
+fn signal_tree(pid: u32, force: bool) {
+    #[cfg(unix)]
+    let status = std::process::Command::new("kill")
+        .arg(if force { "-KILL" } else { "-TERM" })
+        .arg("--")
+        .arg(format!("-{}", pid))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    #[cfg(not(unix))]
+    let status = {
+        let _ = force;
+        std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+    };
+
+    if let Err(e) = status {
+        warn!("⚠️  Failed to stop web server (pid {}): {}", pid, e);
+    }
+}
+
+/// Logs a stream of server output at debug level, keeping the last few lines.
+async fn collect_output(stream: impl AsyncRead + Unpin, output: Arc<Mutex<VecDeque<String>>>) {
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        debug!("webServer: {}", line);
+        if let Ok(mut output) = output.lock() {
+            if output.len() == OUTPUT_TAIL_LINES {
+                output.pop_front();
+            }
+            output.push_back(line);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    /// Serves `200 OK` to every request on a random local port.
+    async fn serve_ok() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let _ = socket
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok",
+                    )
+                    .await;
+            }
+        });
+        url
+    }
+
+    /// A local URL with nothing listening on it.
+    async fn unused_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    fn server(command: &str, timeout: u64, reuse_existing: bool) -> WebServer {
+        WebServer {
+            command: command.to_string(),
+            url: None,
+            timeout: Some(timeout),
+            reuse_existing,
+        }
+    }
+
+    #[tokio::test]
+    async fn check_url_reports_reachability() {
+        assert_eq!(check_url(&serve_ok().await).await, Reachability::Ready);
+        assert!(matches!(
+            check_url(&unused_url().await).await,
+            Reachability::Unreachable(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn check_browser_endpoint_reports_reachability() {
+        let http = serve_ok().await;
+        assert_eq!(check_browser_endpoint(&http).await, Reachability::Ready);
+        let ws = http.replacen("http", "ws", 1);
+        assert_eq!(check_browser_endpoint(&ws).await, Reachability::Ready);
+
+        let unused = unused_url().await.replacen("http", "ws", 1);
+        assert!(matches!(
+            check_browser_endpoint(&unused).await,
+            Reachability::Unreachable(_)
+        ));
+        assert!(matches!(
+            check_browser_endpoint("not a url").await,
+            Reachability::Unreachable(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn reuses_running_server_only_when_allowed() {
+        let url = serve_ok().await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let handle = start_web_server(&server("exit 1", 1000, true), &url, dir.path())
+            .await
+            .unwrap();
+        assert!(!handle.is_owned());
+        assert_eq!(handle.url(), url);
+
+        let err = start_web_server(&server("exit 1", 1000, false), &url, dir.path())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already in use"));
+    }
+
+    #[tokio::test]
+    async fn reports_command_that_exits_early() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = start_web_server(
+            &server("echo port busy >&2; exit 3", 5000, false),
+            &unused_url().await,
+            dir.path(),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+
+        assert!(err.contains("exited"));
+        assert!(err.contains("port busy"));
+    }
+
+    #[tokio::test]
+    async fn times_out_and_stops_the_process_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("still-running");
+        // The grandchild would create the marker if it survived the timeout
+        let command = format!("(sleep 2 && touch {}) & wait", marker.display());
+
+        let err = start_web_server(
+            &server(&command, 300, false),
+            &unused_url().await,
+            dir.path(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Timed out"));
+
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        assert!(!marker.exists());
+    }
+}
@@ -0,0 +1,81 @@
+// Reproduces the benchmark table in docs/performance.md.
+//
+// Start `npm run serve` first, then run:
+//   npm run bench
+//   npm run bench -- --shots 10,50,100 --concurrency 4,8 --slow 0.1
+//
+// For each shots × concurrency pair, this writes pixelguard.bench.json, records a
+// baseline, then times a second `pixelguard test --ci` run against it.
+
+import { spawnSync } from 'node:child_process';
+import fs from 'node:fs';
+
+const PIXELGUARD = process.env.PIXELGUARD ?? '../../target/release/pixelguard';
+const BASE_URL = `http://localhost:${process.env.PORT ?? 4400}`;
+// How long a slow page takes to respond, in milliseconds
+const SLOW_DELAY = 1500;
+
+function option(name, fallback) {
+	const index = process.argv.indexOf(`--${name}`);
+	return index === -1 ? fallback : process.argv[index + 1];
+}
+
+const shotCounts = option('shots', '10,50,100,500').split(',').map(Number);
+const concurrencies = option('concurrency', '4,8').split(',').map(Number);
+// Fraction of shots that respond slowly, spread evenly through the list
+const slowFraction = Number(option('slow', '0.1'));
+
+function writeConfig(shots, concurrency) {
+	const slowEvery = slowFraction > 0 ? Math.round(1 / slowFraction) : 0;
+	const config = {
+		baseUrl: BASE_URL,
+		outputDir: `.pixelguard-bench/${shots}x${concurrency}`,
+		concurrency,
+		// The published numbers use shared browser contexts, the default
+		reuseContexts: true,
+		shots: Array.from({ length: shots }, (_, i) => ({
+			name: `shot-${i}`,
+			path: slowEvery && i % slowEvery === 0 ? `/shot/${i}?delay=${SLOW_DELAY}` : `/shot/${i}`,
+		})),
+	};
+	fs.writeFileSync('pixelguard.bench.json', JSON.stringify(config, null, '\t'));
+}
+
+function pixelguard(args) {
+	const started = Date.now();
+	const result = spawnSync(PIXELGUARD, [...args, '--config', 'pixelguard.bench.json'], {
+		encoding: 'utf8',
+	});
+	if (result.error) {
+		throw result.error;
+	}
+	const line = result.stdout.trim().split('\n').pop();
+	let output = {};
+	try {
+		output = JSON.parse(line);
+	} catch {
+		console.error(result.stderr);
+		throw new Error(`pixelguard ${args.join(' ')} did not print a CI result`);
+	}
+	return { output, totalMs: Date.now() - started };
+}
+
+const seconds = ms => `${(ms / 1000).toFixed(1)}s`;
+
+console.log('| Shots | Concurrency | Capture Time | Diff Time | Total |');
+console.log('|-------|-------------|--------------|-----------|-------|');
+
+for (const shots of shotCounts) {
+	for (const concurrency of concurrencies) {
+		writeConfig(shots, concurrency);
+		fs.rmSync(`.pixelguard-bench/${shots}x${concurrency}`, { recursive: true, force: true });
+
+		pixelguard(['test', '--ci', '--update']);
+		const { output, totalMs } = pixelguard(['test', '--ci']);
+
+		const captureMs = output.captureMs ?? 0;
+		console.log(
+			`| ${shots} | ${concurrency} | ${seconds(captureMs)} | ${seconds(totalMs - captureMs)} | ${seconds(totalMs)} |`,
+		);
+	}
+}
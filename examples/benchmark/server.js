@@ -0,0 +1,53 @@
+// Serves deterministic pages for the capture benchmark.
+//
+// GET /shot/<n>            a page of cards, identical on every run
+// GET /shot/<n>?delay=<ms> the same page, answered after <ms> to simulate a slow story
+
+import http from 'node:http';
+
+const PORT = Number(process.env.PORT ?? 4400);
+
+function renderPage(n) {
+	const cards = Array.from({ length: 12 }, (_, i) => {
+		const hue = (n * 37 + i * 29) % 360;
+		return `<div class="card" style="background: hsl(${hue} 60% 85%)"><h2>Card ${n}.${i}</h2><p>Benchmark content for shot ${n}.</p></div>`;
+	}).join('\n');
+
+	return `<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Shot ${n}</title>
+<style>
+	body { margin: 0; padding: 24px; font-family: sans-serif; background: #fafafa; }
+	.grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(220px, 1fr)); gap: 16px; }
+	.card { border-radius: 8px; padding: 16px; }
+	h2 { margin: 0 0 8px; font-size: 18px; }
+</style>
+</head>
+<body>
+<h1>Shot ${n}</h1>
+<div class="grid">
+${cards}
+</div>
+</body>
+</html>`;
+}
+
+const server = http.createServer((req, res) => {
+	const url = new URL(req.url, `http://localhost:${PORT}`);
+	const match = url.pathname.match(/^\/shot\/(\d+)$/);
+	if (!match) {
+		res.writeHead(404).end('Not found');
+		return;
+	}
+
+	const delay = Number(url.searchParams.get('delay') ?? 0);
+	setTimeout(() => {
+		res.writeHead(200, { 'Content-Type': 'text/html' }).end(renderPage(Number(match[1])));
+	}, delay);
+});
+
+server.listen(PORT, () => {
+	console.log(`Benchmark pages at http://localhost:${PORT}/shot/<n>`);
+});
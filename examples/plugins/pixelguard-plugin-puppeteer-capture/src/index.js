@@ -29,6 +29,7 @@ const fs = require("fs");
  * @param {string} input.baseUrl - Base URL of the dev server
  * @param {Object} input.viewport - Viewport dimensions
  * @param {string} input.outputDir - Output directory for screenshots
+ * @param {boolean} input.fullPage - Config-level fullPage (a shot's own fullPage wins)
  * @param {Object} input.options - Plugin options
  * @returns {Promise<{captured: Array, failed: Array}>}
  */
@@ -92,7 +93,7 @@ async function capture(input) {
 				const screenshotPath = path.join(outputDir, `${shot.name}.png`);
 				await page.screenshot({
 					path: screenshotPath,
-					fullPage: options.fullPage || false,
+					fullPage: shot.fullPage ?? options.fullPage ?? input.fullPage ?? false,
 					type: "png",
 				});
 